@@ -1,41 +1,313 @@
-use hex_literal::hex;
 use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
 use std::process::exit;
 
-use drand_verify::{derive_randomness, G1Pubkey, Pubkey};
+use drand_verify::classify::{classify, PossibleKind};
+use drand_verify::descriptor::NetworkDescriptor;
+use drand_verify::error_json::ErrorReport;
+use drand_verify::networks::{self, NetworkLookupError};
+use drand_verify::{
+    decode_signature, derive_randomness, Encoding, ErrorCode, G1Pubkey, G2PubkeyFastnet,
+    G2PubkeyRfc, Pubkey, Scheme, VerificationError,
+};
 
-/// Public key League of Entropy Mainnet (curl -sS https://drand.cloudflare.com/info)
-const PK_LEO_MAINNET: [u8; 48] = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+const USAGE: &str = "Usage: drand_verify [--network <name> | --network-file <file>] [--output text|json] [--encoding hex|base64] <round> [<previous_signature>] <signature>
+       drand_verify [--network <name> | --network-file <file>] [--output text|json] [--encoding hex|base64] --input <file or - for stdin>";
+
+fn verify_with<P: Pubkey>(
+    network: &NetworkDescriptor,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerificationError> {
+    let pk = P::from_variable(&network.public_key)
+        .map_err(|err| VerificationError::invalid_point("public_key", err))?;
+    pk.verify(round, previous_signature, signature)
+}
+
+/// An error verifying a beacon with its stable error code
+struct CliError {
+    code: ErrorCode,
+    /// The input that caused the error
+    field: String,
+    msg: String,
+}
+
+impl CliError {
+    fn invalid_encoding(field: &str, msg: String) -> Self {
+        Self {
+            code: ErrorCode::InvalidEncoding,
+            field: field.to_string(),
+            msg,
+        }
+    }
+
+    /// Renders the error as text or as an [`ErrorReport`] in JSON
+    fn render(&self, json: bool) -> String {
+        if json {
+            ErrorReport::new(self.code, self.msg.as_str())
+                .with_field(self.field.as_str())
+                .to_json()
+        } else {
+            self.msg.clone()
+        }
+    }
+
+    /// The process exit code for this error
+    fn exit_code(&self) -> i32 {
+        10 + i32::from(self.code.code())
+    }
+}
+
+/// Verifies one beacon given as hex or base64 strings. Returns the randomness for valid beacons.
+fn verify_encoded(
+    network: &NetworkDescriptor,
+    encoding: Encoding,
+    round: &str,
+    previous_signature: &str,
+    signature: &str,
+) -> Result<Option<[u8; 32]>, CliError> {
+    let round = round
+        .parse::<u64>()
+        .map_err(|err| CliError::invalid_encoding("round", format!("Invalid round: {}", err)))?;
+    let previous_signature = decode_signature(previous_signature, encoding).map_err(|err| {
+        CliError::invalid_encoding(
+            "previous_signature",
+            format!("Invalid previous signature: {}", err),
+        )
+    })?;
+    let signature = decode_signature(signature, encoding).map_err(|err| {
+        CliError::invalid_encoding("signature", format!("Invalid signature: {}", err))
+    })?;
+
+    let valid = match network.scheme {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+            verify_with::<G1Pubkey>(network, round, &previous_signature, &signature)
+        }
+        Scheme::BlsUnchainedOnG1 => {
+            verify_with::<G2PubkeyFastnet>(network, round, &previous_signature, &signature)
+        }
+        Scheme::BlsUnchainedG1Rfc9380 => {
+            verify_with::<G2PubkeyRfc>(network, round, &previous_signature, &signature)
+        }
+    }
+    .map_err(|err| CliError {
+        code: err.code(),
+        field: match &err {
+            VerificationError::InvalidPoint { field, .. } => field.clone(),
+        },
+        msg: format!(
+            "Error during verification: {}{}",
+            err,
+            signature_hint(network, &signature)
+        ),
+    })?;
+
+    Ok(valid.then(|| derive_randomness(&signature)))
+}
+
+/// Explains what the signature could be if it is not a signature of the network
+fn signature_hint(network: &NetworkDescriptor, signature: &[u8]) -> &'static str {
+    let expected = if network.public_key.len() == 48 {
+        PossibleKind::G2Signature
+    } else {
+        PossibleKind::G1Signature
+    };
+    let kinds = classify(signature);
+    if kinds.contains(&expected) {
+        ""
+    } else if kinds.contains(&PossibleKind::Hash) {
+        " (this looks like a randomness value or hash, not a signature)"
+    } else if !kinds.is_empty() {
+        " (this is a signature of a different scheme, check the --network argument)"
+    } else {
+        ""
+    }
+}
+
+/// Splits the positional arguments of one beacon.
+/// For unchained networks the previous signature can be omitted.
+fn split_beacon<'a>(parts: &[&'a str]) -> Option<(&'a str, &'a str, &'a str)> {
+    match parts {
+        [round, previous_signature, signature] => Some((round, previous_signature, signature)),
+        [round, signature] => Some((round, "", signature)),
+        _ => None,
+    }
+}
+
+/// Removes `name <value>` from the arguments and returns the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let pos = match args.iter().position(|arg| arg == name) {
+        Some(pos) => pos,
+        None => return Ok(None),
+    };
+    if pos + 1 >= args.len() {
+        return Err(format!("Missing value for {}", name));
+    }
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+    Ok(Some(value))
+}
+
+/// Verifies one beacon per line of the form `<round> [<previous_signature>] <signature>`.
+/// Empty lines and lines starting with `#` are ignored.
+fn verify_lines(
+    network: &NetworkDescriptor,
+    encoding: Encoding,
+    input: impl BufRead,
+    json: bool,
+) -> i32 {
+    let mut code = 0;
+    for line in input.lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(err) => {
+                eprintln!("Error reading input: {}", err);
+                return 100;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (round, previous_signature, signature) = match split_beacon(&parts) {
+            Some(parts) => parts,
+            None => {
+                eprintln!("Invalid line: {}", line);
+                code = code.max(100);
+                continue;
+            }
+        };
+        match verify_encoded(network, encoding, round, previous_signature, signature) {
+            Ok(Some(randomness)) => println!("{} ok {}", round, hex::encode(randomness)),
+            Ok(None) => {
+                println!("{} failed", round);
+                code = code.max(1);
+            }
+            Err(err) => {
+                eprintln!("{} error: {}", round, err.render(json));
+                code = code.max(err.exit_code());
+            }
+        }
+    }
+    code
+}
 
 fn main_impl() -> i32 {
-    let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+    let mut args: Vec<String> = env::args().skip(1).collect();
 
-    let args: Vec<String> = env::args().collect();
-    if args.len() != 4 {
-        eprintln!("Must be called with 3 arguments (round, previous_signature, signature)");
-        return 100;
+    let (network, network_file, input, output, encoding) = match (
+        take_option(&mut args, "--network"),
+        take_option(&mut args, "--network-file"),
+        take_option(&mut args, "--input"),
+        take_option(&mut args, "--output"),
+        take_option(&mut args, "--encoding"),
+    ) {
+        (Ok(network), Ok(network_file), Ok(input), Ok(output), Ok(encoding)) => {
+            (network, network_file, input, output, encoding)
+        }
+        (Err(err), _, _, _, _)
+        | (_, Err(err), _, _, _)
+        | (_, _, Err(err), _, _)
+        | (_, _, _, Err(err), _)
+        | (_, _, _, _, Err(err)) => {
+            eprintln!("{}", err);
+            return 100;
+        }
+    };
+    let json = match output.as_deref() {
+        None | Some("text") => false,
+        Some("json") => true,
+        Some(other) => {
+            eprintln!("Unknown output format '{}'. Use text or json.", other);
+            return 100;
+        }
+    };
+    let encoding = match encoding.as_deref().map(str::parse::<Encoding>) {
+        None => Encoding::Hex,
+        Some(Ok(encoding)) if encoding.is_text() => encoding,
+        Some(_) => {
+            eprintln!("Unknown encoding. Use hex or base64.");
+            return 100;
+        }
+    };
+
+    let network = match (network, network_file) {
+        (Some(_), Some(_)) => {
+            eprintln!("{}", USAGE);
+            return 100;
+        }
+        // The League of Entropy mainnet is the default for backwards compatibility
+        (None, None) => NetworkDescriptor::from(&networks::MAINNET),
+        (Some(name), None) => match networks::by_name(&name) {
+            Ok(network) => NetworkDescriptor::from(network),
+            Err(NetworkLookupError::ChecksumMismatch(err)) => {
+                eprintln!("{}", err);
+                return 100;
+            }
+            Err(NetworkLookupError::UnknownNetwork { .. }) => {
+                let known: Vec<&str> = networks::KNOWN_NETWORKS.iter().map(|n| n.name).collect();
+                eprintln!(
+                    "Unknown network '{}'. Known networks: {}",
+                    name,
+                    known.join(", ")
+                );
+                return 100;
+            }
+        },
+        (None, Some(path)) => match NetworkDescriptor::load(&path) {
+            Ok(descriptor) => descriptor,
+            Err(err) => {
+                eprintln!("Cannot load network file {}: {}", path, err);
+                return 100;
+            }
+        },
+    };
+    let network = &network;
+
+    if let Some(input) = input {
+        if !args.is_empty() {
+            eprintln!("{}", USAGE);
+            return 100;
+        }
+        return if input == "-" {
+            verify_lines(network, encoding, io::stdin().lock(), json)
+        } else {
+            match File::open(&input) {
+                Ok(file) => verify_lines(network, encoding, BufReader::new(file), json),
+                Err(err) => {
+                    eprintln!("Cannot open {}: {}", input, err);
+                    100
+                }
+            }
+        };
     }
 
     // See https://drand.cloudflare.com/public/72785 for example data of the three inputs
-    let round = args[1].parse::<u64>().unwrap();
-    let previous_signature = hex::decode(&args[2]).unwrap();
-    let signature = hex::decode(&args[3]).unwrap();
+    let parts: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (round, previous_signature, signature) = match split_beacon(&parts) {
+        Some(parts) => parts,
+        None => {
+            eprintln!("{}", USAGE);
+            return 100;
+        }
+    };
 
-    match pk.verify(round, &previous_signature, &signature) {
+    match verify_encoded(network, encoding, round, previous_signature, signature) {
         Err(err) => {
-            eprintln!("Error during verification: {}", err);
-            12
-        }
-        Ok(valid) => {
-            if valid {
-                println!("Verification succeeded");
-                let randomness = derive_randomness(&signature);
-                println!("Randomness: {}", hex::encode(randomness));
-                0
-            } else {
-                println!("Verification failed");
-                1
-            }
+            eprintln!("{}", err.render(json));
+            err.exit_code()
+        }
+        Ok(Some(randomness)) => {
+            println!("Verification succeeded");
+            println!("Randomness: {}", hex::encode(randomness));
+            0
+        }
+        Ok(None) => {
+            println!("Verification failed");
+            1
         }
     }
 }
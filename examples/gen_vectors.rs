@@ -0,0 +1,108 @@
+use std::env;
+use std::process::exit;
+
+use drand_verify::signer::{SecretKey, Sign};
+use drand_verify::{derive_randomness, G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc};
+use serde_json::json;
+
+const USAGE: &str = "Usage: gen_vectors --scheme <chained|unchained|fastnet|rfc> --rounds <from>..<to> [--seed <seed>]";
+
+/// Parses `a..b` (exclusive) and `a..=b` (inclusive) ranges
+fn parse_rounds(input: &str) -> Option<(u64, u64)> {
+    let (from, to) = input.split_once("..")?;
+    let from = from.parse::<u64>().ok()?;
+    let to = match to.strip_prefix('=') {
+        Some(to) => to.parse::<u64>().ok()?,
+        None => to.parse::<u64>().ok()?.checked_sub(1)?,
+    };
+    Some((from, to))
+}
+
+fn print_vectors<P: Sign>(scheme_id: &str, chained: bool, seed: &str, from: u64, to: u64) {
+    let sk = SecretKey::from_seed(seed.as_bytes());
+    // For chained schemes, the first beacon links to an unchained signature of the round before
+    let mut previous_signature = if chained {
+        sk.sign::<P>(from.saturating_sub(1), b"")
+    } else {
+        vec![]
+    };
+
+    let mut beacons = Vec::new();
+    for round in from..=to {
+        let signature = sk.sign::<P>(round, &previous_signature);
+        beacons.push(json!({
+            "round": round,
+            "previous_signature": hex::encode(&previous_signature),
+            "signature": hex::encode(&signature),
+            "randomness": hex::encode(derive_randomness(&signature)),
+        }));
+        if chained {
+            previous_signature = signature;
+        }
+    }
+
+    let vectors = json!({
+        "scheme": scheme_id,
+        "seed": seed,
+        "public_key": hex::encode(sk.public_key::<P>()),
+        "beacons": beacons,
+    });
+    println!("{}", serde_json::to_string_pretty(&vectors).unwrap());
+}
+
+fn main_impl() -> i32 {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut scheme = None;
+    let mut rounds = None;
+    let mut seed = "drand-verify test vectors".to_string();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let value = match iter.next() {
+            Some(value) => value,
+            None => {
+                eprintln!("{}", USAGE);
+                return 100;
+            }
+        };
+        match arg.as_str() {
+            "--scheme" => scheme = Some(value.clone()),
+            "--rounds" => rounds = parse_rounds(value),
+            "--seed" => seed = value.clone(),
+            _ => {
+                eprintln!("{}", USAGE);
+                return 100;
+            }
+        }
+    }
+
+    let (scheme, (from, to)) = match (scheme, rounds) {
+        (Some(scheme), Some(rounds)) => (scheme, rounds),
+        _ => {
+            eprintln!("{}", USAGE);
+            return 100;
+        }
+    };
+    if from == 0 || from > to {
+        eprintln!("Round range must be non-empty and start at 1 or higher");
+        return 100;
+    }
+
+    match scheme.as_str() {
+        "chained" => print_vectors::<G1Pubkey>("pedersen-bls-chained", true, &seed, from, to),
+        "unchained" => print_vectors::<G1Pubkey>("pedersen-bls-unchained", false, &seed, from, to),
+        "fastnet" => {
+            print_vectors::<G2PubkeyFastnet>("bls-unchained-on-g1", false, &seed, from, to)
+        }
+        "rfc" => print_vectors::<G2PubkeyRfc>("bls-unchained-g1-rfc9380", false, &seed, from, to),
+        _ => {
+            eprintln!("{}", USAGE);
+            return 100;
+        }
+    }
+    0
+}
+
+fn main() {
+    exit(main_impl())
+}
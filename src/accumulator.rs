@@ -0,0 +1,332 @@
+//! A Merkle tree over verified rounds.
+//!
+//! An archive service appends every beacon it verified to a [`RoundAccumulator`] and publishes
+//! the [`root`](RoundAccumulator::root). For any stored round it can then hand out an
+//! [`InclusionProof`] which convinces a client that the round is part of the verified history
+//! behind that root.
+//!
+//! The tree is the Merkle tree of RFC 9162, section 2.1 (Certificate Transparency) with
+//! SHA-256. The leaves are the [canonical bytes](VerifiedBeacon::canonical_bytes) of the
+//! beacons in the order they were appended:
+//!
+//! ```text
+//! leaf hash = SHA-256(0x00 || canonical_bytes)
+//! node hash = SHA-256(0x01 || left || right)
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::beacon::VerifiedBeacon;
+use crate::chain_hash::ChainHash;
+
+/// The error returned by [`RoundAccumulator::append`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccumulatorError {
+    /// Rounds must be appended in increasing order
+    NotIncreasing { last: u64, actual: u64 },
+}
+
+impl fmt::Display for AccumulatorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccumulatorError::NotIncreasing { last, actual } => write!(
+                f,
+                "Round {} must be higher than the last appended round {}",
+                actual, last
+            ),
+        }
+    }
+}
+
+impl Error for AccumulatorError {}
+
+/// An append-only Merkle tree over the verified beacons of one chain.
+///
+/// Appending a round and computing the root cost `O(log n)` hashes. All inner nodes are kept
+/// in memory, which is about 64 bytes per round.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::accumulator::RoundAccumulator;
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let beacon = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+///
+/// // The archive service
+/// let mut accumulator = RoundAccumulator::new(networks::QUICKNET.chain_hash);
+/// accumulator.append(&beacon).unwrap();
+/// let root = accumulator.root();
+/// let proof = accumulator.prove(123).unwrap();
+///
+/// // A client knowing the root
+/// assert!(proof.verify(&networks::QUICKNET.chain_hash, &beacon, &root));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RoundAccumulator {
+    chain_hash: ChainHash,
+    /// The appended rounds, in increasing order
+    rounds: Vec<u64>,
+    /// `levels[h][i]` is the root of the complete subtree of the leaves `i * 2^h .. (i + 1) * 2^h`
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl RoundAccumulator {
+    pub fn new(chain_hash: ChainHash) -> Self {
+        Self {
+            chain_hash,
+            rounds: Vec::new(),
+            levels: vec![Vec::new()],
+        }
+    }
+
+    pub fn chain_hash(&self) -> ChainHash {
+        self.chain_hash
+    }
+
+    /// The number of appended rounds
+    pub fn len(&self) -> u64 {
+        self.rounds.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rounds.is_empty()
+    }
+
+    /// Appends a beacon. Its round must be higher than all rounds appended before.
+    ///
+    /// Returns the index of the new leaf.
+    pub fn append(&mut self, beacon: &VerifiedBeacon) -> Result<u64, AccumulatorError> {
+        if let Some(&last) = self.rounds.last() {
+            if beacon.round() <= last {
+                return Err(AccumulatorError::NotIncreasing {
+                    last,
+                    actual: beacon.round(),
+                });
+            }
+        }
+        let index = self.len();
+        self.rounds.push(beacon.round());
+
+        let mut hash = leaf_hash(&beacon.canonical_bytes(&self.chain_hash));
+        let mut height = 0;
+        loop {
+            self.levels[height].push(hash);
+            let nodes = &self.levels[height];
+            if nodes.len() % 2 == 1 {
+                break;
+            }
+            hash = node_hash(&nodes[nodes.len() - 2], &nodes[nodes.len() - 1]);
+            height += 1;
+            if self.levels.len() == height {
+                self.levels.push(Vec::new());
+            }
+        }
+        Ok(index)
+    }
+
+    /// The root of the tree. The root of an empty tree is the SHA-256 hash of the empty string.
+    pub fn root(&self) -> [u8; 32] {
+        match self.len() {
+            0 => Sha256::digest(b"").into(),
+            size => self.subtree_root(0, size),
+        }
+    }
+
+    /// Creates a proof that the round is included in the tree of the current size, or returns
+    /// `None` if the round was not appended
+    pub fn prove(&self, round: u64) -> Option<InclusionProof> {
+        let index = self.rounds.binary_search(&round).ok()? as u64;
+        let mut path = Vec::new();
+        self.path(index, 0, self.len(), &mut path);
+        Some(InclusionProof {
+            index,
+            tree_size: self.len(),
+            path,
+        })
+    }
+
+    /// The root of the leaves `start .. start + size`, where `start` is a multiple of the
+    /// largest power of two not exceeding `size`
+    fn subtree_root(&self, start: u64, size: u64) -> [u8; 32] {
+        if size.is_power_of_two() {
+            let height = size.trailing_zeros();
+            return self.levels[height as usize][(start >> height) as usize];
+        }
+        let split = split_point(size);
+        node_hash(
+            &self.subtree_root(start, split),
+            &self.subtree_root(start + split, size - split),
+        )
+    }
+
+    /// Appends the audit path of leaf `index` in the subtree `start .. start + size`,
+    /// starting at the leaf (RFC 9162, section 2.1.3.1)
+    fn path(&self, index: u64, start: u64, size: u64, out: &mut Vec<[u8; 32]>) {
+        if size <= 1 {
+            return;
+        }
+        let split = split_point(size);
+        if index < split {
+            self.path(index, start, split, out);
+            out.push(self.subtree_root(start + split, size - split));
+        } else {
+            self.path(index - split, start + split, size - split, out);
+            out.push(self.subtree_root(start, split));
+        }
+    }
+}
+
+/// A proof that a beacon is the leaf `index` of a tree with `tree_size` leaves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InclusionProof {
+    pub index: u64,
+    pub tree_size: u64,
+    /// The sibling hashes from the leaf to the root
+    pub path: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    /// Returns true if the beacon of the given chain is included in the tree with the given
+    /// root (RFC 9162, section 2.1.3.2).
+    ///
+    /// The beacon has to be verified separately. This only proves that the archive
+    /// included it in its history. Like the root, `tree_size` should be obtained from the
+    /// archive's published state rather than trusted from the proof.
+    pub fn verify(&self, chain_hash: &ChainHash, beacon: &VerifiedBeacon, root: &[u8; 32]) -> bool {
+        if self.index >= self.tree_size {
+            return false;
+        }
+        let mut f = self.index;
+        let mut s = self.tree_size - 1;
+        let mut hash = leaf_hash(&beacon.canonical_bytes(chain_hash));
+        for sibling in &self.path {
+            if s == 0 {
+                return false;
+            }
+            if f % 2 == 1 || f == s {
+                hash = node_hash(sibling, &hash);
+                while f % 2 == 0 && f != 0 {
+                    f >>= 1;
+                    s >>= 1;
+                }
+            } else {
+                hash = node_hash(&hash, sibling);
+            }
+            f >>= 1;
+            s >>= 1;
+        }
+        s == 0 && hash == *root
+    }
+}
+
+/// The largest power of two smaller than `size` (which must be at least 2)
+fn split_point(size: u64) -> u64 {
+    1 << (63 - (size - 1).leading_zeros())
+}
+
+fn leaf_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHAIN_HASH: ChainHash = ChainHash::new([0xab; 32]);
+
+    fn beacon(round: u64) -> VerifiedBeacon {
+        VerifiedBeacon::new(round, &[], &[round as u8; 48])
+    }
+
+    /// MTH of RFC 9162, section 2.1.1
+    fn reference_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+        match leaves.len() {
+            0 => Sha256::digest(b"").into(),
+            1 => leaves[0],
+            n => {
+                let split = split_point(n as u64) as usize;
+                node_hash(
+                    &reference_root(&leaves[..split]),
+                    &reference_root(&leaves[split..]),
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn root_matches_rfc_9162() {
+        let mut accumulator = RoundAccumulator::new(CHAIN_HASH);
+        let mut leaves = Vec::new();
+        assert_eq!(accumulator.root(), reference_root(&leaves));
+        for round in 1..=33 {
+            assert_eq!(accumulator.append(&beacon(round)).unwrap(), round - 1);
+            leaves.push(leaf_hash(&beacon(round).canonical_bytes(&CHAIN_HASH)));
+            assert_eq!(accumulator.root(), reference_root(&leaves));
+        }
+        assert_eq!(accumulator.len(), 33);
+    }
+
+    #[test]
+    fn proofs_work() {
+        let mut accumulator = RoundAccumulator::new(CHAIN_HASH);
+        for size in 1..=17u64 {
+            // rounds with gaps
+            accumulator.append(&beacon(size * 3)).unwrap();
+            let root = accumulator.root();
+            for index in 0..size {
+                let round = (index + 1) * 3;
+                let proof = accumulator.prove(round).unwrap();
+                assert_eq!(proof.index, index);
+                assert_eq!(proof.tree_size, size);
+                assert!(proof.verify(&CHAIN_HASH, &beacon(round), &root));
+
+                // wrong beacon, chain or root
+                assert!(!proof.verify(&CHAIN_HASH, &beacon(round + 1), &root));
+                assert!(!proof.verify(&ChainHash::new([0; 32]), &beacon(round), &root));
+                assert!(!proof.verify(&CHAIN_HASH, &beacon(round), &[0; 32]));
+
+                // tampered proofs
+                let mut tampered = proof.clone();
+                tampered.path.push([0; 32]);
+                assert!(!tampered.verify(&CHAIN_HASH, &beacon(round), &root));
+                if let Some(first) = proof.path.first() {
+                    let mut tampered = proof.clone();
+                    tampered.path[0] = [first[0] ^ 1; 32];
+                    assert!(!tampered.verify(&CHAIN_HASH, &beacon(round), &root));
+                }
+            }
+        }
+        assert_eq!(accumulator.prove(4), None);
+    }
+
+    #[test]
+    fn append_requires_increasing_rounds() {
+        let mut accumulator = RoundAccumulator::new(CHAIN_HASH);
+        accumulator.append(&beacon(5)).unwrap();
+        let err = accumulator.append(&beacon(5)).unwrap_err();
+        assert_eq!(err, AccumulatorError::NotIncreasing { last: 5, actual: 5 });
+        assert_eq!(
+            err.to_string(),
+            "Round 5 must be higher than the last appended round 5"
+        );
+        assert_eq!(accumulator.len(), 1);
+    }
+}
@@ -0,0 +1,244 @@
+//! Aggregation of beacon signatures across rounds.
+//!
+//! The BLS signatures of many rounds can be added up to a single aggregate signature of the
+//! same size. An archive can store one aggregate per day together with the round numbers and
+//! check the aggregate with a single pairing check.
+//!
+//! All beacons of a network are signed by the same key, so the aggregate is valid if and only
+//! if e(g, aggregate) equals e(pk, H(m_1) + … + H(m_n)). The verification costs one hash to the
+//! curve per round but only a single pairing check.
+//!
+//! A valid aggregate does not verify the individual signatures. With a single signer,
+//! σ1 + Δ and σ2 − Δ add up to the same aggregate as σ1 and σ2 for any point Δ. The randomness
+//! is derived from the individual signatures, so an archive that stores them must not rely on
+//! the aggregate. Use [`DrandPubkey::verify_batch`](crate::DrandPubkey::verify_batch) or
+//! [`DrandPubkey::verify_beacon_batch`](crate::DrandPubkey::verify_beacon_batch) instead,
+//! which also need a single pairing check but multiply each signature with a random scalar so
+//! that every signature has to be valid. If the check of an aggregate fails, it cannot identify
+//! the invalid signature either.
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::ops::Add;
+
+use bls12_381::{G1Affine, G1Projective, G2Affine, G2Projective};
+
+use crate::signature::{Signature, SignaturePoint};
+use crate::verify::{message, Pubkey, VerificationError};
+
+#[derive(Debug)]
+pub enum AggregateError {
+    /// Nothing to aggregate or verify
+    Empty,
+    /// The signatures are not all on the same group
+    MixedGroups,
+    /// A round was given more than once. Aggregates are only defined for distinct messages.
+    DuplicateRound {
+        round: u64,
+    },
+    Verification(VerificationError),
+}
+
+impl fmt::Display for AggregateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AggregateError::Empty => write!(f, "No signatures to aggregate"),
+            AggregateError::MixedGroups => {
+                write!(f, "Signatures must all be on the same group")
+            }
+            AggregateError::DuplicateRound { round } => write!(f, "Duplicate round {}", round),
+            AggregateError::Verification(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for AggregateError {}
+
+impl From<VerificationError> for AggregateError {
+    fn from(source: VerificationError) -> Self {
+        AggregateError::Verification(source)
+    }
+}
+
+/// Adds up signatures of the same group to an aggregate signature.
+///
+/// The order of the signatures does not matter.
+pub fn aggregate_signatures(signatures: &[Signature]) -> Result<Signature, AggregateError> {
+    let (first, rest) = signatures.split_first().ok_or(AggregateError::Empty)?;
+    let sum = match first.0 {
+        SignaturePoint::G1(point) => {
+            let mut sum = G1Projective::from(point);
+            for signature in rest {
+                sum += signature.g1().ok_or(AggregateError::MixedGroups)?;
+            }
+            SignaturePoint::G1(G1Affine::from(sum))
+        }
+        SignaturePoint::G2(point) => {
+            let mut sum = G2Projective::from(point);
+            for signature in rest {
+                sum += signature.g2().ok_or(AggregateError::MixedGroups)?;
+            }
+            SignaturePoint::G2(G2Affine::from(sum))
+        }
+    };
+    Ok(Signature(sum))
+}
+
+/// Verifies the aggregate of the signatures of the given rounds of an unchained network.
+///
+/// This does not verify the individual signatures, see the [module documentation](self).
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::aggregate::{aggregate_signatures, verify_aggregate};
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey, Signature};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signatures = [
+///     Signature::from_variable(&hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92")).unwrap(),
+/// ];
+///
+/// // Store the 48 bytes of the aggregate with the rounds
+/// let aggregate = aggregate_signatures(&signatures).unwrap().to_bytes();
+///
+/// let aggregate = Signature::from_variable(&aggregate).unwrap();
+/// assert!(verify_aggregate(&pk, &[123], &aggregate).unwrap());
+/// ```
+pub fn verify_aggregate<P>(
+    pubkey: &P,
+    rounds: &[u64],
+    aggregate: &Signature,
+) -> Result<bool, AggregateError>
+where
+    P: Pubkey,
+    P::Other: Add<Output = P::Other>,
+{
+    let beacons: Vec<(u64, &[u8])> = rounds.iter().map(|&round| (round, &[][..])).collect();
+    verify_aggregate_chained(pubkey, &beacons, aggregate)
+}
+
+/// Like [`verify_aggregate`] but for chained networks, where the message of each round
+/// includes the previous signature.
+///
+/// Pass pairs of round and previous signature.
+pub fn verify_aggregate_chained<P>(
+    pubkey: &P,
+    beacons: &[(u64, &[u8])],
+    aggregate: &Signature,
+) -> Result<bool, AggregateError>
+where
+    P: Pubkey,
+    P::Other: Add<Output = P::Other>,
+{
+    let mut rounds = BTreeSet::new();
+    let mut sum: Option<P::Other> = None;
+    for &(round, previous_signature) in beacons {
+        if !rounds.insert(round) {
+            return Err(AggregateError::DuplicateRound { round });
+        }
+        let msg_on_curve = P::msg_to_curve(&message(round, previous_signature));
+        sum = Some(match sum {
+            Some(sum) => sum + msg_on_curve,
+            None => msg_on_curve,
+        });
+    }
+    let sum = sum.ok_or(AggregateError::Empty)?;
+    Ok(pubkey.verify_step2_decompressed(aggregate, &sum)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G1Pubkey, G2PubkeyRfc};
+    use hex_literal::hex;
+
+    const PK_LEO_MAINNET: [u8; 48] = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+    const SIG_1336: [u8; 96] = hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2");
+    const SIG_1337: [u8; 96] = hex!("945b08dcb30e24da281ccf14a646f0630ceec515af5c5895e18cc1b19edd65d156b71c776a369af3487f1bc6af1062500b059e01095cc0eedce91713977d7735cac675554edfa0d0481bb991ed93d333d08286192c05bf6b65d20f23a37fc7bb");
+    const SIG_72784: [u8; 96] = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+    const SIG_72785: [u8; 96] = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+
+    #[test]
+    fn verify_aggregate_chained_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let signatures = [
+            Signature::from_variable(&SIG_1337).unwrap(),
+            Signature::from_variable(&SIG_72785).unwrap(),
+        ];
+        let aggregate = aggregate_signatures(&signatures).unwrap();
+        assert_eq!(aggregate.to_bytes().len(), 96);
+
+        let beacons = [(1337, &SIG_1336[..]), (72785, &SIG_72784[..])];
+        assert!(verify_aggregate_chained(&pk, &beacons, &aggregate).unwrap());
+
+        // order does not matter
+        let reversed = aggregate_signatures(&[signatures[1], signatures[0]]).unwrap();
+        assert_eq!(reversed, aggregate);
+
+        // wrong round
+        let beacons = [(1338, &SIG_1336[..]), (72785, &SIG_72784[..])];
+        assert!(!verify_aggregate_chained(&pk, &beacons, &aggregate).unwrap());
+
+        // missing round
+        assert!(!verify_aggregate_chained(&pk, &beacons[1..], &aggregate).unwrap());
+    }
+
+    #[test]
+    fn verify_aggregate_rejects_invalid_input() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let g2 = Signature::from_variable(&SIG_1337).unwrap();
+        let g1 = Signature::from_variable(&hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92")).unwrap();
+
+        assert!(matches!(
+            aggregate_signatures(&[]),
+            Err(AggregateError::Empty)
+        ));
+        assert!(matches!(
+            aggregate_signatures(&[g2, g1]),
+            Err(AggregateError::MixedGroups)
+        ));
+        assert!(matches!(
+            verify_aggregate(&pk, &[], &g2),
+            Err(AggregateError::Empty)
+        ));
+        assert!(matches!(
+            verify_aggregate(&pk, &[5, 6, 5], &g2),
+            Err(AggregateError::DuplicateRound { round: 5 })
+        ));
+        assert!(matches!(
+            verify_aggregate(&pk, &[5], &g1),
+            Err(AggregateError::Verification(_))
+        ));
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn verify_aggregate_works() {
+        use crate::signer::SecretKey;
+
+        let sk = SecretKey::from_seed(b"drand-verify test key");
+        let pk = G2PubkeyRfc::from_variable(&sk.public_key::<G2PubkeyRfc>()).unwrap();
+        let rounds: Vec<u64> = (1000..1100).collect();
+        let signatures: Vec<Signature> = rounds
+            .iter()
+            .map(|&round| Signature::from_variable(&sk.sign::<G2PubkeyRfc>(round, &[])).unwrap())
+            .collect();
+        let aggregate = aggregate_signatures(&signatures).unwrap();
+        assert_eq!(aggregate.to_bytes().len(), 48);
+        assert!(verify_aggregate(&pk, &rounds, &aggregate).unwrap());
+        assert!(!verify_aggregate(&pk, &rounds[1..], &aggregate).unwrap());
+    }
+
+    #[test]
+    fn verify_aggregate_works_for_single_round() {
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let signature = Signature::from_variable(&hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92")).unwrap();
+        let aggregate = aggregate_signatures(&[signature]).unwrap();
+        assert_eq!(aggregate, signature);
+        assert!(verify_aggregate(&pk, &[123], &aggregate).unwrap());
+        assert!(!verify_aggregate(&pk, &[124], &aggregate).unwrap());
+    }
+}
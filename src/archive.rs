@@ -0,0 +1,579 @@
+//! A compact binary file format to store and re-verify long beacon histories.
+//!
+//! An archive starts with a header containing the [`ChainInfo`] of the network followed by
+//! fixed-size records, one per beacon:
+//!
+//! | Field              | Size                                                        |
+//! |--------------------|-------------------------------------------------------------|
+//! | round              | 8 bytes, big endian                                         |
+//! | previous signature | signature size, only for chained schemes                    |
+//! | signature          | 48 bytes (signatures on G1) or 96 bytes (signatures on G2)  |
+//!
+//! The header consists of the magic bytes `DRANDARC`, the format version (1 byte),
+//! the scheme ID and public key (each prefixed by a 1 byte length), the chain hash
+//! (32 bytes), the genesis time and period (8 bytes big endian each) and the
+//! optional group hash (1 byte flag followed by 32 bytes if set).
+//!
+//! Records are sorted by round. A year of quicknet beacons takes about 590 MB.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use crate::audit::AuditReport;
+use crate::batch::DEFAULT_MAX_BATCH_SIZE;
+use crate::beacon::Beacon;
+use crate::chain_hash::ChainHash;
+use crate::chain_info::ChainInfo;
+use crate::outcome::VerificationOutcome;
+use crate::scheme::{Scheme, UnknownScheme};
+use crate::verify::{Group, Pubkey};
+
+const MAGIC: &[u8; 8] = b"DRANDARC";
+const VERSION: u8 = 1;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    Io(io::Error),
+    /// The data does not start with the archive magic bytes
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    UnknownScheme(UnknownScheme),
+    /// The header cannot be written or parsed
+    InvalidHeader {
+        msg: String,
+    },
+    /// A beacon does not fit the fixed record size of the archive
+    InvalidBeacon {
+        round: u64,
+        msg: String,
+    },
+    /// Rounds must be strictly increasing
+    RoundNotIncreasing {
+        previous: u64,
+        round: u64,
+    },
+    /// The archive ends in the middle of a record
+    Truncated,
+    /// The pubkey used for verification belongs to a different scheme than the archive
+    SchemeMismatch {
+        archive: Scheme,
+        pubkey: Scheme,
+    },
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Io(err) => write!(f, "I/O error: {}", err),
+            ArchiveError::InvalidMagic => write!(f, "Not a beacon archive"),
+            ArchiveError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported archive version: {}", version)
+            }
+            ArchiveError::UnknownScheme(err) => err.fmt(f),
+            ArchiveError::InvalidHeader { msg } => write!(f, "Invalid archive header: {}", msg),
+            ArchiveError::InvalidBeacon { round, msg } => {
+                write!(f, "Invalid beacon for round {}: {}", round, msg)
+            }
+            ArchiveError::RoundNotIncreasing { previous, round } => write!(
+                f,
+                "Round {} must be greater than the previous round {}",
+                round, previous
+            ),
+            ArchiveError::Truncated => write!(f, "Archive ends in the middle of a record"),
+            ArchiveError::SchemeMismatch { archive, pubkey } => write!(
+                f,
+                "Archive uses scheme {} but the pubkey is for scheme {}",
+                archive, pubkey
+            ),
+        }
+    }
+}
+
+impl Error for ArchiveError {}
+
+impl From<io::Error> for ArchiveError {
+    fn from(source: io::Error) -> Self {
+        ArchiveError::Io(source)
+    }
+}
+
+impl From<UnknownScheme> for ArchiveError {
+    fn from(source: UnknownScheme) -> Self {
+        ArchiveError::UnknownScheme(source)
+    }
+}
+
+/// The size of the signatures of a scheme in bytes
+fn signature_size(scheme: Scheme) -> usize {
+    match scheme {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => Group::G2.compressed_size(),
+        Scheme::BlsUnchainedOnG1 | Scheme::BlsUnchainedG1Rfc9380 => Group::G1.compressed_size(),
+    }
+}
+
+fn record_size(scheme: Scheme) -> usize {
+    let previous_signature_size = if scheme.is_chained() {
+        signature_size(scheme)
+    } else {
+        0
+    };
+    8 + previous_signature_size + signature_size(scheme)
+}
+
+/// Writes beacons to an archive.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::archive::{ArchiveReader, ArchiveWriter};
+/// use drand_verify::{networks, Beacon, ChainInfo, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let info = ChainInfo::from(&networks::QUICKNET);
+/// let mut writer = ArchiveWriter::new(Vec::new(), &info).unwrap();
+/// writer.append(&Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// }).unwrap();
+/// let data = writer.finish().unwrap();
+///
+/// let mut reader = ArchiveReader::new(data.as_slice()).unwrap();
+/// assert_eq!(reader.chain_info(), &info);
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let report = reader.verify(&pk).unwrap();
+/// assert!(report.is_ok());
+/// assert_eq!(report.valid, 1);
+/// ```
+#[derive(Debug)]
+pub struct ArchiveWriter<W: Write> {
+    writer: W,
+    scheme: Scheme,
+    last_round: Option<u64>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Creates a new archive by writing the header for the given chain
+    pub fn new(mut writer: W, chain_info: &ChainInfo) -> Result<Self, ArchiveError> {
+        let scheme = Scheme::from_scheme_str(&chain_info.scheme_id)?;
+        let public_key_len =
+            u8::try_from(chain_info.public_key.len()).map_err(|_| ArchiveError::InvalidHeader {
+                msg: "public key too long".to_string(),
+            })?;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        writer.write_all(&[scheme.as_str().len() as u8])?;
+        writer.write_all(scheme.as_str().as_bytes())?;
+        writer.write_all(&[public_key_len])?;
+        writer.write_all(&chain_info.public_key)?;
+        writer.write_all(chain_info.hash.as_bytes())?;
+        writer.write_all(&chain_info.genesis_time.to_be_bytes())?;
+        writer.write_all(&chain_info.period.to_be_bytes())?;
+        match chain_info.group_hash {
+            Some(group_hash) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&group_hash)?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        Ok(Self {
+            writer,
+            scheme,
+            last_round: None,
+        })
+    }
+
+    /// Appends a beacon. Rounds must be strictly increasing.
+    ///
+    /// The beacon is not verified. Verify beacons before archiving them or
+    /// use [`ArchiveReader::verify`] afterwards.
+    pub fn append(&mut self, beacon: &Beacon) -> Result<(), ArchiveError> {
+        if let Some(previous) = self.last_round {
+            if beacon.round <= previous {
+                return Err(ArchiveError::RoundNotIncreasing {
+                    previous,
+                    round: beacon.round,
+                });
+            }
+        }
+        let expected_size = signature_size(self.scheme);
+        if beacon.signature.len() != expected_size {
+            return Err(ArchiveError::InvalidBeacon {
+                round: beacon.round,
+                msg: format!(
+                    "expected signature of {} bytes, got {}",
+                    expected_size,
+                    beacon.signature.len()
+                ),
+            });
+        }
+        let expected_previous_size = if self.scheme.is_chained() {
+            expected_size
+        } else {
+            0
+        };
+        if beacon.previous_signature.len() != expected_previous_size {
+            return Err(ArchiveError::InvalidBeacon {
+                round: beacon.round,
+                msg: format!(
+                    "expected previous signature of {} bytes, got {}",
+                    expected_previous_size,
+                    beacon.previous_signature.len()
+                ),
+            });
+        }
+
+        self.writer.write_all(&beacon.round.to_be_bytes())?;
+        self.writer.write_all(&beacon.previous_signature)?;
+        self.writer.write_all(&beacon.signature)?;
+        self.last_round = Some(beacon.round);
+        Ok(())
+    }
+
+    /// Flushes the archive and returns the underlying writer
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads beacons from an archive created by [`ArchiveWriter`].
+///
+/// The reader is an iterator over the beacons of the archive.
+#[derive(Debug)]
+pub struct ArchiveReader<R: Read> {
+    reader: R,
+    chain_info: ChainInfo,
+    scheme: Scheme,
+    last_round: Option<u64>,
+    failed: bool,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    /// Opens an archive by reading its header
+    pub fn new(mut reader: R) -> Result<Self, ArchiveError> {
+        let mut magic = [0u8; 8];
+        read_header(&mut reader, &mut magic)?;
+        if &magic != MAGIC {
+            return Err(ArchiveError::InvalidMagic);
+        }
+        let version = read_header_u8(&mut reader)?;
+        if version != VERSION {
+            return Err(ArchiveError::UnsupportedVersion(version));
+        }
+
+        let mut scheme_id = vec![0u8; read_header_u8(&mut reader)? as usize];
+        read_header(&mut reader, &mut scheme_id)?;
+        let scheme_id = String::from_utf8(scheme_id).map_err(|_| ArchiveError::InvalidHeader {
+            msg: "scheme ID is not UTF-8".to_string(),
+        })?;
+        let scheme = Scheme::from_scheme_str(&scheme_id)?;
+
+        let mut public_key = vec![0u8; read_header_u8(&mut reader)? as usize];
+        read_header(&mut reader, &mut public_key)?;
+        let mut hash = [0u8; 32];
+        read_header(&mut reader, &mut hash)?;
+        let mut genesis_time = [0u8; 8];
+        read_header(&mut reader, &mut genesis_time)?;
+        let mut period = [0u8; 8];
+        read_header(&mut reader, &mut period)?;
+        let group_hash = match read_header_u8(&mut reader)? {
+            0 => None,
+            1 => {
+                let mut group_hash = [0u8; 32];
+                read_header(&mut reader, &mut group_hash)?;
+                Some(group_hash)
+            }
+            flag => {
+                return Err(ArchiveError::InvalidHeader {
+                    msg: format!("invalid group hash flag {}", flag),
+                })
+            }
+        };
+
+        Ok(Self {
+            reader,
+            chain_info: ChainInfo {
+                public_key,
+                period: u64::from_be_bytes(period),
+                genesis_time: u64::from_be_bytes(genesis_time),
+                hash: ChainHash::new(hash),
+                scheme_id,
+                group_hash,
+            },
+            scheme,
+            last_round: None,
+            failed: false,
+        })
+    }
+
+    /// The chain info from the archive header.
+    ///
+    /// The header is not authenticated. Compare it with trusted chain info before relying on it.
+    pub fn chain_info(&self) -> &ChainInfo {
+        &self.chain_info
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    /// Reads the next beacon or returns `None` at the end of the archive
+    pub fn next_beacon(&mut self) -> Result<Option<Beacon>, ArchiveError> {
+        let mut record = vec![0u8; record_size(self.scheme)];
+        let filled = read_fully(&mut self.reader, &mut record)?;
+        if filled == 0 {
+            return Ok(None);
+        }
+        if filled != record.len() {
+            return Err(ArchiveError::Truncated);
+        }
+
+        let round = u64::from_be_bytes(record[0..8].try_into().unwrap());
+        if let Some(previous) = self.last_round {
+            if round <= previous {
+                return Err(ArchiveError::RoundNotIncreasing { previous, round });
+            }
+        }
+        self.last_round = Some(round);
+
+        let signature_start = record.len() - signature_size(self.scheme);
+        Ok(Some(Beacon {
+            round,
+            previous_signature: record[8..signature_start].to_vec(),
+            signature: record[signature_start..].to_vec(),
+        }))
+    }
+
+    /// Verifies all remaining beacons of the archive in batches of
+    /// [`DEFAULT_MAX_BATCH_SIZE`](crate::batch::DEFAULT_MAX_BATCH_SIZE).
+    /// With the `rayon` feature every batch is verified in parallel.
+    ///
+    /// Invalid beacons are reported in the returned [`AuditReport`]. Errors reading the
+    /// archive abort the verification.
+    pub fn verify<P: Pubkey + Sync>(&mut self, pubkey: &P) -> Result<AuditReport, ArchiveError> {
+        // pedersen-bls-chained and pedersen-bls-unchained use the same key type
+        if pubkey.scheme().pubkey_type() != self.scheme.pubkey_type() {
+            return Err(ArchiveError::SchemeMismatch {
+                archive: self.scheme,
+                pubkey: pubkey.scheme(),
+            });
+        }
+
+        let mut report = AuditReport::default();
+        let mut batch = Vec::with_capacity(DEFAULT_MAX_BATCH_SIZE);
+        loop {
+            batch.clear();
+            while batch.len() < DEFAULT_MAX_BATCH_SIZE {
+                match self.next_beacon()? {
+                    Some(beacon) => batch.push(beacon),
+                    None => break,
+                }
+            }
+            if batch.is_empty() {
+                return Ok(report);
+            }
+            for (beacon, outcome) in batch.iter().zip(verify_batch(pubkey, &batch)) {
+                match outcome {
+                    VerificationOutcome::Valid(_) => report.valid += 1,
+                    VerificationOutcome::Invalid(reason) => {
+                        report.invalid.push((beacon.round, reason))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+fn verify_batch<P: Pubkey + Sync>(pubkey: &P, beacons: &[Beacon]) -> Vec<VerificationOutcome> {
+    crate::batch::verify_parallel_unbounded(pubkey, beacons)
+}
+
+#[cfg(not(feature = "rayon"))]
+fn verify_batch<P: Pubkey + Sync>(pubkey: &P, beacons: &[Beacon]) -> Vec<VerificationOutcome> {
+    beacons
+        .iter()
+        .map(|beacon| {
+            pubkey.verify_with_outcome(beacon.round, &beacon.previous_signature, &beacon.signature)
+        })
+        .collect()
+}
+
+impl<R: Read> Iterator for ArchiveReader<R> {
+    type Item = Result<Beacon, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.next_beacon().transpose();
+        if matches!(result, Some(Err(_))) {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+/// Reads until `buf` is full or the end of the input is reached. Returns the number of bytes read.
+fn read_fully(reader: &mut impl Read, buf: &mut [u8]) -> Result<usize, io::Error> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(filled)
+}
+
+fn read_header(reader: &mut impl Read, buf: &mut [u8]) -> Result<(), ArchiveError> {
+    if read_fully(reader, buf)? != buf.len() {
+        return Err(ArchiveError::InvalidHeader {
+            msg: "unexpected end of data".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn read_header_u8(reader: &mut impl Read) -> Result<u8, ArchiveError> {
+    let mut buf = [0u8; 1];
+    read_header(reader, &mut buf)?;
+    Ok(buf[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::{MAINNET, QUICKNET};
+    use crate::{G1Pubkey, G2PubkeyRfc, InvalidReason};
+    use hex_literal::hex;
+
+    fn quicknet_beacon(round: u64) -> Beacon {
+        Beacon {
+            round,
+            previous_signature: vec![],
+            // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        }
+    }
+
+    fn mainnet_beacon() -> Beacon {
+        // https://api3.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/public/72785
+        Beacon {
+            round: 72785,
+            previous_signature: hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").to_vec(),
+            signature: hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").to_vec(),
+        }
+    }
+
+    #[test]
+    fn roundtrip_works() {
+        let mut info = ChainInfo::from(&MAINNET);
+        info.group_hash = Some([7; 32]);
+        let mut writer = ArchiveWriter::new(Vec::new(), &info).unwrap();
+        writer.append(&mainnet_beacon()).unwrap();
+        let data = writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::new(data.as_slice()).unwrap();
+        assert_eq!(reader.chain_info(), &info);
+        assert_eq!(reader.scheme(), Scheme::PedersenBlsChained);
+        let beacons: Vec<Beacon> = reader.by_ref().collect::<Result<_, _>>().unwrap();
+        assert_eq!(beacons, [mainnet_beacon()]);
+        assert!(reader.next_beacon().unwrap().is_none());
+
+        let mut reader = ArchiveReader::new(data.as_slice()).unwrap();
+        let pk = G1Pubkey::from_variable(MAINNET.public_key).unwrap();
+        let report = reader.verify(&pk).unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.valid, 1);
+    }
+
+    #[test]
+    fn verify_reports_invalid_beacons() {
+        let info = ChainInfo::from(&QUICKNET);
+        let mut writer = ArchiveWriter::new(Vec::new(), &info).unwrap();
+        writer.append(&quicknet_beacon(123)).unwrap();
+        writer.append(&quicknet_beacon(124)).unwrap();
+        let data = writer.finish().unwrap();
+        let header_len = data.len() - 2 * (8 + 48);
+
+        let pk = G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap();
+        let report = ArchiveReader::new(data.as_slice())
+            .unwrap()
+            .verify(&pk)
+            .unwrap();
+        assert_eq!(report.valid, 1);
+        assert_eq!(report.invalid, [(124, InvalidReason::SignatureMismatch)]);
+
+        // wrong pubkey type
+        let pk = G1Pubkey::from_variable(MAINNET.public_key).unwrap();
+        let err = ArchiveReader::new(data.as_slice())
+            .unwrap()
+            .verify(&pk)
+            .unwrap_err();
+        assert!(matches!(err, ArchiveError::SchemeMismatch { .. }));
+
+        // truncated
+        let mut reader = ArchiveReader::new(&data[..data.len() - 1]).unwrap();
+        assert!(reader.next().unwrap().is_ok());
+        assert!(matches!(reader.next(), Some(Err(ArchiveError::Truncated))));
+        assert!(reader.next().is_none());
+
+        // empty
+        let mut reader = ArchiveReader::new(&data[..header_len]).unwrap();
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn writer_rejects_invalid_beacons() {
+        let info = ChainInfo::from(&QUICKNET);
+        let mut writer = ArchiveWriter::new(Vec::new(), &info).unwrap();
+        writer.append(&quicknet_beacon(123)).unwrap();
+        assert!(matches!(
+            writer.append(&quicknet_beacon(123)),
+            Err(ArchiveError::RoundNotIncreasing {
+                previous: 123,
+                round: 123
+            })
+        ));
+        let mut beacon = quicknet_beacon(124);
+        beacon.previous_signature = vec![1; 48];
+        assert!(matches!(
+            writer.append(&beacon),
+            Err(ArchiveError::InvalidBeacon { round: 124, .. })
+        ));
+        assert!(matches!(
+            writer.append(&mainnet_beacon()),
+            Err(ArchiveError::InvalidBeacon { round: 72785, .. })
+        ));
+
+        let mut info = ChainInfo::from(&QUICKNET);
+        info.scheme_id = "bls-bn254-unchained-on-g1".to_string();
+        assert!(matches!(
+            ArchiveWriter::new(Vec::new(), &info),
+            Err(ArchiveError::UnknownScheme(_))
+        ));
+    }
+
+    #[test]
+    fn reader_rejects_invalid_headers() {
+        assert!(matches!(
+            ArchiveReader::new(&b"DRANDARX\x01"[..]),
+            Err(ArchiveError::InvalidMagic)
+        ));
+        assert!(matches!(
+            ArchiveReader::new(&b"DRANDARC\x02"[..]),
+            Err(ArchiveError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            ArchiveReader::new(&b"DRANDARC\x01\x05ab"[..]),
+            Err(ArchiveError::InvalidHeader { .. })
+        ));
+    }
+}
@@ -0,0 +1,703 @@
+//! Helpers for using this crate in tokio based services (feature `tokio`).
+//!
+//! Pairing checks take milliseconds of CPU time and must not run on the async worker threads.
+//! [`spawn_verify`] moves them to tokio's blocking pool and [`BeaconWatcher`] publishes the
+//! latest verified beacon to any number of tasks via a [`watch`] channel.
+//! [`VerificationQueue`] is the core of a beacon gateway: it accepts beacons from many
+//! producers, verifies them in batches and delivers every verified round to all subscribers.
+//!
+//! With the feature `metrics`, [`BeaconWatcher`] publishes the following metrics via the
+//! [`metrics`](https://docs.rs/metrics) facade. Install an exporter such as
+//! `metrics-exporter-prometheus` to serve them.
+//!
+//! | Name                                 | Type      | Description                                          |
+//! |--------------------------------------|-----------|------------------------------------------------------|
+//! | `drand_verify_verified_rounds_total` | counter   | Beacons verified and published                       |
+//! | `drand_verify_failures_total`        | counter   | Rejected beacons, labeled with the `reason`          |
+//! | `drand_verify_latest_round`          | gauge     | The latest verified round                            |
+//! | `drand_verify_lag_rounds`            | gauge     | Rounds published by the network but not verified yet |
+//! | `drand_verify_beacon_delay_seconds`  | histogram | Time between publishing and verifying a beacon       |
+//!
+//! The last two require [`BeaconWatcher::with_chain_info`].
+
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "metrics")]
+use std::time::SystemTime;
+
+use tokio::sync::{mpsc, watch};
+use tokio::task::{self, JoinError};
+
+use crate::beacon::{Beacon, VerifiedBeacon};
+use crate::chain_info::ChainInfo;
+use crate::light_client::LightClientError;
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::rounds::Timestamp;
+use crate::verify::Pubkey;
+
+/// Verifies the beacon on tokio's blocking thread pool.
+///
+/// An error is only returned if the verification task panicked or the runtime is shutting down.
+pub async fn spawn_verify<P>(
+    pubkey: Arc<P>,
+    beacon: Beacon,
+) -> Result<VerificationOutcome, JoinError>
+where
+    P: Pubkey + Send + Sync + 'static,
+{
+    task::spawn_blocking(move || {
+        pubkey.verify_with_outcome(beacon.round, &beacon.previous_signature, &beacon.signature)
+    })
+    .await
+}
+
+/// The error returned by [`BeaconWatcher::push`]
+#[derive(Debug)]
+pub enum PushError {
+    /// The beacon was rejected
+    Rejected(LightClientError),
+    /// The verification task did not complete
+    Join(JoinError),
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PushError::Rejected(err) => write!(f, "{}", err),
+            PushError::Join(err) => write!(f, "Verification task failed: {}", err),
+        }
+    }
+}
+
+impl Error for PushError {}
+
+impl PushError {
+    /// A short machine readable reason, used as the `reason` label of metrics
+    pub fn reason(&self) -> &'static str {
+        match self {
+            PushError::Rejected(LightClientError::NotMonotonic { .. }) => "not_monotonic",
+            PushError::Rejected(LightClientError::Invalid(InvalidReason::InvalidPoint {
+                ..
+            })) => "invalid_point",
+            PushError::Rejected(LightClientError::Invalid(InvalidReason::SignatureMismatch)) => {
+                "signature_mismatch"
+            }
+            PushError::Join(_) => "join",
+        }
+    }
+}
+
+/// Verifies beacons and broadcasts the latest one.
+///
+/// Like [`LightClient`](crate::light_client::LightClient), only beacons with a round higher
+/// than the latest one are accepted. Subscribers receive `None` until the first beacon was verified.
+///
+/// ## Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use hex_literal::hex;
+/// use drand_verify::async_tokio::BeaconWatcher;
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+/// let watcher = BeaconWatcher::new(Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap()));
+/// let mut latest = watcher.subscribe();
+///
+/// // Call this for every beacon received from a relay
+/// watcher.push(Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// }).await.unwrap();
+///
+/// latest.changed().await.unwrap();
+/// assert_eq!(latest.borrow().as_ref().unwrap().round(), 123);
+/// # });
+/// ```
+pub struct BeaconWatcher<P: Pubkey> {
+    pubkey: Arc<P>,
+    sender: watch::Sender<Option<VerifiedBeacon>>,
+    chain_info: Option<ChainInfo>,
+}
+
+impl<P> BeaconWatcher<P>
+where
+    P: Pubkey + Send + Sync + 'static,
+{
+    pub fn new(pubkey: Arc<P>) -> Self {
+        Self {
+            pubkey,
+            sender: watch::Sender::new(None),
+            chain_info: None,
+        }
+    }
+
+    /// Creates a watcher that continues after a beacon verified earlier, e.g. before a restart
+    pub fn from_checkpoint(pubkey: Arc<P>, latest: VerifiedBeacon) -> Self {
+        Self {
+            pubkey,
+            sender: watch::Sender::new(Some(latest)),
+            chain_info: None,
+        }
+    }
+
+    /// Sets the chain info of the network, which is needed to compute the lag
+    pub fn with_chain_info(mut self, chain_info: ChainInfo) -> Self {
+        self.chain_info = Some(chain_info);
+        self
+    }
+
+    /// Returns a receiver for the latest verified beacon
+    pub fn subscribe(&self) -> watch::Receiver<Option<VerifiedBeacon>> {
+        self.sender.subscribe()
+    }
+
+    /// The latest verified round or 0 if nothing was verified yet
+    pub fn latest_round(&self) -> u64 {
+        self.sender
+            .borrow()
+            .as_ref()
+            .map_or(0, VerifiedBeacon::round)
+    }
+
+    /// The number of rounds published by the network at `now` that are higher than the
+    /// latest verified round, or `None` without [`BeaconWatcher::with_chain_info`]
+    pub fn lag(&self, now: impl Timestamp) -> Option<u64> {
+        let info = self.chain_info.as_ref()?;
+        Some(info.round_at(now).saturating_sub(self.latest_round()))
+    }
+
+    /// Verifies the beacon on the blocking thread pool and publishes it if its round
+    /// is higher than the latest verified round.
+    ///
+    /// Concurrent pushes are fine. If a higher round was published while this beacon
+    /// was verified, this beacon is rejected.
+    pub async fn push(&self, beacon: Beacon) -> Result<VerifiedBeacon, PushError> {
+        let result = self.push_impl(beacon).await;
+        #[cfg(feature = "metrics")]
+        self.record_metrics(&result);
+        result
+    }
+
+    async fn push_impl(&self, beacon: Beacon) -> Result<VerifiedBeacon, PushError> {
+        self.check_monotonic(beacon.round)?;
+        let verified = match spawn_verify(self.pubkey.clone(), beacon).await {
+            Ok(VerificationOutcome::Valid(verified)) => verified,
+            Ok(VerificationOutcome::Invalid(reason)) => {
+                return Err(PushError::Rejected(LightClientError::Invalid(reason)))
+            }
+            Err(err) => return Err(PushError::Join(err)),
+        };
+
+        let mut result = Ok(());
+        self.sender.send_if_modified(|latest| {
+            let latest_round = latest.as_ref().map_or(0, VerifiedBeacon::round);
+            if verified.round() > latest_round {
+                *latest = Some(verified.clone());
+                true
+            } else {
+                result = Err(not_monotonic(latest_round, verified.round()));
+                false
+            }
+        });
+        result.map(|()| verified)
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_metrics(&self, result: &Result<VerifiedBeacon, PushError>) {
+        let now = SystemTime::now();
+        match result {
+            Ok(verified) => {
+                metrics::counter!("drand_verify_verified_rounds_total").increment(1);
+                metrics::gauge!("drand_verify_latest_round").set(verified.round() as f64);
+                if let Some(info) = &self.chain_info {
+                    let published: SystemTime = info.round_time(verified.round());
+                    if let Ok(delay) = now.duration_since(published) {
+                        metrics::histogram!("drand_verify_beacon_delay_seconds")
+                            .record(delay.as_secs_f64());
+                    }
+                }
+            }
+            Err(err) => {
+                metrics::counter!("drand_verify_failures_total", "reason" => err.reason())
+                    .increment(1);
+            }
+        }
+        if let Some(lag) = self.lag(now) {
+            metrics::gauge!("drand_verify_lag_rounds").set(lag as f64);
+        }
+    }
+
+    fn check_monotonic(&self, round: u64) -> Result<(), PushError> {
+        let latest = self.latest_round();
+        if round <= latest {
+            return Err(not_monotonic(latest, round));
+        }
+        Ok(())
+    }
+}
+
+fn not_monotonic(latest: u64, actual: u64) -> PushError {
+    PushError::Rejected(LightClientError::NotMonotonic { latest, actual })
+}
+
+/// The number of delivered rounds [`VerificationQueue`] remembers to drop duplicates
+pub const DEDUP_WINDOW: usize = 1024;
+
+/// The error returned by [`VerificationQueue::submit`] when the queue stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueueClosed;
+
+impl fmt::Display for QueueClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Verification queue closed")
+    }
+}
+
+impl Error for QueueClosed {}
+
+type Subscribers = Arc<Mutex<Vec<mpsc::Sender<VerifiedBeacon>>>>;
+
+/// A bounded queue that verifies beacons from many producers and fans out the verified
+/// beacons to all subscribers.
+///
+/// A background task takes up to `capacity` beacons at a time from the queue, drops
+/// duplicates and rounds delivered already (of the last [`DEDUP_WINDOW`] delivered rounds),
+/// verifies the rest on the blocking thread pool (in parallel with the feature `rayon`) and
+/// sends every valid beacon to every subscriber. Invalid beacons are dropped silently.
+///
+/// Memory is bounded everywhere: [`VerificationQueue::submit`] waits while the queue is full,
+/// and the task waits while a subscriber's channel is full. So a slow subscriber slows down
+/// the producers instead of growing a buffer. Subscribers only receive beacons verified after
+/// they subscribed. The task stops when all handles of the queue are dropped, which closes
+/// the subscriber channels.
+///
+/// ## Examples
+///
+/// ```
+/// use std::sync::Arc;
+/// use hex_literal::hex;
+/// use drand_verify::async_tokio::VerificationQueue;
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+/// let queue = VerificationQueue::spawn(Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap()), 16);
+/// let mut verified = queue.subscribe(16);
+///
+/// // Producers submit every beacon received from any relay
+/// let beacon = Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// };
+/// queue.submit(beacon.clone()).await.unwrap();
+/// queue.submit(beacon).await.unwrap();
+///
+/// assert_eq!(verified.recv().await.unwrap().round(), 123);
+/// drop(queue);
+/// // The duplicate was dropped
+/// assert!(verified.recv().await.is_none());
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct VerificationQueue {
+    sender: mpsc::Sender<Beacon>,
+    subscribers: Subscribers,
+}
+
+impl VerificationQueue {
+    /// Spawns the verification task on the current tokio runtime.
+    ///
+    /// `capacity` bounds the number of queued beacons and the size of a batch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0 or if called outside of a tokio runtime.
+    pub fn spawn<P>(pubkey: Arc<P>, capacity: usize) -> Self
+    where
+        P: Pubkey + Send + Sync + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(capacity);
+        let subscribers = Subscribers::default();
+        task::spawn(run_queue(pubkey, receiver, subscribers.clone(), capacity));
+        Self {
+            sender,
+            subscribers,
+        }
+    }
+
+    /// Adds a beacon to the queue, waiting while the queue is full
+    pub async fn submit(&self, beacon: Beacon) -> Result<(), QueueClosed> {
+        self.sender.send(beacon).await.map_err(|_| QueueClosed)
+    }
+
+    /// Returns a receiver for all beacons verified from now on. The queue waits for the
+    /// subscriber when more than `capacity` beacons are not received yet.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is 0.
+    pub fn subscribe(&self, capacity: usize) -> mpsc::Receiver<VerifiedBeacon> {
+        let (sender, receiver) = mpsc::channel(capacity);
+        lock(&self.subscribers).push(sender);
+        receiver
+    }
+}
+
+fn lock(subscribers: &Subscribers) -> std::sync::MutexGuard<'_, Vec<mpsc::Sender<VerifiedBeacon>>> {
+    // The list is consistent after every statement, so a poisoned lock can be reused
+    subscribers
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+async fn run_queue<P>(
+    pubkey: Arc<P>,
+    mut receiver: mpsc::Receiver<Beacon>,
+    subscribers: Subscribers,
+    batch_size: usize,
+) where
+    P: Pubkey + Send + Sync + 'static,
+{
+    let mut delivered = BTreeSet::new();
+    let mut batch = Vec::with_capacity(batch_size);
+    while receiver.recv_many(&mut batch, batch_size).await != 0 {
+        let mut beacons: Vec<Beacon> = Vec::with_capacity(batch.len());
+        for beacon in batch.drain(..) {
+            if !delivered.contains(&beacon.round) && !beacons.contains(&beacon) {
+                beacons.push(beacon);
+            }
+        }
+        if beacons.is_empty() {
+            continue;
+        }
+
+        let pk = pubkey.clone();
+        // A panicking verification only loses this batch
+        let outcomes = match task::spawn_blocking(move || verify_all(&*pk, &beacons)).await {
+            Ok(outcomes) => outcomes,
+            Err(_) => continue,
+        };
+        for verified in outcomes
+            .into_iter()
+            .filter_map(VerificationOutcome::into_verified)
+        {
+            if !delivered.insert(verified.round()) {
+                continue;
+            }
+            while delivered.len() > DEDUP_WINDOW {
+                if let Some(&oldest) = delivered.iter().next() {
+                    delivered.remove(&oldest);
+                }
+            }
+            let senders = lock(&subscribers).clone();
+            for sender in senders {
+                // A closed subscriber is removed below
+                let _ = sender.send(verified.clone()).await;
+            }
+            lock(&subscribers).retain(|sender| !sender.is_closed());
+        }
+    }
+}
+
+fn verify_all<P: Pubkey + Sync>(pubkey: &P, beacons: &[Beacon]) -> Vec<VerificationOutcome> {
+    #[cfg(feature = "rayon")]
+    {
+        crate::batch::verify_parallel_unbounded(pubkey, beacons)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        beacons
+            .iter()
+            .map(|beacon| {
+                pubkey.verify_with_outcome(
+                    beacon.round,
+                    &beacon.previous_signature,
+                    &beacon.signature,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G2PubkeyRfc, InvalidReason};
+    use hex_literal::hex;
+
+    const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+
+    fn beacon_123() -> Beacon {
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        Beacon {
+            round: 123,
+            previous_signature: vec![],
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        }
+    }
+
+    /// Runs a future on a single threaded runtime without the `macros` feature of tokio
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    #[test]
+    fn spawn_verify_works() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+
+            let outcome = spawn_verify(pk.clone(), beacon_123()).await.unwrap();
+            assert_eq!(outcome.into_verified().unwrap().round(), 123);
+
+            let mut beacon = beacon_123();
+            beacon.round = 124;
+            let outcome = spawn_verify(pk, beacon).await.unwrap();
+            assert!(!outcome.is_valid());
+        })
+    }
+
+    #[test]
+    fn verification_queue_works() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let queue = VerificationQueue::spawn(pk, 4);
+            let mut a = queue.subscribe(1);
+            let mut b = queue.subscribe(1);
+
+            let mut invalid = beacon_123();
+            invalid.round = 124;
+            let producers: Vec<_> = (0..5)
+                .map(|i| {
+                    let queue = queue.clone();
+                    let beacon = if i % 2 == 0 {
+                        beacon_123()
+                    } else {
+                        invalid.clone()
+                    };
+                    tokio::spawn(async move { queue.submit(beacon).await })
+                })
+                .collect();
+            for producer in producers {
+                producer.await.unwrap().unwrap();
+            }
+
+            assert_eq!(a.recv().await.unwrap().round(), 123);
+            assert_eq!(b.recv().await.unwrap().round(), 123);
+
+            // Delivered rounds are not delivered again, even after the batch
+            queue.submit(beacon_123()).await.unwrap();
+            // A dropped subscriber does not block the others
+            drop(b);
+            drop(queue);
+            assert!(a.recv().await.is_none());
+        })
+    }
+
+    #[test]
+    fn verification_queue_deduplicates() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let queue = VerificationQueue::spawn(pk, 8);
+            let mut verified = queue.subscribe(8);
+
+            // Duplicates in one batch
+            for _ in 0..3 {
+                queue.submit(beacon_123()).await.unwrap();
+            }
+            assert_eq!(verified.recv().await.unwrap().round(), 123);
+
+            // Duplicates in a later batch
+            queue.submit(beacon_123()).await.unwrap();
+            let mut invalid = beacon_123();
+            invalid.round = 124;
+            queue.submit(invalid).await.unwrap();
+
+            drop(queue);
+            // The channel closes after the queue processed everything
+            assert!(verified.recv().await.is_none());
+        })
+    }
+
+    #[test]
+    fn beacon_watcher_from_checkpoint_works() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let checkpoint = spawn_verify(pk.clone(), beacon_123())
+                .await
+                .unwrap()
+                .into_verified()
+                .unwrap();
+
+            let watcher = BeaconWatcher::from_checkpoint(pk, checkpoint.clone());
+            assert_eq!(watcher.latest_round(), 123);
+            assert_eq!(watcher.subscribe().borrow().as_ref(), Some(&checkpoint));
+            match watcher.push(beacon_123()).await.unwrap_err() {
+                PushError::Rejected(LightClientError::NotMonotonic { latest, actual }) => {
+                    assert_eq!((latest, actual), (123, 123));
+                }
+                err => panic!("Unexpected error: {}", err),
+            }
+        })
+    }
+
+    #[test]
+    fn beacon_watcher_works() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let watcher = BeaconWatcher::new(pk);
+            let mut receiver = watcher.subscribe();
+            assert_eq!(*receiver.borrow(), None);
+            assert_eq!(watcher.latest_round(), 0);
+
+            // Invalid beacons are not published
+            let mut invalid = beacon_123();
+            invalid.round = 124;
+            match watcher.push(invalid).await.unwrap_err() {
+                PushError::Rejected(LightClientError::Invalid(
+                    InvalidReason::SignatureMismatch,
+                )) => {}
+                err => panic!("Unexpected error: {}", err),
+            }
+            assert!(!receiver.has_changed().unwrap());
+
+            let verified = watcher.push(beacon_123()).await.unwrap();
+            assert_eq!(verified.round(), 123);
+            assert!(receiver.has_changed().unwrap());
+            assert_eq!(receiver.borrow_and_update().as_ref(), Some(&verified));
+            assert_eq!(watcher.latest_round(), 123);
+
+            // Replays are rejected
+            match watcher.push(beacon_123()).await.unwrap_err() {
+                PushError::Rejected(LightClientError::NotMonotonic { latest, actual }) => {
+                    assert_eq!((latest, actual), (123, 123));
+                }
+                err => panic!("Unexpected error: {}", err),
+            }
+            assert!(!receiver.has_changed().unwrap());
+        })
+    }
+
+    #[test]
+    fn lag_works() {
+        block_on(async {
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let info = ChainInfo::from(&crate::networks::QUICKNET);
+            let watcher = BeaconWatcher::new(pk.clone());
+            assert_eq!(watcher.lag(info.round_time::<u64>(130)), None);
+
+            let watcher = BeaconWatcher::new(pk).with_chain_info(info.clone());
+            assert_eq!(watcher.lag(info.round_time::<u64>(130)), Some(130));
+            watcher.push(beacon_123()).await.unwrap();
+            assert_eq!(watcher.lag(info.round_time::<u64>(130)), Some(7));
+            assert_eq!(watcher.lag(info.round_time::<u64>(100)), Some(0));
+        })
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn beacon_watcher_records_metrics() {
+        block_on(async {
+            use metrics::{
+                Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata,
+                Recorder, SharedString, Unit,
+            };
+            use std::collections::HashMap;
+            use std::sync::Mutex;
+
+            type Values = Arc<Mutex<HashMap<String, f64>>>;
+
+            struct Handle(String, Values);
+
+            impl CounterFn for Handle {
+                fn increment(&self, value: u64) {
+                    *self.1.lock().unwrap().entry(self.0.clone()).or_default() += value as f64;
+                }
+                fn absolute(&self, value: u64) {
+                    self.1.lock().unwrap().insert(self.0.clone(), value as f64);
+                }
+            }
+
+            impl GaugeFn for Handle {
+                fn increment(&self, _value: f64) {}
+                fn decrement(&self, _value: f64) {}
+                fn set(&self, value: f64) {
+                    self.1.lock().unwrap().insert(self.0.clone(), value);
+                }
+            }
+
+            impl HistogramFn for Handle {
+                fn record(&self, value: f64) {
+                    self.1.lock().unwrap().insert(self.0.clone(), value);
+                }
+            }
+
+            #[derive(Default)]
+            struct TestRecorder(Values);
+
+            impl TestRecorder {
+                fn handle(&self, key: &Key) -> Arc<Handle> {
+                    let labels: Vec<String> = key
+                        .labels()
+                        .map(|label| format!("{}={}", label.key(), label.value()))
+                        .collect();
+                    let name = format!("{}{{{}}}", key.name(), labels.join(","));
+                    Arc::new(Handle(name, self.0.clone()))
+                }
+            }
+
+            impl Recorder for TestRecorder {
+                fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+                fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+                fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+                fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+                    Counter::from_arc(self.handle(key))
+                }
+                fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+                    Gauge::from_arc(self.handle(key))
+                }
+                fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+                    Histogram::from_arc(self.handle(key))
+                }
+            }
+
+            let recorder = TestRecorder::default();
+            // the test runtime is single threaded, so the recorder stays active across awaits
+            let _guard = metrics::set_default_local_recorder(&recorder);
+
+            let pk = Arc::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+            let info = ChainInfo::from(&crate::networks::QUICKNET);
+            let watcher = BeaconWatcher::new(pk).with_chain_info(info);
+            let mut invalid = beacon_123();
+            invalid.round = 122;
+            watcher.push(invalid).await.unwrap_err();
+            watcher.push(beacon_123()).await.unwrap();
+            watcher.push(beacon_123()).await.unwrap_err();
+
+            let values = recorder.0.lock().unwrap();
+            let value = |name: &str| values.get(name).copied();
+            assert_eq!(value("drand_verify_verified_rounds_total{}"), Some(1.0));
+            assert_eq!(value("drand_verify_latest_round{}"), Some(123.0));
+            assert_eq!(
+                value("drand_verify_failures_total{reason=signature_mismatch}"),
+                Some(1.0)
+            );
+            assert_eq!(
+                value("drand_verify_failures_total{reason=not_monotonic}"),
+                Some(1.0)
+            );
+            // round 123 was published in 2023
+            assert!(value("drand_verify_beacon_delay_seconds{}").unwrap() > 3600.0);
+            assert!(value("drand_verify_lag_rounds{}").unwrap() > 1000.0);
+        })
+    }
+}
@@ -0,0 +1,443 @@
+//! Auditing of long beacon histories by verifying a random sample of rounds.
+//!
+//! Verifying every round of a 3 second network over years is impractical. If a fraction `f`
+//! of all rounds were invalid, a uniform sample of `n` rounds contains at least one of them
+//! with probability `1 - (1 - f)^n`. For example 1000 samples detect 0.5% invalid rounds
+//! with more than 99% probability, independent of the length of the history.
+//!
+//! This works for chained networks as well. Each sampled beacon is verified against its own
+//! previous signature. A valid beacon does not vouch for any earlier round, because its
+//! signature only covers the bytes of the previous signature, not whether that is valid.
+
+use std::collections::BTreeSet;
+use std::ops::ControlFlow;
+
+use sha2::{Digest, Sha256};
+
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::source::BeaconSource;
+use crate::verify::Pubkey;
+
+/// Selects `sample_size` distinct rounds from `first..=last` uniformly at random.
+///
+/// The selection is deterministic for a given `seed` such that audits can be reproduced
+/// by third parties. Use an unpredictable seed (e.g. a drand randomness value published
+/// after the history was created) so the audited party cannot know the sample in advance.
+///
+/// The result is sorted. If the range contains `sample_size` rounds or less, all rounds are returned.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::audit::plan_sample;
+///
+/// let rounds = plan_sample(1, 50_000_000, 3, b"seed");
+/// assert_eq!(rounds.len(), 3);
+/// assert_eq!(rounds, plan_sample(1, 50_000_000, 3, b"seed"));
+/// ```
+pub fn plan_sample(first: u64, last: u64, sample_size: usize, seed: &[u8]) -> Vec<u64> {
+    if first > last {
+        return vec![];
+    }
+    let range_len = (last - first).checked_add(1);
+    if range_len.map_or(false, |len| len <= sample_size as u64) {
+        return (first..=last).collect();
+    }
+
+    let mut selected = BTreeSet::new();
+    let mut counter: u64 = 0;
+    while selected.len() < sample_size {
+        let candidate = random_u64(seed, counter);
+        counter += 1;
+        let round = match range_len {
+            Some(len) => {
+                // Rejection sampling to avoid modulo bias
+                let zone = u64::MAX - u64::MAX % len;
+                if candidate >= zone {
+                    continue;
+                }
+                first + candidate % len
+            }
+            // The range covers all u64 values
+            None => candidate,
+        };
+        selected.insert(round);
+    }
+    selected.into_iter().collect()
+}
+
+pub(crate) fn random_u64(seed: &[u8], counter: u64) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(counter.to_be_bytes());
+    let hash = hasher.finalize();
+    u64::from_be_bytes(hash[0..8].try_into().unwrap())
+}
+
+/// The result of [`audit_sample`]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditReport {
+    /// Number of rounds that were verified successfully
+    pub valid: usize,
+    /// Rounds that failed verification
+    pub invalid: Vec<(u64, InvalidReason)>,
+    /// Rounds that could not be loaded from the source
+    pub unavailable: Vec<(u64, String)>,
+}
+
+impl AuditReport {
+    /// The number of rounds that were checked
+    pub fn checked(&self) -> usize {
+        self.valid + self.invalid.len()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.invalid.is_empty() && self.unavailable.is_empty()
+    }
+
+    /// The probability that this audit would have found an invalid round
+    /// if the given fraction of all rounds was invalid.
+    ///
+    /// This assumes the sample was drawn uniformly at random, e.g. using [`plan_sample`].
+    pub fn confidence(&self, invalid_fraction: f64) -> f64 {
+        detection_probability(self.checked(), invalid_fraction)
+    }
+}
+
+/// The probability that a uniform sample of `sample_size` rounds contains at least
+/// one invalid round if the given fraction of all rounds is invalid.
+pub fn detection_probability(sample_size: usize, invalid_fraction: f64) -> f64 {
+    let invalid_fraction = invalid_fraction.clamp(0.0, 1.0);
+    1.0 - (1.0 - invalid_fraction).powf(sample_size as f64)
+}
+
+/// The number of samples needed to detect the given fraction of invalid rounds
+/// with the given probability.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::audit::required_sample_size;
+///
+/// assert_eq!(required_sample_size(0.99, 0.005), 919);
+/// ```
+pub fn required_sample_size(confidence: f64, invalid_fraction: f64) -> usize {
+    if confidence <= 0.0 {
+        return 0;
+    }
+    if invalid_fraction <= 0.0 || confidence >= 1.0 {
+        return usize::MAX;
+    }
+    if invalid_fraction >= 1.0 {
+        return 1;
+    }
+    ((1.0 - confidence).ln() / (1.0 - invalid_fraction).ln()).ceil() as usize
+}
+
+/// Loads the given rounds from the source and verifies them.
+pub fn audit_sample<P, S>(pubkey: &P, source: S, rounds: &[u64]) -> AuditReport
+where
+    P: Pubkey,
+    S: BeaconSource,
+    S::Error: ToString,
+{
+    let mut cursor = AuditCursor::new();
+    audit_resumable(pubkey, source, rounds, &mut cursor, |_, _| {
+        ControlFlow::Continue(())
+    });
+    cursor.report
+}
+
+/// The state of an audit started with [`audit_resumable`].
+///
+/// Persist the cursor (e.g. using the `serde` feature) to continue an interrupted audit
+/// after a restart. The cursor is only meaningful together with the same list of rounds.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AuditCursor {
+    /// The number of rounds from the list that were processed
+    pub position: usize,
+    /// The results so far
+    pub report: AuditReport,
+}
+
+impl AuditCursor {
+    /// Creates a cursor at the start of an audit
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if all of the given rounds were processed
+    pub fn is_finished(&self, rounds: &[u64]) -> bool {
+        self.position >= rounds.len()
+    }
+}
+
+/// The progress of an audit, see [`audit_resumable`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditProgress {
+    pub processed: usize,
+    pub total: usize,
+}
+
+impl AuditProgress {
+    /// The processed fraction of the audit in percent (0 to 100)
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.processed as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+/// Like [`audit_sample`] but continues at the position of the cursor and
+/// calls `on_progress` after every round.
+///
+/// The callback receives the progress and the updated cursor, which it can persist.
+/// Returning [`ControlFlow::Break`] stops the audit. Call this function again with
+/// the same rounds and cursor to continue.
+///
+/// ## Examples
+///
+/// ```
+/// use std::ops::ControlFlow;
+/// use drand_verify::audit::{audit_resumable, AuditCursor};
+/// use drand_verify::{networks, Beacon, G2PubkeyRfc, Pubkey};
+///
+/// # struct Relay;
+/// # impl drand_verify::BeaconSource for Relay {
+/// #     type Error = String;
+/// #     fn beacon(&mut self, round: u64) -> Result<Beacon, String> {
+/// #         Err(format!("Round {} not found", round))
+/// #     }
+/// # }
+/// # let mut source = Relay;
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let rounds = [1, 2, 3, 4];
+/// let mut cursor = AuditCursor::new();
+///
+/// // Stop after half of the rounds
+/// audit_resumable(&pk, &mut source, &rounds, &mut cursor, |progress, _cursor| {
+///     if progress.percent() >= 50.0 {
+///         ControlFlow::Break(())
+///     } else {
+///         ControlFlow::Continue(())
+///     }
+/// });
+/// assert_eq!(cursor.position, 2);
+///
+/// // Continue later
+/// audit_resumable(&pk, &mut source, &rounds, &mut cursor, |_, _| ControlFlow::Continue(()));
+/// assert!(cursor.is_finished(&rounds));
+/// assert_eq!(cursor.report.unavailable.len(), 4);
+/// ```
+pub fn audit_resumable<P, S, F>(
+    pubkey: &P,
+    mut source: S,
+    rounds: &[u64],
+    cursor: &mut AuditCursor,
+    mut on_progress: F,
+) where
+    P: Pubkey,
+    S: BeaconSource,
+    S::Error: ToString,
+    F: FnMut(AuditProgress, &AuditCursor) -> ControlFlow<()>,
+{
+    while let Some(&round) = rounds.get(cursor.position) {
+        audit_round(pubkey, &mut source, round, &mut cursor.report);
+        cursor.position += 1;
+        let progress = AuditProgress {
+            processed: cursor.position,
+            total: rounds.len(),
+        };
+        if on_progress(progress, cursor).is_break() {
+            return;
+        }
+    }
+}
+
+fn audit_round<P, S>(pubkey: &P, source: &mut S, round: u64, report: &mut AuditReport)
+where
+    P: Pubkey,
+    S: BeaconSource,
+    S::Error: ToString,
+{
+    let beacon = match source.beacon(round) {
+        Ok(beacon) => beacon,
+        Err(err) => {
+            report.unavailable.push((round, err.to_string()));
+            return;
+        }
+    };
+    if beacon.round != round {
+        report
+            .invalid
+            .push((round, InvalidReason::SignatureMismatch));
+        return;
+    }
+    match pubkey.verify_with_outcome(round, &beacon.previous_signature, &beacon.signature) {
+        VerificationOutcome::Valid(_) => report.valid += 1,
+        VerificationOutcome::Invalid(reason) => report.invalid.push((round, reason)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Beacon, G2PubkeyRfc};
+    use hex_literal::hex;
+
+    const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+
+    #[test]
+    fn plan_sample_works() {
+        let sample = plan_sample(1, 1_000_000, 100, b"seed");
+        assert_eq!(sample.len(), 100);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|round| (1..=1_000_000).contains(round)));
+        assert_eq!(sample, plan_sample(1, 1_000_000, 100, b"seed"));
+        assert_ne!(sample, plan_sample(1, 1_000_000, 100, b"other seed"));
+
+        // small ranges
+        assert_eq!(plan_sample(5, 7, 3, b"seed"), [5, 6, 7]);
+        assert_eq!(plan_sample(5, 7, 10, b"seed"), [5, 6, 7]);
+        assert_eq!(plan_sample(7, 5, 10, b"seed"), Vec::<u64>::new());
+        assert_eq!(plan_sample(5, 7, 0, b"seed"), Vec::<u64>::new());
+        assert_eq!(plan_sample(5, 7, 2, b"seed").len(), 2);
+
+        // full range
+        assert_eq!(plan_sample(0, u64::MAX, 5, b"seed").len(), 5);
+    }
+
+    #[test]
+    fn detection_probability_works() {
+        assert_eq!(detection_probability(0, 0.5), 0.0);
+        assert_eq!(detection_probability(10, 0.0), 0.0);
+        assert_eq!(detection_probability(1, 1.0), 1.0);
+        assert!((detection_probability(2, 0.5) - 0.75).abs() < 1e-12);
+        assert!(detection_probability(919, 0.005) >= 0.99);
+        assert!(detection_probability(918, 0.005) < 0.99);
+
+        assert_eq!(required_sample_size(0.0, 0.1), 0);
+        assert_eq!(required_sample_size(0.5, 1.0), 1);
+        assert_eq!(required_sample_size(0.5, 0.0), usize::MAX);
+        assert_eq!(required_sample_size(0.75, 0.5), 2);
+    }
+
+    struct QuicknetSource;
+
+    impl BeaconSource for QuicknetSource {
+        type Error = String;
+
+        fn beacon(&mut self, round: u64) -> Result<Beacon, Self::Error> {
+            match round {
+                // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+                123 => Ok(Beacon {
+                    round,
+                    previous_signature: vec![],
+                    signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+                }),
+                // wrong signature
+                124 => Ok(Beacon {
+                    round,
+                    previous_signature: vec![],
+                    signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+                }),
+                // wrong round
+                125 => Ok(Beacon {
+                    round: 123,
+                    previous_signature: vec![],
+                    signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+                }),
+                _ => Err(format!("Round {} not found", round)),
+            }
+        }
+    }
+
+    #[test]
+    fn audit_sample_works() {
+        let pk = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+
+        let report = audit_sample(&pk, QuicknetSource, &[123]);
+        assert!(report.is_ok());
+        assert_eq!(report.checked(), 1);
+        assert!((report.confidence(0.5) - 0.5).abs() < 1e-12);
+
+        let report = audit_sample(&pk, QuicknetSource, &[123, 124, 125, 126]);
+        assert!(!report.is_ok());
+        assert_eq!(report.valid, 1);
+        assert_eq!(
+            report.invalid,
+            [
+                (124, InvalidReason::SignatureMismatch),
+                (125, InvalidReason::SignatureMismatch)
+            ]
+        );
+        assert_eq!(
+            report.unavailable,
+            [(126, "Round 126 not found".to_string())]
+        );
+    }
+
+    #[test]
+    fn audit_resumable_works() {
+        let pk = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        let rounds = [123, 124, 125, 126];
+
+        let mut cursor = AuditCursor::new();
+        let mut progress = vec![];
+        audit_resumable(&pk, QuicknetSource, &rounds, &mut cursor, |p, c| {
+            assert_eq!(c.position, p.processed);
+            progress.push(p.percent());
+            if p.processed == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        assert_eq!(progress, [25.0, 50.0]);
+        assert_eq!(cursor.position, 2);
+        assert!(!cursor.is_finished(&rounds));
+
+        let mut progress = vec![];
+        audit_resumable(&pk, QuicknetSource, &rounds, &mut cursor, |p, _| {
+            progress.push(p.percent());
+            ControlFlow::Continue(())
+        });
+        assert_eq!(progress, [75.0, 100.0]);
+        assert!(cursor.is_finished(&rounds));
+        assert_eq!(cursor.report, audit_sample(&pk, QuicknetSource, &rounds));
+
+        // finished audits do not call the callback
+        audit_resumable(&pk, QuicknetSource, &rounds, &mut cursor, |_, _| {
+            panic!("must not be called")
+        });
+        assert_eq!(
+            AuditProgress {
+                processed: 0,
+                total: 0
+            }
+            .percent(),
+            100.0
+        );
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn audit_cursor_serialization_works() {
+        let pk = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        let mut cursor = AuditCursor::new();
+        audit_resumable(
+            &pk,
+            QuicknetSource,
+            &[123, 124, 126],
+            &mut cursor,
+            |_, _| ControlFlow::Continue(()),
+        );
+        let data = crate::cbor::to_vec(&cursor).unwrap();
+        let restored: AuditCursor = crate::cbor::from_slice(&data).unwrap();
+        assert_eq!(restored, cursor);
+    }
+}
@@ -0,0 +1,166 @@
+//! Logging of inputs that failed verification (feature `audit-log`).
+//!
+//! Register a [`RejectionSink`] with [`set_sink`] to receive every input rejected by the
+//! verification methods of [`Pubkey`](crate::Pubkey) (`verify`, `verify_with_hash`,
+//! `verify_decompressed`, `verify_no_alloc` and the methods built on top of them) and by
+//! [`BatchVerifier::push`](crate::batch::BatchVerifier::push) and
+//! [`DrandPubkey::verify_batch`](crate::DrandPubkey::verify_batch) for malformed signatures.
+//! A failed combined pairing check of a batch cannot be attributed to a single beacon and is
+//! not recorded. This allows forensic analysis of suspected attacks against production verifiers.
+//!
+//! The sink is global for the process and called synchronously on the verifying thread,
+//! so it should be fast (e.g. push to a channel) and must not panic.
+
+use std::fmt;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+use crate::outcome::InvalidReason;
+use crate::scheme::Scheme;
+
+/// An input that failed verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedInput<'a> {
+    /// The scheme of the key used for verification
+    pub scheme: Scheme,
+    pub round: u64,
+    pub previous_signature: &'a [u8],
+    pub signature: &'a [u8],
+    pub reason: &'a InvalidReason,
+}
+
+/// Formats the input as a single line
+/// `round=<round> scheme=<scheme ID> previous_signature=<hex> signature=<hex> reason=<reason>`.
+impl fmt::Display for RejectedInput<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "round={} scheme={} previous_signature={} signature={} reason={}",
+            self.round,
+            self.scheme,
+            hex::encode(self.previous_signature),
+            hex::encode(self.signature),
+            self.reason
+        )
+    }
+}
+
+/// Receives inputs that failed verification
+pub trait RejectionSink: Send + Sync {
+    fn record(&self, input: &RejectedInput);
+}
+
+impl<F> RejectionSink for F
+where
+    F: Fn(&RejectedInput) + Send + Sync,
+{
+    fn record(&self, input: &RejectedInput) {
+        self(input)
+    }
+}
+
+static SINK: Lazy<RwLock<Option<Box<dyn RejectionSink>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Registers the sink for rejected inputs, replacing the previous one.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::audit_log::{clear_sink, set_sink, RejectedInput};
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+///
+/// set_sink(|input: &RejectedInput| eprintln!("rejected beacon: {}", input));
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// assert!(pk.verify(123, &[], &[0xab; 48]).is_err()); // logged
+///
+/// clear_sink();
+/// ```
+pub fn set_sink(sink: impl RejectionSink + 'static) {
+    *SINK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Box::new(sink));
+}
+
+/// Removes the registered sink
+pub fn clear_sink() {
+    *SINK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Passes the input to the registered sink, if any. The reason is only created if a sink
+/// is registered, so verification without a sink does not allocate.
+pub(crate) fn record(
+    scheme: Scheme,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+    reason: impl FnOnce() -> InvalidReason,
+) {
+    let sink = SINK.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(sink) = sink.as_ref() {
+        sink.record(&RejectedInput {
+            scheme,
+            round,
+            previous_signature,
+            signature,
+            reason: &reason(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch::BatchVerifier;
+    use crate::{G2PubkeyRfc, Pubkey};
+    use hex_literal::hex;
+    use sha2::Sha256;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn sink_receives_rejected_inputs() {
+        let lines = Arc::new(Mutex::new(Vec::<String>::new()));
+        let collected = lines.clone();
+        set_sink(move |input: &RejectedInput| {
+            // Other tests verify in parallel, only collect the rounds of this test
+            if input.round >= 1_000_000_000 {
+                collected.lock().unwrap().push(input.to_string());
+            }
+        });
+
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        assert!(!pk.verify(1_000_000_123, &[], &signature).unwrap());
+        assert!(pk.verify(1_000_000_124, &[], &[0xab; 2]).is_err());
+        // other entry points
+        assert!(!pk
+            .verify_with_hash::<Sha256>(1_000_000_126, &[], &signature)
+            .unwrap());
+        assert!(pk.verify_no_alloc(1_000_000_127, &[], &[0xab; 2]).is_err());
+        assert!(BatchVerifier::<_, 1>::new(&pk)
+            .push(1_000_000_128, &[], &[0xab; 2])
+            .is_err());
+        assert!(pk.verify_batch(&[(1_000_000_129, [0xab; 2])]).is_err());
+        clear_sink();
+        assert!(!pk.verify(1_000_000_125, &[], &signature).unwrap());
+
+        let lines = lines.lock().unwrap();
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[0],
+            "round=1000000123 scheme=bls-unchained-g1-rfc9380 previous_signature= signature=b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92 reason=Signature does not match"
+        );
+        assert!(lines[1].starts_with("round=1000000124 scheme=bls-unchained-g1-rfc9380 previous_signature= signature=abab reason=Invalid point for field signature"));
+        assert!(lines[2].starts_with("round=1000000126 "));
+        assert!(lines[2].ends_with("reason=Signature does not match"));
+        for (line, round) in lines[3..].iter().zip(1_000_000_127..) {
+            assert!(line.starts_with(&format!(
+                "round={} scheme=bls-unchained-g1-rfc9380 previous_signature= signature=abab reason=Invalid point for field signature",
+                round
+            )));
+        }
+    }
+}
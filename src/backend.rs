@@ -0,0 +1,266 @@
+//! Runtime introspection of what this build of the crate supports.
+
+use hex_literal::hex;
+
+use crate::networks::{FASTNET, MAINNET, QUICKNET};
+use crate::randomness::derive_randomness;
+use crate::scheme::Scheme;
+use crate::signature::constant_time_eq_signatures;
+use crate::verify::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// The BLS12-381 implementation used for all curve operations
+pub const BACKEND_NAME: &str = "zkcrypto/bls12_381";
+
+/// Elliptic curves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Curve {
+    Bls12_381,
+    Bn254,
+}
+
+/// The capabilities of this build, see [`capabilities`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The name of the active backend, e.g. "zkcrypto/bls12_381"
+    pub backend: &'static str,
+    /// Whether beacons can be verified in parallel (feature `rayon`)
+    pub parallel: bool,
+    /// The curves supported by the backend
+    pub curves: &'static [Curve],
+    /// The supported hash-to-curve suites as defined in RFC 9380
+    pub hash_to_curve_suites: &'static [&'static str],
+    /// The supported drand scheme IDs
+    pub schemes: &'static [&'static str],
+}
+
+impl Capabilities {
+    pub fn supports_curve(&self, curve: Curve) -> bool {
+        self.curves.contains(&curve)
+    }
+
+    pub fn supports_scheme(&self, scheme_id: &str) -> bool {
+        self.schemes.contains(&scheme_id)
+    }
+}
+
+/// Reports the capabilities of this build.
+///
+/// This allows downstream crates that are compiled with different feature sets to adapt at runtime.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::backend::{capabilities, Curve};
+///
+/// let caps = capabilities();
+/// assert!(caps.supports_curve(Curve::Bls12_381));
+/// assert!(caps.supports_scheme("bls-unchained-g1-rfc9380"));
+/// ```
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        backend: BACKEND_NAME,
+        parallel: cfg!(feature = "rayon"),
+        curves: &[Curve::Bls12_381],
+        hash_to_curve_suites: &[
+            "BLS12381G1_XMD:SHA-256_SSWU_RO_",
+            "BLS12381G2_XMD:SHA-256_SSWU_RO_",
+        ],
+        schemes: &[
+            "pedersen-bls-chained",
+            "pedersen-bls-unchained",
+            "bls-unchained-on-g1",
+            "bls-unchained-g1-rfc9380",
+        ],
+    }
+}
+
+/// A beacon with its key and randomness, checked by [`self_test`]
+#[derive(Clone, Copy)]
+pub(crate) struct KnownAnswer {
+    pub(crate) scheme: Scheme,
+    pub(crate) public_key: &'static [u8],
+    pub(crate) round: u64,
+    pub(crate) previous_signature: &'static [u8],
+    pub(crate) signature: &'static [u8],
+    pub(crate) randomness: [u8; 32],
+}
+
+/// One beacon per scheme from public relays. The randomness values were computed independently.
+pub(crate) const KNOWN_ANSWERS: [KnownAnswer; 4] = [
+    // https://api.drand.sh/public/72785
+    KnownAnswer {
+        scheme: Scheme::PedersenBlsChained,
+        public_key: MAINNET.public_key,
+        round: 72785,
+        previous_signature: &hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747"),
+        signature: &hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42"),
+        randomness: hex!("8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9"),
+    },
+    // https://pl-us.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf/public/223344
+    KnownAnswer {
+        scheme: Scheme::PedersenBlsUnchained,
+        public_key: &hex!("8200fc249deb0148eb918d6e213980c5d01acd7fc251900d9260136da3b54836ce125172399ddc69c4e3e11429b62c11"),
+        round: 223344,
+        previous_signature: &[],
+        signature: &hex!("94f6b85df7cce7237e8e7df66d794ddad092de5d8bb6a791b97e905aa89852e506ac36a792eba7021e22eebf34891f8914bf9a8dd9233ea0a4c5ca00ef8404999f899073dd2eade61fe54077fee8168f83dcb61a758b6883b38904054e64a433"),
+        randomness: hex!("f3d6adf1daa2c7877f90fb0f1a675ab0a42653a1e2a9b66fee0749d47a47bc57"),
+    },
+    // https://api3.drand.sh/dbd506d6ef76e5f386f41c651dcb808c5bcbd75471cc4eafa3f4df7ad4e4c493/public/23456
+    KnownAnswer {
+        scheme: Scheme::BlsUnchainedOnG1,
+        public_key: FASTNET.public_key,
+        round: 23456,
+        previous_signature: &[],
+        signature: &hex!("98401ef9833e75bf06fda3243e4fcf6d075d62b45c2a59d26df5d5fcbdfd0c14ee89fc035abd5528a8c25b68fbecae65"),
+        randomness: hex!("cb3e35c8b6c31306cf873435b0c7b847558be9dc75ec45d6de0d14d9e32f62d2"),
+    },
+    // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+    KnownAnswer {
+        scheme: Scheme::BlsUnchainedG1Rfc9380,
+        public_key: QUICKNET.public_key,
+        round: 123,
+        previous_signature: &[],
+        signature: &hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"),
+        randomness: hex!("fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc"),
+    },
+];
+
+/// The result of the known-answer test of one scheme, see [`self_test`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfTestCase {
+    pub scheme: Scheme,
+    pub round: u64,
+    /// The public key was decoded
+    pub key_decoded: bool,
+    /// The known beacon was accepted
+    pub valid_accepted: bool,
+    /// The known beacon with a different round was rejected
+    pub invalid_rejected: bool,
+    /// The derived randomness matches the known value
+    pub randomness_matches: bool,
+}
+
+impl SelfTestCase {
+    pub fn passed(&self) -> bool {
+        self.key_decoded && self.valid_accepted && self.invalid_rejected && self.randomness_matches
+    }
+}
+
+/// The result of [`self_test`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// One case per supported scheme
+    pub cases: Vec<SelfTestCase>,
+}
+
+impl SelfTestReport {
+    /// Returns true if all cases passed
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(SelfTestCase::passed)
+    }
+
+    /// The cases that did not pass
+    pub fn failures(&self) -> impl Iterator<Item = &SelfTestCase> {
+        self.cases.iter().filter(|case| !case.passed())
+    }
+}
+
+/// Runs a known-answer test with one beacon of every supported scheme.
+///
+/// Call this at startup to fail fast if a miscompiled backend or a broken target produces
+/// wrong results. It also warms up the code paths of verification. This takes a few pairing
+/// checks per scheme.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::backend::self_test;
+///
+/// let report = self_test();
+/// for case in report.failures() {
+///     eprintln!("Self-test failed for {}: {:?}", case.scheme, case);
+/// }
+/// assert!(report.passed());
+/// ```
+pub fn self_test() -> SelfTestReport {
+    let cases = KNOWN_ANSWERS
+        .iter()
+        .map(|answer| match answer.scheme {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                run_known_answer::<G1Pubkey>(answer)
+            }
+            Scheme::BlsUnchainedOnG1 => run_known_answer::<G2PubkeyFastnet>(answer),
+            Scheme::BlsUnchainedG1Rfc9380 => run_known_answer::<G2PubkeyRfc>(answer),
+        })
+        .collect();
+    SelfTestReport { cases }
+}
+
+fn run_known_answer<P: Pubkey>(answer: &KnownAnswer) -> SelfTestCase {
+    let mut case = SelfTestCase {
+        scheme: answer.scheme,
+        round: answer.round,
+        key_decoded: false,
+        valid_accepted: false,
+        invalid_rejected: false,
+        randomness_matches: constant_time_eq_signatures(
+            &derive_randomness(answer.signature),
+            &answer.randomness,
+        ),
+    };
+    let pk = match P::from_variable(answer.public_key) {
+        Ok(pk) => pk,
+        Err(_) => return case,
+    };
+    case.key_decoded = true;
+    case.valid_accepted = pk
+        .verify(answer.round, answer.previous_signature, answer.signature)
+        .unwrap_or(false);
+    case.invalid_rejected = !pk
+        .verify(
+            answer.round + 1,
+            answer.previous_signature,
+            answer.signature,
+        )
+        .unwrap_or(true);
+    case
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capabilities_works() {
+        let caps = capabilities();
+        assert_eq!(caps.backend, "zkcrypto/bls12_381");
+        assert_eq!(caps.parallel, cfg!(feature = "rayon"));
+        assert!(caps.supports_curve(Curve::Bls12_381));
+        assert!(!caps.supports_curve(Curve::Bn254));
+        assert!(caps.supports_scheme("pedersen-bls-chained"));
+        assert!(caps.supports_scheme("bls-unchained-on-g1"));
+        assert!(!caps.supports_scheme("bls-bn254-unchained-on-g1"));
+        assert_eq!(caps.hash_to_curve_suites.len(), 2);
+    }
+
+    #[test]
+    fn self_test_works() {
+        let report = self_test();
+        assert!(report.passed());
+        assert_eq!(report.failures().count(), 0);
+        let schemes: Vec<Scheme> = report.cases.iter().map(|case| case.scheme).collect();
+        assert_eq!(schemes, Scheme::ALL);
+
+        let mut case = report.cases[0];
+        case.invalid_rejected = false;
+        assert!(!case.passed());
+
+        // a wrong randomness is detected
+        let mut answer = KNOWN_ANSWERS[3];
+        answer.randomness[0] ^= 1;
+        let case = run_known_answer::<G2PubkeyRfc>(&answer);
+        assert!(case.valid_accepted && !case.randomness_matches && !case.passed());
+    }
+}
@@ -0,0 +1,679 @@
+//! Shared infrastructure for APIs processing many beacons in one call.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+use bls12_381::Scalar;
+use sha2::{Digest, Sha256};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "rayon")]
+use rayon::ThreadPool;
+
+use crate::beacon::Beacon;
+use crate::outcome::VerificationOutcome;
+use crate::verify::{rejected, DrandPubkey, ErrorCode, Pubkey, SchemeMarker};
+
+/// The maximum number of beacons processed in a single batch call unless configured otherwise.
+///
+/// This bounds the memory a single call can allocate when the caller (or an upstream
+/// source) is malicious.
+pub const DEFAULT_MAX_BATCH_SIZE: usize = 10_000;
+
+/// The error returned when a batch exceeds the configured maximum size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchTooLarge {
+    pub max: usize,
+    pub actual: usize,
+}
+
+impl fmt::Display for BatchTooLarge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Batch too large: maximum is {}, actual: {}",
+            self.max, self.actual
+        )
+    }
+}
+
+impl BatchTooLarge {
+    /// The stable numeric code of this error, see [`ErrorCode`](crate::ErrorCode)
+    pub fn code(&self) -> crate::ErrorCode {
+        crate::ErrorCode::BatchTooLarge
+    }
+}
+
+impl Error for BatchTooLarge {}
+
+/// Returns an error if `actual` exceeds `max`.
+///
+/// Call this before allocating anything that grows with the batch size.
+pub fn check_batch_size(actual: usize, max: usize) -> Result<(), BatchTooLarge> {
+    if actual > max {
+        Err(BatchTooLarge { max, actual })
+    } else {
+        Ok(())
+    }
+}
+
+/// Verifies the beacons in parallel on rayon's global thread pool (feature `rayon`).
+///
+/// The outcomes are in the same order as the beacons. Returns an error for more than
+/// [`DEFAULT_MAX_BATCH_SIZE`] beacons.
+#[cfg(feature = "rayon")]
+pub fn verify_parallel<P: Pubkey + Sync>(
+    pubkey: &P,
+    beacons: &[Beacon],
+) -> Result<Vec<VerificationOutcome>, BatchTooLarge> {
+    check_batch_size(beacons.len(), DEFAULT_MAX_BATCH_SIZE)?;
+    Ok(verify_parallel_unbounded(pubkey, beacons))
+}
+
+/// Like [`verify_parallel`] without the size check, for callers that bound the batch themselves
+#[cfg(feature = "rayon")]
+pub(crate) fn verify_parallel_unbounded<P: Pubkey + Sync>(
+    pubkey: &P,
+    beacons: &[Beacon],
+) -> Vec<VerificationOutcome> {
+    beacons
+        .par_iter()
+        .map(|beacon| {
+            pubkey.verify_with_outcome(beacon.round, &beacon.previous_signature, &beacon.signature)
+        })
+        .collect()
+}
+
+/// Like [`verify_parallel`] but uses the given thread pool instead of the global one.
+///
+/// Use this to bound the CPU usage of verification, e.g. with a pool of 2 threads:
+///
+/// ```
+/// use drand_verify::batch::verify_parallel_in;
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pool = rayon::ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+///
+/// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+/// let beacons = [Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// }];
+/// let outcomes = verify_parallel_in(&pool, &pk, &beacons).unwrap();
+/// assert!(outcomes[0].is_valid());
+/// ```
+#[cfg(feature = "rayon")]
+pub fn verify_parallel_in<P: Pubkey + Sync>(
+    pool: &ThreadPool,
+    pubkey: &P,
+    beacons: &[Beacon],
+) -> Result<Vec<VerificationOutcome>, BatchTooLarge> {
+    check_batch_size(beacons.len(), DEFAULT_MAX_BATCH_SIZE)?;
+    Ok(pool.install(|| verify_parallel_unbounded(pubkey, beacons)))
+}
+
+/// The result of a batch call with a deadline
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeadlineOutcomes {
+    /// One entry per input beacon, in the same order. `None` if the beacon was skipped
+    /// because the deadline passed.
+    pub outcomes: Vec<Option<VerificationOutcome>>,
+}
+
+impl DeadlineOutcomes {
+    /// Returns true if all beacons were processed before the deadline
+    pub fn is_complete(&self) -> bool {
+        self.outcomes.iter().all(Option::is_some)
+    }
+
+    /// The number of beacons skipped because of the deadline
+    pub fn skipped(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.is_none())
+            .count()
+    }
+}
+
+/// Verifies the beacons in order until all are processed or the deadline passed.
+///
+/// The deadline is checked before each beacon, so a call can exceed it by the time of one
+/// verification. Returns an error for more than [`DEFAULT_MAX_BATCH_SIZE`] beacons.
+/// Note that `Instant::now` panics on wasm32-unknown-unknown.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use drand_verify::batch::verify_with_deadline;
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+/// let beacons = [Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// }];
+/// let result =
+///     verify_with_deadline(&pk, &beacons, Instant::now() + Duration::from_secs(10)).unwrap();
+/// assert!(result.is_complete());
+///
+/// // Nothing is processed after the deadline
+/// let result = verify_with_deadline(&pk, &beacons, Instant::now()).unwrap();
+/// assert_eq!(result.skipped(), 1);
+/// ```
+pub fn verify_with_deadline<P: Pubkey>(
+    pubkey: &P,
+    beacons: &[Beacon],
+    deadline: Instant,
+) -> Result<DeadlineOutcomes, BatchTooLarge> {
+    check_batch_size(beacons.len(), DEFAULT_MAX_BATCH_SIZE)?;
+    let mut expired = false;
+    let outcomes = beacons
+        .iter()
+        .map(|beacon| {
+            expired = expired || Instant::now() >= deadline;
+            if expired {
+                return None;
+            }
+            Some(pubkey.verify_with_outcome(
+                beacon.round,
+                &beacon.previous_signature,
+                &beacon.signature,
+            ))
+        })
+        .collect();
+    Ok(DeadlineOutcomes { outcomes })
+}
+
+/// Like [`verify_parallel`] but skips the beacons whose verification did not start before
+/// the deadline (feature `rayon`).
+#[cfg(feature = "rayon")]
+pub fn verify_parallel_with_deadline<P: Pubkey + Sync>(
+    pubkey: &P,
+    beacons: &[Beacon],
+    deadline: Instant,
+) -> Result<DeadlineOutcomes, BatchTooLarge> {
+    check_batch_size(beacons.len(), DEFAULT_MAX_BATCH_SIZE)?;
+    let outcomes = beacons
+        .par_iter()
+        .map(|beacon| {
+            if Instant::now() >= deadline {
+                return None;
+            }
+            Some(pubkey.verify_with_outcome(
+                beacon.round,
+                &beacon.previous_signature,
+                &beacon.signature,
+            ))
+        })
+        .collect();
+    Ok(DeadlineOutcomes { outcomes })
+}
+
+type Point<S> = <DrandPubkey<S> as Pubkey>::Other;
+
+/// Verifies up to `N` beacons with a single pairing check, using only stack memory.
+///
+/// Every beacon is weighted with a scalar `r_i` derived from all pushed inputs, and
+/// [`BatchVerifier::verify`] checks `e(Σ r_i σ_i, g) == e(Σ r_i H_i, pk)`. This costs two
+/// pairings for the whole batch instead of two per beacon. If the check fails, at least one
+/// beacon is invalid and the beacons have to be verified one by one to find it.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::batch::BatchVerifier;
+/// use drand_verify::{networks, ErrorCode, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+///
+/// let mut batch = BatchVerifier::<_, 1>::new(&pk);
+/// batch.push(123, &[], &signature).unwrap();
+/// assert!(batch.verify());
+/// assert_eq!(batch.push(123, &[], &signature), Err(ErrorCode::BatchTooLarge));
+/// ```
+pub struct BatchVerifier<'a, S: SchemeMarker, const N: usize> {
+    pubkey: &'a DrandPubkey<S>,
+    /// The decoded signature and message of each beacon
+    entries: [Option<(Point<S>, Point<S>)>; N],
+    len: usize,
+    /// Hashes all pushed inputs to derive the scalars
+    transcript: Sha256,
+}
+
+impl<'a, S: SchemeMarker, const N: usize> BatchVerifier<'a, S, N> {
+    pub fn new(pubkey: &'a DrandPubkey<S>) -> Self {
+        Self {
+            pubkey,
+            entries: [(); N].map(|_| None),
+            len: 0,
+            transcript: Sha256::new(),
+        }
+    }
+
+    /// Adds a beacon to the batch.
+    ///
+    /// Returns [`ErrorCode::BatchTooLarge`] if the batch holds `N` beacons already and the
+    /// usual error codes for malformed signatures.
+    pub fn push(
+        &mut self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<(), ErrorCode> {
+        if self.len == N {
+            return Err(ErrorCode::BatchTooLarge);
+        }
+        let entry = self
+            .pubkey
+            .prepare_no_alloc(round, previous_signature, signature)
+            .map_err(|err| {
+                rejected(
+                    self.pubkey.scheme(),
+                    round,
+                    previous_signature,
+                    signature,
+                    err,
+                )
+            })?;
+        absorb(&mut self.transcript, round, previous_signature, signature);
+        self.entries[self.len] = Some(entry);
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Removes all beacons so the verifier can be reused
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.len = 0;
+        self.transcript = Sha256::new();
+    }
+
+    /// Returns true if all beacons in the batch are valid. An empty batch is valid.
+    pub fn verify(&self) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let seed = self.transcript.clone().finalize();
+        let entries = self.entries[..self.len]
+            .iter()
+            .flatten()
+            .enumerate()
+            .map(|(index, (sigma, msg))| (sigma, msg, batch_scalar(&seed, index)));
+        self.pubkey.combined_pairing_check_no_alloc(entries)
+    }
+}
+
+impl<S: SchemeMarker> DrandPubkey<S> {
+    /// Verifies many unchained beacons given as `(round, signature)` with a single pairing check.
+    ///
+    /// This works like [`BatchVerifier`] with heap memory: all signatures and messages are
+    /// combined with scalars derived from the inputs, so the whole batch costs one multi-Miller
+    /// loop and one final exponentiation. Returns `Ok(false)` if at least one beacon is invalid,
+    /// use [`Pubkey::verify`] to find it. An empty batch is valid. Batches of more than
+    /// [`DEFAULT_MAX_BATCH_SIZE`] beacons are rejected with [`ErrorCode::BatchTooLarge`].
+    /// Chained beacons can be checked with [`BatchVerifier`] or [`check_link`](crate::check_link).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+    /// use hex_literal::hex;
+    ///
+    /// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+    /// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    ///
+    /// assert_eq!(pk.verify_batch(&[(123, signature)]), Ok(true));
+    /// assert_eq!(pk.verify_batch(&[(124, signature)]), Ok(false));
+    /// ```
+    pub fn verify_batch<T: AsRef<[u8]>>(&self, beacons: &[(u64, T)]) -> Result<bool, ErrorCode> {
+        self.combined_verify(
+            beacons.len(),
+            beacons
+                .iter()
+                .map(|(round, signature)| (*round, &[][..], signature.as_ref())),
+        )
+    }
+
+    /// Verifies many beacons with a single pairing check like [`DrandPubkey::verify_batch`],
+    /// but for chained networks as well.
+    ///
+    /// Every beacon is verified against its own previous signature. Checking only the links
+    /// between beacons is not enough: a signature covers the bytes of the previous signature,
+    /// not whether that signature is valid.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{Beacon, G1Pubkey, Pubkey};
+    /// use hex_literal::hex;
+    ///
+    /// let pk = G1Pubkey::from_fixed(hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31")).unwrap();
+    /// let beacon = Beacon {
+    ///     round: 72785,
+    ///     previous_signature: hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").to_vec(),
+    ///     signature: hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").to_vec(),
+    /// };
+    /// assert_eq!(pk.verify_beacon_batch(&[beacon.clone()]), Ok(true));
+    ///
+    /// let wrong_round = Beacon { round: 72786, ..beacon };
+    /// assert_eq!(pk.verify_beacon_batch(&[wrong_round]), Ok(false));
+    /// ```
+    pub fn verify_beacon_batch(&self, beacons: &[Beacon]) -> Result<bool, ErrorCode> {
+        self.combined_verify(
+            beacons.len(),
+            beacons.iter().map(|beacon| {
+                (
+                    beacon.round,
+                    beacon.previous_signature.as_slice(),
+                    beacon.signature.as_slice(),
+                )
+            }),
+        )
+    }
+
+    /// Verifies `len` beacons given as `(round, previous_signature, signature)`
+    fn combined_verify<'b>(
+        &self,
+        len: usize,
+        beacons: impl Iterator<Item = (u64, &'b [u8], &'b [u8])>,
+    ) -> Result<bool, ErrorCode> {
+        check_batch_size(len, DEFAULT_MAX_BATCH_SIZE).map_err(|err| err.code())?;
+        if len == 0 {
+            return Ok(true);
+        }
+        let mut transcript = Sha256::new();
+        let entries = beacons
+            .map(|(round, previous_signature, signature)| {
+                absorb(&mut transcript, round, previous_signature, signature);
+                self.prepare_no_alloc(round, previous_signature, signature)
+                    .map_err(|err| {
+                        rejected(self.scheme(), round, previous_signature, signature, err)
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let seed = transcript.finalize();
+        let entries = entries
+            .iter()
+            .enumerate()
+            .map(|(index, (sigma, msg))| (sigma, msg, batch_scalar(&seed, index)));
+        Ok(self.combined_pairing_check(entries))
+    }
+}
+
+/// Adds the inputs of a beacon to the transcript the scalars are derived from
+fn absorb(transcript: &mut Sha256, round: u64, previous_signature: &[u8], signature: &[u8]) {
+    transcript.update(round.to_be_bytes());
+    transcript.update((previous_signature.len() as u64).to_be_bytes());
+    transcript.update(previous_signature);
+    transcript.update((signature.len() as u64).to_be_bytes());
+    transcript.update(signature);
+}
+
+/// The scalar of the beacon at `index`, which the signer cannot predict when creating
+/// the signatures.
+fn batch_scalar(seed: &[u8], index: usize) -> Scalar {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update((index as u64).to_be_bytes());
+    let mut wide = [0u8; 64];
+    wide[..32].copy_from_slice(&hasher.finalize());
+    Scalar::from_bytes_wide(&wide)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_batch_size_works() {
+        assert_eq!(check_batch_size(0, 0), Ok(()));
+        assert_eq!(check_batch_size(10, 10), Ok(()));
+        assert_eq!(
+            check_batch_size(11, 10),
+            Err(BatchTooLarge {
+                max: 10,
+                actual: 11
+            })
+        );
+        assert_eq!(
+            BatchTooLarge {
+                max: 10,
+                actual: 11
+            }
+            .to_string(),
+            "Batch too large: maximum is 10, actual: 11"
+        );
+    }
+
+    #[test]
+    fn verify_with_deadline_works() {
+        use crate::G2PubkeyRfc;
+        use hex_literal::hex;
+        use std::time::Duration;
+
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let valid = Beacon {
+            round: 123,
+            previous_signature: vec![],
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        };
+        let mut invalid = valid.clone();
+        invalid.round = 124;
+        let beacons = [valid, invalid];
+
+        let result =
+            verify_with_deadline(&pk, &beacons, Instant::now() + Duration::from_secs(60)).unwrap();
+        assert!(result.is_complete());
+        assert_eq!(result.skipped(), 0);
+        let valid: Vec<bool> = result
+            .outcomes
+            .iter()
+            .map(|outcome| outcome.as_ref().unwrap().is_valid())
+            .collect();
+        assert_eq!(valid, [true, false]);
+
+        let result = verify_with_deadline(&pk, &beacons, Instant::now()).unwrap();
+        assert!(!result.is_complete());
+        assert_eq!(result.outcomes, [None, None]);
+
+        assert!(verify_with_deadline(&pk, &[], Instant::now())
+            .unwrap()
+            .is_complete());
+        assert_eq!(
+            verify_with_deadline(&pk, &too_many_beacons(), Instant::now()),
+            Err(BatchTooLarge {
+                max: DEFAULT_MAX_BATCH_SIZE,
+                actual: DEFAULT_MAX_BATCH_SIZE + 1
+            })
+        );
+
+        #[cfg(feature = "rayon")]
+        {
+            let result = verify_parallel_with_deadline(
+                &pk,
+                &beacons,
+                Instant::now() + Duration::from_secs(60),
+            )
+            .unwrap();
+            assert!(result.is_complete());
+            let result = verify_parallel_with_deadline(&pk, &beacons, Instant::now()).unwrap();
+            assert_eq!(result.skipped(), 2);
+            assert!(
+                verify_parallel_with_deadline(&pk, &too_many_beacons(), Instant::now()).is_err()
+            );
+        }
+    }
+
+    /// One beacon more than [`DEFAULT_MAX_BATCH_SIZE`]
+    fn too_many_beacons() -> Vec<Beacon> {
+        let beacon = Beacon {
+            round: 1,
+            previous_signature: vec![],
+            signature: vec![],
+        };
+        vec![beacon; DEFAULT_MAX_BATCH_SIZE + 1]
+    }
+
+    #[test]
+    fn batch_verifier_works() {
+        use crate::{G1Pubkey, G2PubkeyRfc};
+        use hex_literal::hex;
+
+        let pk = G1Pubkey::from_fixed(hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31")).unwrap();
+        // https://api.drand.sh/public/1337
+        let prev_1337 = hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2");
+        let sig_1337 = hex!("945b08dcb30e24da281ccf14a646f0630ceec515af5c5895e18cc1b19edd65d156b71c776a369af3487f1bc6af1062500b059e01095cc0eedce91713977d7735cac675554edfa0d0481bb991ed93d333d08286192c05bf6b65d20f23a37fc7bb");
+        // https://api.drand.sh/public/72785
+        let prev_72785 = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        let sig_72785 = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+
+        let mut batch = BatchVerifier::<_, 3>::new(&pk);
+        assert!(batch.is_empty());
+        assert!(batch.verify());
+        batch.push(1337, &prev_1337, &sig_1337).unwrap();
+        batch.push(72785, &prev_72785, &sig_72785).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert!(batch.verify());
+
+        // one invalid beacon fails the batch
+        batch.push(72786, &prev_72785, &sig_72785).unwrap();
+        assert!(!batch.verify());
+        assert_eq!(
+            batch.push(1337, &prev_1337, &sig_1337),
+            Err(ErrorCode::BatchTooLarge)
+        );
+
+        // swapped signatures do not cancel out
+        batch.clear();
+        assert!(batch.is_empty());
+        batch.push(1337, &prev_1337, &sig_72785).unwrap();
+        batch.push(72785, &prev_72785, &sig_1337).unwrap();
+        assert!(!batch.verify());
+
+        // malformed inputs are rejected on push
+        batch.clear();
+        assert_eq!(
+            batch.push(1337, &prev_1337, &[0xff; 96]),
+            Err(ErrorCode::InvalidSignature)
+        );
+        assert_eq!(
+            batch.push(1337, &prev_1337, &sig_1337[..95]),
+            Err(ErrorCode::InvalidSignatureLength)
+        );
+        assert!(batch.is_empty());
+
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let mut batch = BatchVerifier::<_, 2>::new(&pk);
+        batch.push(123, &[], &signature).unwrap();
+        batch.push(123, &[], &signature).unwrap();
+        assert!(batch.verify());
+        batch.clear();
+        batch.push(123, &[], &signature).unwrap();
+        batch.push(124, &[], &signature).unwrap();
+        assert!(!batch.verify());
+    }
+
+    #[test]
+    fn verify_batch_works() {
+        use crate::G2PubkeyRfc;
+        use hex_literal::hex;
+
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        assert_eq!(pk.verify_batch::<&[u8]>(&[]), Ok(true));
+        assert_eq!(
+            pk.verify_batch(&[(123, signature), (123, signature)]),
+            Ok(true)
+        );
+        assert_eq!(
+            pk.verify_batch(&[(123, signature), (124, signature)]),
+            Ok(false)
+        );
+        assert_eq!(
+            pk.verify_batch(&[(123, &signature[..]), (124, &signature[..47])]),
+            Err(ErrorCode::InvalidSignatureLength)
+        );
+        let too_many = vec![(123, signature); DEFAULT_MAX_BATCH_SIZE + 1];
+        assert_eq!(pk.verify_batch(&too_many), Err(ErrorCode::BatchTooLarge));
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn verify_batch_works_for_many_rounds() {
+        use crate::signer::SecretKey;
+        use crate::G2PubkeyRfc;
+
+        let sk = SecretKey::from_seed(b"verify_batch");
+        let pk = G2PubkeyRfc::from_variable(&sk.public_key::<G2PubkeyRfc>()).unwrap();
+        let mut beacons: Vec<(u64, Vec<u8>)> = (1..=50)
+            .map(|round| (round, sk.sign::<G2PubkeyRfc>(round, &[])))
+            .collect();
+        assert_eq!(pk.verify_batch(&beacons), Ok(true));
+
+        // swapped signatures do not cancel out
+        let signature = beacons[3].1.clone();
+        beacons[3].1 = beacons[4].1.clone();
+        beacons[4].1 = signature;
+        assert_eq!(pk.verify_batch(&beacons), Ok(false));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn verify_parallel_works() {
+        use crate::G2PubkeyRfc;
+        use hex_literal::hex;
+
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let valid = Beacon {
+            round: 123,
+            previous_signature: vec![],
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        };
+        let mut invalid = valid.clone();
+        invalid.round = 124;
+        let beacons = [valid.clone(), invalid.clone(), valid];
+
+        let outcomes = verify_parallel(&pk, &beacons).unwrap();
+        let valid: Vec<bool> = outcomes.iter().map(VerificationOutcome::is_valid).collect();
+        assert_eq!(valid, [true, false, true]);
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let outcomes = verify_parallel_in(&pool, &pk, &beacons).unwrap();
+        let valid: Vec<bool> = outcomes.iter().map(VerificationOutcome::is_valid).collect();
+        assert_eq!(valid, [true, false, true]);
+
+        assert!(verify_parallel(&pk, &[]).unwrap().is_empty());
+
+        let too_large = Err(BatchTooLarge {
+            max: DEFAULT_MAX_BATCH_SIZE,
+            actual: DEFAULT_MAX_BATCH_SIZE + 1,
+        });
+        assert_eq!(verify_parallel(&pk, &too_many_beacons()), too_large);
+        assert_eq!(
+            verify_parallel_in(&pool, &pk, &too_many_beacons()),
+            too_large
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use std::error::Error;
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::chain_hash::ChainHash;
+use crate::outcome::VerificationOutcome;
+use crate::randomness::derive_randomness;
+use crate::signature::constant_time_eq_signatures;
+use crate::verify::Pubkey;
+
+/// A drand beacon as received from a relay. This is not verified.
+///
+/// With the feature `serde`, this (de)serializes from and to the JSON of the
+/// `/public/{round}` endpoint, i.e. with hex encoded bytes. The `randomness` key is
+/// optional when deserializing. If present, it must match the signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "BeaconJson", into = "BeaconJson")
+)]
+pub struct Beacon {
+    pub round: u64,
+    /// The signature of the previous round. Empty for unchained networks.
+    pub previous_signature: Vec<u8>,
+    /// The signature in compressed format
+    pub signature: Vec<u8>,
+}
+
+/// The error returned by [`check_link`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LinkError {
+    /// The round of the next beacon is not the round after the previous beacon
+    RoundGap { previous: u64, next: u64 },
+    /// The previous signature of the next beacon is not the signature of the previous beacon
+    SignatureMismatch { round: u64 },
+}
+
+impl fmt::Display for LinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::RoundGap { previous, next } => {
+                write!(f, "Round {} does not follow round {}", next, previous)
+            }
+            LinkError::SignatureMismatch { round } => write!(
+                f,
+                "Previous signature of round {} does not match the signature of round {}",
+                round,
+                round - 1
+            ),
+        }
+    }
+}
+
+impl Error for LinkError {}
+
+/// Checks that `next` is the beacon directly following `prev` in a chained network,
+/// i.e. `next.round == prev.round + 1` and `next.previous_signature == prev.signature`.
+///
+/// No signatures are verified, so this is a cheap pre-filter when ingesting archived chains.
+/// Verifying `next` only authenticates the bytes of `prev.signature`, not that this signature
+/// is valid. Each beacon of a linked sequence must be verified on its own, e.g. with
+/// [`DrandPubkey::verify_beacon_batch`](crate::DrandPubkey::verify_beacon_batch).
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{check_link, Beacon};
+///
+/// let prev = Beacon { round: 1, previous_signature: vec![0; 96], signature: vec![1; 96] };
+/// let next = Beacon { round: 2, previous_signature: vec![1; 96], signature: vec![2; 96] };
+/// assert!(check_link(&prev, &next).is_ok());
+/// assert!(check_link(&next, &prev).is_err());
+/// ```
+pub fn check_link(prev: &Beacon, next: &Beacon) -> Result<(), LinkError> {
+    if prev.round.checked_add(1) != Some(next.round) {
+        return Err(LinkError::RoundGap {
+            previous: prev.round,
+            next: next.round,
+        });
+    }
+    if !constant_time_eq_signatures(&next.previous_signature, &prev.signature) {
+        return Err(LinkError::SignatureMismatch { round: next.round });
+    }
+    Ok(())
+}
+
+/// Domain separation tag of [`VerifiedBeacon::canonical_digest`]
+pub const CANONICAL_BEACON_V1: &str = "drand-verify/canonical-beacon/v1";
+
+/// A beacon that passed signature verification.
+///
+/// Instances can only be created by this crate as the result of a successful verification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedBeacon {
+    round: u64,
+    previous_signature: Vec<u8>,
+    signature: Vec<u8>,
+    randomness: [u8; 32],
+}
+
+impl VerifiedBeacon {
+    /// Must only be called after the signature was verified successfully
+    pub(crate) fn new(round: u64, previous_signature: &[u8], signature: &[u8]) -> Self {
+        Self {
+            round,
+            previous_signature: previous_signature.to_vec(),
+            signature: signature.to_vec(),
+            randomness: derive_randomness(signature),
+        }
+    }
+
+    pub fn round(&self) -> u64 {
+        self.round
+    }
+
+    /// The previous signature. This is empty for unchained networks.
+    pub fn previous_signature(&self) -> &[u8] {
+        &self.previous_signature
+    }
+
+    /// The signature in compressed format
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// The randomness derived from the signature using [`derive_randomness`]
+    pub fn randomness(&self) -> [u8; 32] {
+        self.randomness
+    }
+
+    /// A canonical encoding of the beacon of the chain with the given hash, for signing
+    /// or Merkle-izing verified beacons.
+    ///
+    /// The encoding is
+    ///
+    /// ```text
+    /// chain_hash (32 bytes) || round || len(signature) || signature || randomness (32 bytes)
+    /// ```
+    ///
+    /// with the round and length as 8 byte big endian integers. The previous signature is not
+    /// included. The encoding never changes, see [`VerifiedBeacon::canonical_digest`].
+    pub fn canonical_bytes(&self, chain_hash: &ChainHash) -> Vec<u8> {
+        let mut out = Vec::with_capacity(32 + 8 + 8 + self.signature.len() + 32);
+        out.extend_from_slice(chain_hash.as_bytes());
+        out.extend_from_slice(&self.round.to_be_bytes());
+        out.extend_from_slice(&(self.signature.len() as u64).to_be_bytes());
+        out.extend_from_slice(&self.signature);
+        out.extend_from_slice(&self.randomness);
+        out
+    }
+
+    /// `SHA-256(CANONICAL_BEACON_V1 || canonical_bytes)`, a stable digest of what was verified
+    /// that third parties can reproduce from the beacon and chain hash.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+    /// use hex_literal::hex;
+    ///
+    /// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+    /// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    /// let beacon = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+    /// assert_eq!(
+    ///     beacon.canonical_digest(&networks::QUICKNET.chain_hash),
+    ///     hex!("01f1f28782a1c7316886f1047aec227d03f229a268a3ab4f86b9e457c4c38502")
+    /// );
+    /// ```
+    pub fn canonical_digest(&self, chain_hash: &ChainHash) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(CANONICAL_BEACON_V1.as_bytes());
+        hasher.update(self.canonical_bytes(chain_hash));
+        hasher.finalize().into()
+    }
+
+    /// A random number generator seeded by the randomness, for sampling from the
+    /// distributions of the `rand` crate (feature `rand`).
+    ///
+    /// See [`BeaconRng`](crate::sampler::BeaconRng) for the fixed byte stream.
+    #[cfg(feature = "rand")]
+    pub fn distribution_sampler(&self) -> crate::sampler::BeaconRng {
+        crate::sampler::BeaconRng::new(self.randomness)
+    }
+}
+
+/// A beacon loaded from storage, e.g. a `BeaconStore` (feature `store`) or a
+/// [`VerifiedBeacon`] serialized before. This is not verified.
+///
+/// Anyone who can write to the storage can create such a beacon, so it must be verified again
+/// with [`StoredBeacon::verify`] before use. With the feature `serde`, this deserializes from
+/// the serialized form of [`VerifiedBeacon`].
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey, StoredBeacon};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let verified = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+///
+/// let stored = StoredBeacon::from(&verified);
+/// assert_eq!(stored.verify(&pk).into_verified(), Some(verified));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredBeacon {
+    pub round: u64,
+    /// The signature of the previous round. Empty for unchained networks.
+    pub previous_signature: Vec<u8>,
+    /// The signature in compressed format
+    pub signature: Vec<u8>,
+}
+
+impl StoredBeacon {
+    /// Verifies the beacon again
+    pub fn verify<P: Pubkey>(&self, pubkey: &P) -> VerificationOutcome {
+        pubkey.verify_with_outcome(self.round, &self.previous_signature, &self.signature)
+    }
+}
+
+impl From<&VerifiedBeacon> for StoredBeacon {
+    fn from(beacon: &VerifiedBeacon) -> Self {
+        Self {
+            round: beacon.round,
+            previous_signature: beacon.previous_signature.clone(),
+            signature: beacon.signature.clone(),
+        }
+    }
+}
+
+impl From<StoredBeacon> for Beacon {
+    fn from(stored: StoredBeacon) -> Self {
+        Self {
+            round: stored.round,
+            previous_signature: stored.previous_signature,
+            signature: stored.signature,
+        }
+    }
+}
+
+/// The serialized form of [`VerifiedBeacon`].
+///
+/// The fields are ordered by the length of their names such that the CBOR encoding
+/// is deterministic as defined in RFC 8949, section 4.2.1.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct VerifiedBeaconRepr {
+    round: u64,
+    #[serde(with = "serde_bytes")]
+    signature: Vec<u8>,
+    #[serde(with = "serde_bytes")]
+    randomness: [u8; 32],
+    #[serde(with = "serde_bytes")]
+    previous_signature: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for VerifiedBeacon {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        VerifiedBeaconRepr {
+            round: self.round,
+            signature: self.signature.clone(),
+            randomness: self.randomness,
+            previous_signature: self.previous_signature.clone(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a [`VerifiedBeacon`] serialized before.
+///
+/// The randomness is checked to match the signature in order to detect corrupted data.
+/// This does not authenticate anything, see [`StoredBeacon::verify`].
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for StoredBeacon {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = VerifiedBeaconRepr::deserialize(deserializer)?;
+        if !constant_time_eq_signatures(&derive_randomness(&repr.signature), &repr.randomness) {
+            return Err(serde::de::Error::custom(
+                "Randomness does not match signature",
+            ));
+        }
+        Ok(StoredBeacon {
+            round: repr.round,
+            previous_signature: repr.previous_signature,
+            signature: repr.signature,
+        })
+    }
+}
+
+/// The JSON of the `/public/{round}` endpoint
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BeaconJson {
+    round: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    randomness: Option<String>,
+    signature: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    previous_signature: String,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BeaconJson> for Beacon {
+    type Error = String;
+
+    fn try_from(json: BeaconJson) -> Result<Self, String> {
+        let signature =
+            hex::decode(&json.signature).map_err(|_| "signature must be hex encoded")?;
+        if let Some(randomness) = json.randomness {
+            let randomness =
+                hex::decode(&randomness).map_err(|_| "randomness must be hex encoded")?;
+            if !constant_time_eq_signatures(&derive_randomness(&signature), &randomness) {
+                return Err("Randomness does not match signature".to_string());
+            }
+        }
+        Ok(Beacon {
+            round: json.round,
+            previous_signature: hex::decode(&json.previous_signature)
+                .map_err(|_| "previous_signature must be hex encoded")?,
+            signature,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Beacon> for BeaconJson {
+    fn from(beacon: Beacon) -> Self {
+        BeaconJson {
+            round: beacon.round,
+            randomness: Some(hex::encode(derive_randomness(&beacon.signature))),
+            signature: hex::encode(&beacon.signature),
+            previous_signature: hex::encode(&beacon.previous_signature),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn canonical_bytes_works() {
+        let chain_hash = ChainHash::new([0xab; 32]);
+        let beacon = VerifiedBeacon::new(0x0102, &[0xcc; 96], &[0x01; 48]);
+        let bytes = beacon.canonical_bytes(&chain_hash);
+        assert_eq!(bytes.len(), 128);
+        assert_eq!(bytes[..32], [0xab; 32]);
+        assert_eq!(bytes[32..40], [0, 0, 0, 0, 0, 0, 1, 2]);
+        assert_eq!(bytes[40..48], [0, 0, 0, 0, 0, 0, 0, 48]);
+        assert_eq!(bytes[48..96], [0x01; 48]);
+        assert_eq!(bytes[96..], beacon.randomness());
+
+        // the previous signature is not part of the encoding
+        let other = VerifiedBeacon::new(0x0102, &[], &[0x01; 48]);
+        assert_eq!(other.canonical_bytes(&chain_hash), bytes);
+        assert_eq!(
+            other.canonical_digest(&chain_hash),
+            beacon.canonical_digest(&chain_hash)
+        );
+        assert_ne!(
+            beacon.canonical_digest(&ChainHash::new([0; 32])),
+            beacon.canonical_digest(&chain_hash)
+        );
+    }
+
+    #[test]
+    fn check_link_works() {
+        // The signature of round 1336 is the previous signature of https://api.drand.sh/public/1337
+        let prev = Beacon {
+            round: 1336,
+            previous_signature: vec![0xaa; 96], // not relevant for the link
+            signature: hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2").to_vec(),
+        };
+        // curl -sS https://api.drand.sh/public/1337
+        let next = Beacon {
+            round: 1337,
+            previous_signature: hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2").to_vec(),
+            signature: hex!("945b08dcb30e24da281ccf14a646f0630ceec515af5c5895e18cc1b19edd65d156b71c776a369af3487f1bc6af1062500b059e01095cc0eedce91713977d7735cac675554edfa0d0481bb991ed93d333d08286192c05bf6b65d20f23a37fc7bb").to_vec(),
+        };
+        assert_eq!(check_link(&prev, &next), Ok(()));
+
+        assert_eq!(
+            check_link(&next, &prev),
+            Err(LinkError::RoundGap {
+                previous: 1337,
+                next: 1336
+            })
+        );
+
+        let mut skipped = next.clone();
+        skipped.round = 1338;
+        assert_eq!(
+            check_link(&prev, &skipped),
+            Err(LinkError::RoundGap {
+                previous: 1336,
+                next: 1338
+            })
+        );
+
+        let mut broken = next.clone();
+        broken.previous_signature[0] ^= 0x01;
+        assert_eq!(
+            check_link(&prev, &broken),
+            Err(LinkError::SignatureMismatch { round: 1337 })
+        );
+
+        let mut last = prev.clone();
+        last.round = u64::MAX;
+        assert!(check_link(&last, &next).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_works() {
+        // curl -sS https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let json = r#"{"round":123,"randomness":"fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc","signature":"b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"}"#;
+        let beacon: Beacon = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            beacon,
+            Beacon {
+                round: 123,
+                previous_signature: vec![],
+                signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+            }
+        );
+        assert_eq!(serde_json::to_string(&beacon).unwrap(), json);
+
+        // curl -sS https://api.drand.sh/public/72785
+        let json = r#"{"round":72785,"randomness":"8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9","signature":"82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42","previous_signature":"a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747"}"#;
+        let beacon: Beacon = serde_json::from_str(json).unwrap();
+        assert_eq!(beacon.round, 72785);
+        assert_eq!(beacon.previous_signature.len(), 96);
+        assert_eq!(serde_json::to_string(&beacon).unwrap(), json);
+
+        // randomness is optional
+        let beacon: Beacon =
+            serde_json::from_str(r#"{"round":1,"signature":"0102","previous_signature":"03"}"#)
+                .unwrap();
+        assert_eq!(beacon.signature, [1, 2]);
+        assert_eq!(beacon.previous_signature, [3]);
+
+        let wrong_randomness = json.replace("8b676484", "00000000");
+        let err = serde_json::from_str::<Beacon>(&wrong_randomness).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Randomness does not match signature"));
+        let err = serde_json::from_str::<Beacon>(r#"{"round":1,"signature":"zz"}"#).unwrap_err();
+        assert!(err.to_string().contains("signature must be hex encoded"));
+    }
+}
@@ -0,0 +1,444 @@
+//! A verification microservice for one drand network.
+//!
+//! Endpoints:
+//!
+//! - `POST /verify` takes a beacon as returned by the `/public/{round}` endpoint of a relay,
+//!   or an array of such beacons, and answers with the verification result(s).
+//! - `GET /randomness/{round}` returns the randomness of a round verified before.
+//!
+//! Verification results are cached with [`CachingPubkey`] and arrays of unchained beacons are
+//! checked with a single [batch verification](DrandPubkey::verify_batch).
+
+use std::collections::BTreeMap;
+use std::env;
+use std::io::Read;
+use std::process::exit;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use drand_verify::batch::{check_batch_size, DEFAULT_MAX_BATCH_SIZE};
+use drand_verify::caching::CachingPubkey;
+use drand_verify::descriptor::NetworkDescriptor;
+use drand_verify::error_json::ErrorReport;
+use drand_verify::{
+    derive_randomness, networks, Beacon, BlsUnchainedG1Rfc9380, BlsUnchainedOnG1, DrandPubkey,
+    ErrorCode, PedersenBls, Pubkey, Scheme, SchemeMarker, VerificationError,
+};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Response, Server};
+
+const USAGE: &str = "Usage: drand-verifierd [--network <name> | --network-file <file>] [--listen <address>] [--workers <n>] [--cache <n>] [--history <n>]";
+
+/// Request bodies larger than this are rejected
+const MAX_BODY_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Verifies beacons of one network
+trait Verifier: Send + Sync {
+    fn verify(&self, beacon: &Beacon) -> Result<bool, VerificationError>;
+
+    /// Verifies all beacons. The result has one entry per beacon.
+    fn verify_all(&self, beacons: &[Beacon]) -> Vec<Result<bool, VerificationError>>;
+}
+
+impl<S: SchemeMarker> Verifier for CachingPubkey<DrandPubkey<S>>
+where
+    DrandPubkey<S>: Send + Sync,
+{
+    fn verify(&self, beacon: &Beacon) -> Result<bool, VerificationError> {
+        Pubkey::verify(
+            self,
+            beacon.round,
+            &beacon.previous_signature,
+            &beacon.signature,
+        )
+    }
+
+    fn verify_all(&self, beacons: &[Beacon]) -> Vec<Result<bool, VerificationError>> {
+        let unchained = beacons
+            .iter()
+            .all(|beacon| beacon.previous_signature.is_empty());
+        if beacons.len() > 1 && unchained {
+            let batch: Vec<(u64, &[u8])> = beacons
+                .iter()
+                .map(|beacon| (beacon.round, beacon.signature.as_slice()))
+                .collect();
+            if self.inner().verify_batch(&batch) == Ok(true) {
+                return beacons.iter().map(|_| Ok(true)).collect();
+            }
+            // Verify one by one to find the invalid beacons
+        }
+        beacons
+            .iter()
+            .map(|beacon| Verifier::verify(self, beacon))
+            .collect()
+    }
+}
+
+fn caching_verifier<S: SchemeMarker>(
+    public_key: &[u8],
+    cache: usize,
+) -> Result<Box<dyn Verifier>, VerificationError>
+where
+    DrandPubkey<S>: Send + Sync + 'static,
+{
+    let pk = DrandPubkey::<S>::from_variable(public_key)
+        .map_err(|err| VerificationError::invalid_point("public_key", err))?;
+    Ok(Box::new(CachingPubkey::new(pk, cache)))
+}
+
+/// Creates the verifier matching the scheme of the network
+fn verifier_for(
+    network: &NetworkDescriptor,
+    cache: usize,
+) -> Result<Box<dyn Verifier>, VerificationError> {
+    match network.scheme {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+            caching_verifier::<PedersenBls>(&network.public_key, cache)
+        }
+        Scheme::BlsUnchainedOnG1 => {
+            caching_verifier::<BlsUnchainedOnG1>(&network.public_key, cache)
+        }
+        Scheme::BlsUnchainedG1Rfc9380 => {
+            caching_verifier::<BlsUnchainedG1Rfc9380>(&network.public_key, cache)
+        }
+    }
+}
+
+/// The state shared by all workers
+struct Service {
+    verifier: Box<dyn Verifier>,
+    /// The randomness of verified rounds
+    randomness: Mutex<BTreeMap<u64, [u8; 32]>>,
+    /// The maximum number of rounds kept in `randomness`
+    history: usize,
+}
+
+impl Service {
+    fn new(verifier: Box<dyn Verifier>, history: usize) -> Self {
+        Self {
+            verifier,
+            randomness: Mutex::new(BTreeMap::new()),
+            history,
+        }
+    }
+
+    /// Handles one request and returns the status code and the JSON body of the response
+    fn handle(&self, method: &Method, url: &str, body: &[u8]) -> (u16, String) {
+        let path = url.split('?').next().unwrap_or_default();
+        match (method, path) {
+            (Method::Post, "/verify") => self.verify(body),
+            (Method::Get, path) if path.starts_with("/randomness/") => {
+                self.randomness(&path["/randomness/".len()..])
+            }
+            (_, "/verify") => (405, error_message("Use POST /verify")),
+            (_, path) if path.starts_with("/randomness/") => {
+                (405, error_message("Use GET /randomness/{round}"))
+            }
+            _ => (404, error_message("Not found")),
+        }
+    }
+
+    fn verify(&self, body: &[u8]) -> (u16, String) {
+        let invalid_body = |err: serde_json::Error| {
+            let report = ErrorReport::new(ErrorCode::InvalidEncoding, err.to_string())
+                .with_field("body")
+                .to_json();
+            (400, report)
+        };
+        let value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(err) => return invalid_body(err),
+        };
+        let is_array = value.is_array();
+        let beacons: Vec<Beacon> = if is_array {
+            match serde_json::from_value(value) {
+                Ok(beacons) => beacons,
+                Err(err) => return invalid_body(err),
+            }
+        } else {
+            match serde_json::from_value(value) {
+                Ok(beacon) => vec![beacon],
+                Err(err) => return invalid_body(err),
+            }
+        };
+        if let Err(err) = check_batch_size(beacons.len(), DEFAULT_MAX_BATCH_SIZE) {
+            return (413, ErrorReport::from(&err).to_json());
+        }
+
+        let results = self.verifier.verify_all(&beacons);
+        let mut entries = Vec::with_capacity(beacons.len());
+        for (beacon, result) in beacons.iter().zip(results) {
+            entries.push(match result {
+                Ok(true) => {
+                    let randomness = derive_randomness(&beacon.signature);
+                    self.remember(beacon.round, randomness);
+                    json!({
+                        "round": beacon.round,
+                        "valid": true,
+                        "randomness": hex::encode(randomness),
+                    })
+                }
+                Ok(false) => json!({
+                    "round": beacon.round,
+                    "valid": false,
+                    "reason": "Signature does not match",
+                }),
+                Err(err) => json!({
+                    "round": beacon.round,
+                    "valid": false,
+                    "error": ErrorReport::from(&err),
+                }),
+            });
+        }
+        let response = if is_array {
+            Value::Array(entries)
+        } else {
+            entries.remove(0)
+        };
+        (200, response.to_string())
+    }
+
+    fn randomness(&self, round: &str) -> (u16, String) {
+        let round = match round.parse::<u64>() {
+            Ok(round) => round,
+            Err(err) => {
+                let report = ErrorReport::new(
+                    ErrorCode::InvalidEncoding,
+                    format!("Invalid round: {}", err),
+                )
+                .with_field("round");
+                return (400, report.to_json());
+            }
+        };
+        match self.lock().get(&round) {
+            Some(randomness) => (
+                200,
+                json!({ "round": round, "randomness": hex::encode(randomness) }).to_string(),
+            ),
+            None => (404, error_message("Round was not verified before")),
+        }
+    }
+
+    /// Keeps the randomness of a verified round, dropping the oldest rounds beyond `history`
+    fn remember(&self, round: u64, randomness: [u8; 32]) {
+        if self.history == 0 {
+            return;
+        }
+        let mut known = self.lock();
+        known.insert(round, randomness);
+        while known.len() > self.history {
+            if let Some(&oldest) = known.keys().next() {
+                known.remove(&oldest);
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, [u8; 32]>> {
+        // The map is consistent after every statement, so a poisoned lock can be reused
+        self.randomness
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn error_message(message: &str) -> String {
+    json!({ "message": message }).to_string()
+}
+
+/// Answers requests until the server is closed
+fn serve(server: &Server, service: &Service) {
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("static header is valid");
+    for mut request in server.incoming_requests() {
+        let mut body = Vec::new();
+        let (status, response) = match request
+            .as_reader()
+            .take(MAX_BODY_SIZE + 1)
+            .read_to_end(&mut body)
+        {
+            Ok(_) if body.len() as u64 > MAX_BODY_SIZE => {
+                (413, error_message("Request body too large"))
+            }
+            Ok(_) => service.handle(request.method(), request.url(), &body),
+            Err(err) => (400, error_message(&format!("Error reading body: {}", err))),
+        };
+        let response = Response::from_string(response)
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        if let Err(err) = request.respond(response) {
+            eprintln!("Error sending response: {}", err);
+        }
+    }
+}
+
+/// Removes `name <value>` from the arguments and returns the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let pos = match args.iter().position(|arg| arg == name) {
+        Some(pos) => pos,
+        None => return Ok(None),
+    };
+    if pos + 1 >= args.len() {
+        return Err(format!("Missing value for {}", name));
+    }
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+    Ok(Some(value))
+}
+
+/// Removes `name <value>` from the arguments and parses the value as a number
+fn take_number(args: &mut Vec<String>, name: &str, default: usize) -> Result<usize, String> {
+    match take_option(args, name)? {
+        Some(value) => value
+            .parse()
+            .map_err(|err| format!("Invalid value for {}: {}", name, err)),
+        None => Ok(default),
+    }
+}
+
+fn main_impl() -> Result<(), String> {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let network = take_option(&mut args, "--network")?;
+    let network_file = take_option(&mut args, "--network-file")?;
+    let listen = take_option(&mut args, "--listen")?.unwrap_or_else(|| "127.0.0.1:8080".into());
+    let workers = take_number(&mut args, "--workers", 4)?.max(1);
+    let cache = take_number(&mut args, "--cache", 10_000)?;
+    let history = take_number(&mut args, "--history", 100_000)?;
+    if !args.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let network = match (network, network_file) {
+        (Some(_), Some(_)) => return Err(USAGE.to_string()),
+        (_, Some(path)) => NetworkDescriptor::load(&path)
+            .map_err(|err| format!("Cannot load network file {}: {}", path, err))?,
+        (name, None) => {
+            let name = name.as_deref().unwrap_or("mainnet");
+            networks::by_name(name)
+                .map(NetworkDescriptor::from)
+                .map_err(|err| err.to_string())?
+        }
+    };
+    let verifier = verifier_for(&network, cache)
+        .map_err(|err| format!("Invalid network public key: {}", err))?;
+    let service = Arc::new(Service::new(verifier, history));
+    let server = Arc::new(
+        Server::http(&listen).map_err(|err| format!("Cannot listen on {}: {}", listen, err))?,
+    );
+    eprintln!(
+        "Verifying {} ({}) on http://{}",
+        network.name, network.scheme, listen
+    );
+
+    let handles: Vec<_> = (0..workers)
+        .map(|_| {
+            let server = Arc::clone(&server);
+            let service = Arc::clone(&service);
+            thread::spawn(move || serve(&server, &service))
+        })
+        .collect();
+    for handle in handles {
+        let _ = handle.join();
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = main_impl() {
+        eprintln!("{}", err);
+        exit(100);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUICKNET_123: &str = r#"{"round":123,"signature":"b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"}"#;
+    const QUICKNET_124: &str = r#"{"round":124,"signature":"b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"}"#;
+
+    fn quicknet() -> Service {
+        let network = NetworkDescriptor::from(&networks::QUICKNET);
+        Service::new(verifier_for(&network, 10).unwrap(), 10)
+    }
+
+    #[test]
+    fn verify_works() {
+        let service = quicknet();
+        let (status, body) = service.handle(&Method::Post, "/verify", QUICKNET_123.as_bytes());
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            r#"{"randomness":"fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc","round":123,"valid":true}"#
+        );
+
+        let (status, body) = service.handle(&Method::Post, "/verify", QUICKNET_124.as_bytes());
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            r#"{"reason":"Signature does not match","round":124,"valid":false}"#
+        );
+
+        let (status, body) = service.handle(&Method::Post, "/verify", b"{\"round\":1}");
+        assert_eq!(status, 400);
+        assert!(body.starts_with(r#"{"code":5,"kind":"invalid_encoding","field":"body""#));
+    }
+
+    #[test]
+    fn verify_works_for_arrays() {
+        let service = quicknet();
+        let valid = format!("[{},{}]", QUICKNET_123, QUICKNET_123);
+        let (status, body) = service.handle(&Method::Post, "/verify", valid.as_bytes());
+        assert_eq!(status, 200);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body[0]["valid"], true);
+        assert_eq!(body[1]["valid"], true);
+
+        let mixed = format!("[{},{}]", QUICKNET_123, QUICKNET_124);
+        let (status, body) = service.handle(&Method::Post, "/verify", mixed.as_bytes());
+        assert_eq!(status, 200);
+        let body: Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(body[0]["valid"], true);
+        assert_eq!(body[1]["valid"], false);
+
+        let too_many = format!("[{}]", vec![QUICKNET_123; 10_001].join(","));
+        let (status, body) = service.handle(&Method::Post, "/verify", too_many.as_bytes());
+        assert_eq!(status, 413);
+        assert!(body.starts_with(r#"{"code":6,"kind":"batch_too_large""#));
+    }
+
+    #[test]
+    fn randomness_works() {
+        let service = quicknet();
+        let (status, _) = service.handle(&Method::Get, "/randomness/123", b"");
+        assert_eq!(status, 404);
+
+        service.handle(&Method::Post, "/verify", QUICKNET_123.as_bytes());
+        let (status, body) = service.handle(&Method::Get, "/randomness/123", b"");
+        assert_eq!(status, 200);
+        assert_eq!(
+            body,
+            r#"{"randomness":"fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc","round":123}"#
+        );
+
+        let (status, _) = service.handle(&Method::Get, "/randomness/abc", b"");
+        assert_eq!(status, 400);
+        let (status, _) = service.handle(&Method::Post, "/randomness/123", b"");
+        assert_eq!(status, 405);
+        let (status, _) = service.handle(&Method::Get, "/verify", b"");
+        assert_eq!(status, 405);
+        let (status, _) = service.handle(&Method::Get, "/", b"");
+        assert_eq!(status, 404);
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let network = NetworkDescriptor::from(&networks::QUICKNET);
+        let service = Service::new(verifier_for(&network, 10).unwrap(), 2);
+        service.remember(1, [1; 32]);
+        service.remember(2, [2; 32]);
+        service.remember(3, [3; 32]);
+        assert_eq!(service.handle(&Method::Get, "/randomness/1", b"").0, 404);
+        assert_eq!(service.handle(&Method::Get, "/randomness/2", b"").0, 200);
+        assert_eq!(service.handle(&Method::Get, "/randomness/3", b"").0, 200);
+    }
+}
@@ -0,0 +1,247 @@
+//! A [`Pubkey`] decorator that caches verification results.
+//!
+//! API gateways often receive the same beacon in many requests per second. [`CachingPubkey`]
+//! answers repeated verifications of the same round and signatures from a bounded in-memory
+//! cache instead of computing the pairing again.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use sha2::{Digest, Sha256};
+
+use crate::points::{InvalidPoint, UncheckedCapability};
+use crate::scheme::Scheme;
+use crate::signature::Signature;
+use crate::verify::{ErrorCode, Group, Pubkey, VerificationError};
+
+/// The number of results cached by pubkeys created through the [`Pubkey`] constructors
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
+
+/// The round and a hash of the previous signature and signature
+type CacheKey = (u64, [u8; 32]);
+
+#[derive(Debug, Default)]
+struct Cache {
+    results: HashMap<CacheKey, bool>,
+    /// Keys in insertion order, the oldest first
+    order: VecDeque<CacheKey>,
+}
+
+/// Wraps a [`Pubkey`] and caches the results of [`Pubkey::verify`].
+///
+/// Only valid and invalid results are cached, not errors for malformed inputs. When the
+/// cache is full, the oldest result is evicted. The cache is shared by all threads using the
+/// key. With the feature `audit-log`, rejected inputs are only recorded on a cache miss.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::caching::CachingPubkey;
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let pk = CachingPubkey::new(pk, 100);
+///
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// assert!(pk.verify(123, &[], &signature).unwrap());
+/// // Answered from the cache
+/// assert!(pk.verify(123, &[], &signature).unwrap());
+/// assert_eq!(pk.cached(), 1);
+/// ```
+#[derive(Debug)]
+pub struct CachingPubkey<P> {
+    inner: P,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+impl<P: Pubkey> CachingPubkey<P> {
+    /// Wraps the key with a cache of at most `capacity` results
+    pub fn new(inner: P, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    pub fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    /// The number of cached results
+    pub fn cached(&self) -> usize {
+        self.lock().results.len()
+    }
+
+    pub fn clear(&self) {
+        let mut cache = self.lock();
+        cache.results.clear();
+        cache.order.clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        // The cache is consistent after every statement, so a poisoned lock can be reused
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn insert(&self, key: CacheKey, valid: bool) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut cache = self.lock();
+        if cache.results.insert(key, valid).is_none() {
+            cache.order.push_back(key);
+        }
+        while cache.order.len() > self.capacity {
+            if let Some(oldest) = cache.order.pop_front() {
+                cache.results.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn cache_key(round: u64, previous_signature: &[u8], signature: &[u8]) -> CacheKey {
+    let mut hasher = Sha256::new();
+    hasher.update((previous_signature.len() as u64).to_be_bytes());
+    hasher.update(previous_signature);
+    hasher.update(signature);
+    (round, hasher.finalize().into())
+}
+
+impl<P: Pubkey> Pubkey for CachingPubkey<P> {
+    type This = P::This;
+    type ThisCompressed = P::ThisCompressed;
+    type Other = P::Other;
+
+    fn msg_to_curve(msg: &[u8]) -> Self::Other {
+        P::msg_to_curve(msg)
+    }
+
+    fn scheme(&self) -> Scheme {
+        self.inner.scheme()
+    }
+
+    fn signature_group(&self) -> Group {
+        self.inner.signature_group()
+    }
+
+    fn from_fixed(data: Self::ThisCompressed) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(P::from_fixed(data)?, DEFAULT_CACHE_SIZE))
+    }
+
+    fn from_fixed_unchecked(
+        data: Self::ThisCompressed,
+        capability: UncheckedCapability,
+    ) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(
+            P::from_fixed_unchecked(data, capability)?,
+            DEFAULT_CACHE_SIZE,
+        ))
+    }
+
+    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(P::from_variable(data)?, DEFAULT_CACHE_SIZE))
+    }
+
+    fn verify_step2(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        self.inner.verify_step2(signature, msg_on_curve)
+    }
+
+    fn verify_step2_no_alloc(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, ErrorCode> {
+        self.inner.verify_step2_no_alloc(signature, msg_on_curve)
+    }
+
+    fn verify_step2_decompressed(
+        &self,
+        signature: &Signature,
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        self.inner
+            .verify_step2_decompressed(signature, msg_on_curve)
+    }
+
+    fn verify(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let key = cache_key(round, previous_signature, signature);
+        if let Some(&valid) = self.lock().results.get(&key) {
+            return Ok(valid);
+        }
+        let valid = self.inner.verify(round, previous_signature, signature)?;
+        self.insert(key, valid);
+        Ok(valid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::G1Pubkey;
+    use hex_literal::hex;
+
+    const PK_LEO_MAINNET: [u8; 48] = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+    const SIG_72784: [u8; 96] = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+    const SIG_72785: [u8; 96] = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+
+    #[test]
+    fn verify_works() {
+        let pk = CachingPubkey::<G1Pubkey>::from_fixed(PK_LEO_MAINNET).unwrap();
+        assert_eq!(pk.scheme(), Scheme::PedersenBlsChained);
+
+        assert!(pk.verify(72785, &SIG_72784, &SIG_72785).unwrap());
+        assert!(pk.verify(72785, &SIG_72784, &SIG_72785).unwrap());
+        assert_eq!(pk.cached(), 1);
+
+        // invalid results are cached
+        assert!(!pk.verify(72786, &SIG_72784, &SIG_72785).unwrap());
+        assert!(!pk.verify(72786, &SIG_72784, &SIG_72785).unwrap());
+        // the previous signature is part of the key
+        assert!(!pk.verify(72785, &[], &SIG_72785).unwrap());
+        assert_eq!(pk.cached(), 3);
+
+        // errors are not cached
+        assert!(pk.verify(72785, &SIG_72784, &[0xff; 96]).is_err());
+        assert_eq!(pk.cached(), 3);
+
+        // the outcome API uses the cache
+        assert!(pk
+            .verify_with_outcome(72785, &SIG_72784, &SIG_72785)
+            .is_valid());
+
+        pk.clear();
+        assert_eq!(pk.cached(), 0);
+    }
+
+    #[test]
+    fn cache_is_bounded() {
+        let pk = CachingPubkey::new(G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap(), 2);
+        for round in 1..=5 {
+            assert!(!pk.verify(round, &SIG_72784, &SIG_72785).unwrap());
+        }
+        assert_eq!(pk.cached(), 2);
+        {
+            let cache = pk.lock();
+            let rounds: Vec<u64> = cache.order.iter().map(|(round, _)| *round).collect();
+            assert_eq!(rounds, [4, 5]);
+        }
+
+        let pk = CachingPubkey::new(G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap(), 0);
+        assert!(pk.verify(72785, &SIG_72784, &SIG_72785).unwrap());
+        assert_eq!(pk.cached(), 0);
+    }
+}
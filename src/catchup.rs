@@ -0,0 +1,379 @@
+//! Catch-up packets with multiple consecutive beacons.
+//!
+//! A client that fell behind receives the missing rounds in one message. A catch-up packet
+//! is a sequence of [frames](crate::frame), each prefixed by its length as a single byte,
+//! i.e. the stream format of [`FrameDecoder`](crate::frame::FrameDecoder). The rounds of a
+//! packet must be consecutive and, for chained networks, linked by their previous signatures.
+//!
+//! The packet is verified as a unit. Every beacon is verified, chained or not, using a single
+//! pairing check per [`DEFAULT_MAX_BATCH_SIZE`] beacons (see
+//! [`DrandPubkey::verify_beacon_batch`]). The links only ensure the packet is one consecutive
+//! piece of the chain: the signature of a beacon covers the bytes of the previous signature,
+//! not whether that signature is valid.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::batch::DEFAULT_MAX_BATCH_SIZE;
+use crate::beacon::{check_link, Beacon, LinkError, VerifiedBeacon};
+use crate::frame::{Frame, FrameError};
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::verify::{DrandPubkey, Pubkey, SchemeMarker};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CatchupError {
+    /// The packet contains no beacons
+    Empty,
+    /// The frame at `index` is invalid
+    Frame { index: usize, source: FrameError },
+    /// The packet ends within a frame
+    Truncated,
+    /// Two beacons are not consecutive or not linked
+    Link(LinkError),
+    /// A chained and an unchained beacon are in the same packet
+    MixedChaining { round: u64 },
+    /// The beacon of the given round failed verification
+    Invalid { round: u64, reason: InvalidReason },
+}
+
+impl fmt::Display for CatchupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatchupError::Empty => write!(f, "Catch-up packet is empty"),
+            CatchupError::Frame { index, source } => write!(f, "Frame {}: {}", index, source),
+            CatchupError::Truncated => write!(f, "Catch-up packet ends within a frame"),
+            CatchupError::Link(err) => err.fmt(f),
+            CatchupError::MixedChaining { round } => write!(
+                f,
+                "Round {} is chained differently than the rounds before",
+                round
+            ),
+            CatchupError::Invalid { round, reason } => {
+                write!(f, "Invalid beacon for round {}: {}", round, reason)
+            }
+        }
+    }
+}
+
+impl Error for CatchupError {}
+
+impl From<LinkError> for CatchupError {
+    fn from(source: LinkError) -> Self {
+        CatchupError::Link(source)
+    }
+}
+
+/// The consecutive beacons of a catch-up packet.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::catchup::CatchupPacket;
+/// use drand_verify::frame::Frame;
+/// use drand_verify::{networks, Beacon, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let beacon = Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// };
+/// let frame = Frame::from(&beacon).to_vec();
+/// let mut data = vec![frame.len() as u8];
+/// data.extend_from_slice(&frame);
+///
+/// let packet = CatchupPacket::parse(&data).unwrap();
+/// assert_eq!(packet.verify(&pk).unwrap().round(), 123);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CatchupPacket {
+    beacons: Vec<Beacon>,
+}
+
+impl CatchupPacket {
+    /// Decodes a packet and checks that its rounds are consecutive and linked.
+    /// No signatures are verified.
+    pub fn parse(data: &[u8]) -> Result<Self, CatchupError> {
+        let mut beacons = Vec::new();
+        let mut rest = data;
+        while let Some((&length, tail)) = rest.split_first() {
+            let length = usize::from(length);
+            if tail.len() < length {
+                return Err(CatchupError::Truncated);
+            }
+            let (frame, tail) = tail.split_at(length);
+            let frame = Frame::parse(frame).map_err(|source| CatchupError::Frame {
+                index: beacons.len(),
+                source,
+            })?;
+            beacons.push(frame.to_beacon());
+            rest = tail;
+        }
+        Self::new(beacons)
+    }
+
+    /// Creates a packet from beacons received in another format, e.g. JSON.
+    ///
+    /// The beacons must be ordered by round. Their rounds must be consecutive and,
+    /// for chained networks, linked.
+    pub fn new(beacons: Vec<Beacon>) -> Result<Self, CatchupError> {
+        let first = beacons.first().ok_or(CatchupError::Empty)?;
+        let chained = !first.previous_signature.is_empty();
+        for pair in beacons.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if next.previous_signature.is_empty() == chained {
+                return Err(CatchupError::MixedChaining { round: next.round });
+            }
+            if chained {
+                check_link(prev, next)?;
+            } else if prev.round.checked_add(1) != Some(next.round) {
+                return Err(LinkError::RoundGap {
+                    previous: prev.round,
+                    next: next.round,
+                }
+                .into());
+            }
+        }
+        Ok(Self { beacons })
+    }
+
+    pub fn beacons(&self) -> &[Beacon] {
+        &self.beacons
+    }
+
+    pub fn is_chained(&self) -> bool {
+        !self.beacons[0].previous_signature.is_empty()
+    }
+
+    /// Verifies every beacon of the packet and returns the beacon of the highest round.
+    ///
+    /// An error is returned for the first invalid beacon.
+    pub fn verify<S: SchemeMarker>(
+        &self,
+        pubkey: &DrandPubkey<S>,
+    ) -> Result<VerifiedBeacon, CatchupError>
+    where
+        DrandPubkey<S>: Sync,
+    {
+        for chunk in self.beacons.chunks(DEFAULT_MAX_BATCH_SIZE) {
+            if pubkey.verify_beacon_batch(chunk) == Ok(true) {
+                continue;
+            }
+            // Verify one by one to find the invalid beacon
+            for (beacon, outcome) in chunk.iter().zip(verify_all(pubkey, chunk)) {
+                if let VerificationOutcome::Invalid(reason) = outcome {
+                    return Err(CatchupError::Invalid {
+                        round: beacon.round,
+                        reason,
+                    });
+                }
+            }
+        }
+        let last = self.beacons.last().expect("Packets are never empty");
+        Ok(VerifiedBeacon::new(
+            last.round,
+            &last.previous_signature,
+            &last.signature,
+        ))
+    }
+}
+
+fn verify_all<P: Pubkey + Sync>(pubkey: &P, beacons: &[Beacon]) -> Vec<VerificationOutcome> {
+    #[cfg(feature = "rayon")]
+    {
+        crate::batch::verify_parallel_unbounded(pubkey, beacons)
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        beacons
+            .iter()
+            .map(|beacon| {
+                pubkey.verify_with_outcome(
+                    beacon.round,
+                    &beacon.previous_signature,
+                    &beacon.signature,
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    fn encode(beacons: &[Beacon]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for beacon in beacons {
+            let frame = Frame::from(beacon).to_vec();
+            out.push(frame.len() as u8);
+            out.extend_from_slice(&frame);
+        }
+        out
+    }
+
+    fn mainnet_beacons() -> [Beacon; 2] {
+        // https://api.drand.sh/public/72784 is the previous signature of 72785
+        let sig_72784 = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        [
+            Beacon {
+                round: 72784,
+                previous_signature: vec![0xaa; 96], // not checked
+                signature: sig_72784.to_vec(),
+            },
+            Beacon {
+                round: 72785,
+                previous_signature: sig_72784.to_vec(),
+                signature: hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").to_vec(),
+            },
+        ]
+    }
+
+    #[test]
+    fn chained_packets_work() {
+        let pk = crate::G1Pubkey::from_fixed(hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31")).unwrap();
+        let beacons = mainnet_beacons();
+        let packet = CatchupPacket::parse(&encode(&beacons)).unwrap();
+        assert!(packet.is_chained());
+        assert_eq!(packet.beacons(), beacons);
+        // the first beacon is checked against its made up previous signature
+        assert!(matches!(
+            packet.verify(&pk),
+            Err(CatchupError::Invalid { round: 72784, .. })
+        ));
+        let packet = CatchupPacket::new(beacons[1..].to_vec()).unwrap();
+        assert_eq!(packet.verify(&pk).unwrap().round(), 72785);
+
+        // broken link
+        let mut broken = beacons.clone();
+        broken[1].previous_signature[0] ^= 1;
+        assert_eq!(
+            CatchupPacket::new(broken.to_vec()),
+            Err(CatchupError::Link(LinkError::SignatureMismatch {
+                round: 72785
+            }))
+        );
+
+        // invalid last beacon
+        let mut invalid = beacons[1].clone();
+        invalid.signature = beacons[0].signature.clone();
+        let packet = CatchupPacket::new(vec![invalid]).unwrap();
+        assert!(matches!(
+            packet.verify(&pk),
+            Err(CatchupError::Invalid { round: 72785, .. })
+        ));
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn chained_packets_verify_every_beacon() {
+        use crate::signer::SecretKey;
+        use crate::G1Pubkey;
+
+        let sk = SecretKey::from_seed(b"catchup");
+        let pk = G1Pubkey::from_variable(&sk.public_key::<G1Pubkey>()).unwrap();
+        let s1 = sk.sign::<G1Pubkey>(1, &[0x11; 96]);
+        let s2 = sk.sign::<G1Pubkey>(2, &s1);
+        let s3 = sk.sign::<G1Pubkey>(3, &s2);
+        let s4 = sk.sign::<G1Pubkey>(4, &s3);
+        let beacon = |round, previous_signature: &[u8], signature: &[u8]| Beacon {
+            round,
+            previous_signature: previous_signature.to_vec(),
+            signature: signature.to_vec(),
+        };
+        let packet = CatchupPacket::new(vec![
+            beacon(2, &s1, &s2),
+            beacon(3, &s2, &s3),
+            beacon(4, &s3, &s4),
+        ])
+        .unwrap();
+        assert_eq!(packet.verify(&pk).unwrap().round(), 4);
+
+        // A forged beacon in the middle is linked correctly, but the beacon after it
+        // was signed over a different previous signature.
+        let forged = sk.sign::<G1Pubkey>(9, &s1);
+        let packet = CatchupPacket::new(vec![
+            beacon(2, &s1, &forged),
+            beacon(3, &forged, &s3),
+            beacon(4, &s3, &s4),
+        ])
+        .unwrap();
+        assert!(matches!(
+            packet.verify(&pk),
+            Err(CatchupError::Invalid { round: 2, .. })
+        ));
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn unchained_packets_work() {
+        use crate::signer::SecretKey;
+        use crate::G2PubkeyRfc;
+
+        let sk = SecretKey::from_seed(b"catchup");
+        let pk = G2PubkeyRfc::from_variable(&sk.public_key::<G2PubkeyRfc>()).unwrap();
+        let beacons: Vec<Beacon> = (10..14)
+            .map(|round| Beacon {
+                round,
+                previous_signature: vec![],
+                signature: sk.sign::<G2PubkeyRfc>(round, &[]),
+            })
+            .collect();
+        let packet = CatchupPacket::parse(&encode(&beacons)).unwrap();
+        assert!(!packet.is_chained());
+        assert_eq!(packet.verify(&pk).unwrap().round(), 13);
+
+        // every beacon is verified
+        let mut invalid = beacons.clone();
+        invalid[1].signature = beacons[2].signature.clone();
+        let packet = CatchupPacket::new(invalid).unwrap();
+        assert!(matches!(
+            packet.verify(&pk),
+            Err(CatchupError::Invalid { round: 11, .. })
+        ));
+
+        let mut gap = beacons.clone();
+        gap.remove(1);
+        assert_eq!(
+            CatchupPacket::new(gap),
+            Err(CatchupError::Link(LinkError::RoundGap {
+                previous: 10,
+                next: 12
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_rejects_malformed_packets() {
+        assert_eq!(CatchupPacket::parse(&[]), Err(CatchupError::Empty));
+
+        let data = encode(&mainnet_beacons());
+        assert_eq!(
+            CatchupPacket::parse(&data[..data.len() - 1]),
+            Err(CatchupError::Truncated)
+        );
+
+        let mut data = encode(&mainnet_beacons());
+        data.extend_from_slice(&[3, 1, 2, 3]);
+        assert_eq!(
+            CatchupPacket::parse(&data),
+            Err(CatchupError::Frame {
+                index: 2,
+                source: FrameError::InvalidLength { actual: 3 }
+            })
+        );
+
+        let [chained, _] = mainnet_beacons();
+        let unchained = Beacon {
+            round: 72785,
+            previous_signature: vec![],
+            signature: chained.signature.clone(),
+        };
+        assert_eq!(
+            CatchupPacket::new(vec![chained, unchained]),
+            Err(CatchupError::MixedChaining { round: 72785 })
+        );
+    }
+}
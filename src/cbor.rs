@@ -0,0 +1,145 @@
+//! CBOR encoding and decoding of the serializable types of this crate.
+//!
+//! All types are encoded with definite lengths, minimal integer encoding and map keys
+//! sorted by their encoding (RFC 8949, section 4.2.1), such that the encoding is deterministic.
+
+use std::fmt;
+
+use ciborium::Value;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum CborError {
+    Encode { msg: String },
+    Decode { msg: String },
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::Encode { msg } => write!(f, "Error encoding CBOR: {}", msg),
+            CborError::Decode { msg } => write!(f, "Error decoding CBOR: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CborError {}
+
+/// Encodes a value as CBOR
+pub fn to_vec<T: Serialize>(value: &T) -> Result<Vec<u8>, CborError> {
+    let value = Value::serialized(value).map_err(|err| CborError::Encode {
+        msg: err.to_string(),
+    })?;
+    encode(&canonicalize(value)?)
+}
+
+fn encode(value: &Value) -> Result<Vec<u8>, CborError> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out).map_err(|err| CborError::Encode {
+        msg: err.to_string(),
+    })?;
+    Ok(out)
+}
+
+/// Sorts the keys of all maps by the bytewise order of their encoding
+fn canonicalize(value: Value) -> Result<Value, CborError> {
+    Ok(match value {
+        Value::Map(entries) => {
+            let mut entries = entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let key = canonicalize(key)?;
+                    Ok((encode(&key)?, key, canonicalize(value)?))
+                })
+                .collect::<Result<Vec<_>, CborError>>()?;
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Map(
+                entries
+                    .into_iter()
+                    .map(|(_, key, value)| (key, value))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .into_iter()
+                .map(canonicalize)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Tag(tag, inner) => Value::Tag(tag, Box::new(canonicalize(*inner)?)),
+        other => other,
+    })
+}
+
+/// Decodes a value from CBOR
+pub fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T, CborError> {
+    ciborium::from_reader(data).map_err(|err| CborError::Decode {
+        msg: err.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{networks, G2PubkeyRfc, Pubkey, StoredBeacon, VerifiedBeacon};
+    use hex_literal::hex;
+
+    #[test]
+    fn verified_beacon_roundtrip_works() {
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let beacon = VerifiedBeacon::new(123, b"", &signature);
+
+        let encoded = to_vec(&beacon).unwrap();
+        // map(4), "round": 123, "signature": bytes(48) ...
+        assert_eq!(encoded[..9], hex!("a465726f756e64187b"));
+        assert_eq!(encoded, to_vec(&beacon).unwrap());
+
+        let decoded: StoredBeacon = from_slice(&encoded).unwrap();
+        assert_eq!(decoded, StoredBeacon::from(&beacon));
+        let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+        assert_eq!(decoded.verify(&pk).into_verified(), Some(beacon));
+
+        // Corrupted signature
+        let mut corrupted = encoded.clone();
+        corrupted[25] ^= 0x01;
+        match from_slice::<StoredBeacon>(&corrupted).unwrap_err() {
+            CborError::Decode { msg } => {
+                assert!(
+                    msg.contains("Randomness does not match signature"),
+                    "{}",
+                    msg
+                )
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+
+    #[test]
+    fn map_keys_are_canonical() {
+        let beacon = crate::Beacon {
+            round: 123,
+            previous_signature: vec![],
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        };
+        // map(3), "round": 123, "signature": text(96), "randomness": text(64)
+        assert_eq!(
+            to_vec(&beacon).unwrap(),
+            hex!("a365726f756e64187b697369676e617475726578606237356336396430623732613564393036653835346538303862613765326163636231353432616333353561653438366435393161613964343337363534383265323663643032646638333564333534366432336334623133653064666339326a72616e646f6d6e657373784066623866376263323962663234646235313837316563386337396633613165346264303535376263306466636565396564316439323465363964316336306463")
+        );
+
+        let info = crate::ChainInfo::from(&networks::QUICKNET);
+        let value: Value = from_slice(&to_vec(&info).unwrap()).unwrap();
+        let keys: Vec<&str> = value
+            .as_map()
+            .unwrap()
+            .iter()
+            .map(|(key, _)| key.as_text().unwrap())
+            .collect();
+        assert_eq!(
+            keys,
+            ["hash", "period", "schemeID", "public_key", "genesis_time"]
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The 32 byte hash identifying a drand chain.
+///
+/// This is the `hash` field of the `/info` endpoint and the path segment under which
+/// relays serve the chain. Using a dedicated type instead of `[u8; 32]` avoids mixing up
+/// chain hashes with group hashes or randomness values.
+///
+/// ```
+/// use drand_verify::{networks, ChainHash};
+///
+/// let hash: ChainHash = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(hash, networks::QUICKNET.chain_hash);
+/// assert_eq!(hash.to_string(), "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChainHash([u8; 32]);
+
+impl ChainHash {
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Parses a chain hash from 64 hex characters (upper or lower case)
+    pub fn from_hex(input: &str) -> Result<Self, InvalidChainHash> {
+        hex::decode(input)
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Self)
+            .ok_or_else(|| InvalidChainHash {
+                input: input.to_string(),
+            })
+    }
+
+    /// The lower case hex representation
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+}
+
+impl From<[u8; 32]> for ChainHash {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl From<ChainHash> for [u8; 32] {
+    fn from(hash: ChainHash) -> Self {
+        hash.0
+    }
+}
+
+impl AsRef<[u8]> for ChainHash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ChainHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Debug for ChainHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ChainHash({})", self.to_hex())
+    }
+}
+
+impl FromStr for ChainHash {
+    type Err = InvalidChainHash;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+/// The error returned when parsing a chain hash that is not 32 hex encoded bytes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidChainHash {
+    pub input: String,
+}
+
+impl fmt::Display for InvalidChainHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Chain hash must be 32 hex encoded bytes, got '{}'",
+            self.input
+        )
+    }
+}
+
+impl Error for InvalidChainHash {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_hex_works() {
+        let hash =
+            ChainHash::from_hex("8990E7A9AAED2FFED73DBD7092123D6F289930540D7651336225DC172E51B2CE")
+                .unwrap();
+        assert_eq!(
+            hash.to_hex(),
+            "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"
+        );
+        assert_eq!(hash.as_bytes()[0], 0x89);
+        assert_eq!("ab".repeat(32).parse(), Ok(ChainHash::new([0xab; 32])));
+
+        for invalid in ["", "ab", &"ab".repeat(33), &"zz".repeat(32)] {
+            assert_eq!(
+                ChainHash::from_hex(invalid),
+                Err(InvalidChainHash {
+                    input: invalid.to_string()
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn fmt_works() {
+        let hash = ChainHash::new([0x0f; 32]);
+        assert_eq!(hash.to_string(), "0f".repeat(32));
+        assert_eq!(
+            format!("{:?}", hash),
+            format!("ChainHash({})", "0f".repeat(32))
+        );
+    }
+}
@@ -0,0 +1,560 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::chain_hash::ChainHash;
+use crate::dyn_pubkey::DynPubkey;
+use crate::networks::Network;
+use crate::randomness_beacon::BeaconKey;
+use crate::rounds::{self, RoundIterator, Timestamp};
+use crate::scheme::{KeyLengthMismatch, Scheme, UnknownScheme};
+use crate::verify::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// Information about a drand network as served by the `/info` endpoint of relays.
+///
+/// With the feature `serde`, this (de)serializes from and to the JSON of the `/info`
+/// endpoint, i.e. with the keys `schemeID` and `groupHash` and hex encoded bytes.
+/// Unknown keys like `metadata` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "ChainInfoJson", into = "ChainInfoJson")
+)]
+pub struct ChainInfo {
+    /// The group public key in compressed format (48 or 96 bytes, depending on the scheme)
+    pub public_key: Vec<u8>,
+    /// The period between two rounds in seconds
+    pub period: u64,
+    /// The time of round 1 in seconds since the Unix epoch
+    pub genesis_time: u64,
+    /// The chain hash identifying the network
+    pub hash: ChainHash,
+    /// The drand scheme ID, e.g. "bls-unchained-g1-rfc9380"
+    pub scheme_id: String,
+    /// The hash of the group that runs the network (`groupHash`), if known
+    pub group_hash: Option<[u8; 32]>,
+}
+
+/// The parts of a drand group file that a relay's chain info must match.
+///
+/// The values are taken from the group file (`group.toml`) of a node operator. The group hash
+/// is not stored in the group file. Use the drand tooling (e.g. `drand show group`) to obtain it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupInfo {
+    /// The distributed public key, i.e. the first coefficient of the group's public polynomial
+    pub public_key: Vec<u8>,
+    pub group_hash: [u8; 32],
+    /// The period between two rounds in seconds
+    pub period: u64,
+    /// The time of round 1 in seconds since the Unix epoch
+    pub genesis_time: u64,
+    pub scheme_id: String,
+}
+
+/// A difference between chain info and a group, see [`ChainInfo::check_group`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GroupMismatch {
+    /// The chain info does not contain a group hash
+    MissingGroupHash,
+    GroupHash {
+        chain_info: [u8; 32],
+        group: [u8; 32],
+    },
+    PublicKey {
+        chain_info: Vec<u8>,
+        group: Vec<u8>,
+    },
+    Period {
+        chain_info: u64,
+        group: u64,
+    },
+    GenesisTime {
+        chain_info: u64,
+        group: u64,
+    },
+    SchemeId {
+        chain_info: String,
+        group: String,
+    },
+}
+
+impl fmt::Display for GroupMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GroupMismatch::MissingGroupHash => {
+                write!(f, "Chain info does not contain a group hash")
+            }
+            GroupMismatch::GroupHash { chain_info, group } => write!(
+                f,
+                "Group hash mismatch: chain info has {}, group has {}",
+                hex::encode(chain_info),
+                hex::encode(group)
+            ),
+            GroupMismatch::PublicKey { chain_info, group } => write!(
+                f,
+                "Public key mismatch: chain info has {}, group has {}",
+                hex::encode(chain_info),
+                hex::encode(group)
+            ),
+            GroupMismatch::Period { chain_info, group } => write!(
+                f,
+                "Period mismatch: chain info has {}, group has {}",
+                chain_info, group
+            ),
+            GroupMismatch::GenesisTime { chain_info, group } => write!(
+                f,
+                "Genesis time mismatch: chain info has {}, group has {}",
+                chain_info, group
+            ),
+            GroupMismatch::SchemeId { chain_info, group } => write!(
+                f,
+                "Scheme ID mismatch: chain info has {}, group has {}",
+                chain_info, group
+            ),
+        }
+    }
+}
+
+impl Error for GroupMismatch {}
+
+/// The error returned by [`ChainInfo::scheme`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvalidChainInfo {
+    UnknownScheme(UnknownScheme),
+    /// The public key does not fit the scheme, e.g. a 48 byte key with bls-unchained-g1-rfc9380
+    KeyLength(KeyLengthMismatch),
+    /// The public key is not a valid point
+    InvalidPublicKey,
+    /// The requested pubkey type does not verify beacons of the chain's scheme
+    PubkeyType {
+        scheme: Scheme,
+    },
+}
+
+impl fmt::Display for InvalidChainInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidChainInfo::UnknownScheme(err) => err.fmt(f),
+            InvalidChainInfo::KeyLength(err) => err.fmt(f),
+            InvalidChainInfo::InvalidPublicKey => write!(f, "Public key is not a valid point"),
+            InvalidChainInfo::PubkeyType { scheme } => write!(
+                f,
+                "Scheme {} must be verified with {}",
+                scheme,
+                scheme.pubkey_type()
+            ),
+        }
+    }
+}
+
+impl Error for InvalidChainInfo {}
+
+impl ChainInfo {
+    /// Returns the latest round published at or before `time`. See [`rounds::round_at`].
+    pub fn round_at(&self, time: impl Timestamp) -> u64 {
+        rounds::round_at(self.genesis_time, self.period, time)
+    }
+
+    /// Returns the time at which `round` is published. See [`rounds::round_time`].
+    pub fn round_time<T: Timestamp>(&self, round: u64) -> T {
+        rounds::round_time(self.genesis_time, self.period, round)
+    }
+
+    /// Returns an iterator over all rounds published in the time range from `start` to `end`
+    /// (inclusive) together with their publishing times. See [`rounds::rounds_between`].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, ChainInfo};
+    ///
+    /// let info = ChainInfo::from(&networks::QUICKNET);
+    /// let start = 1735689600u64; // 2025-01-01T00:00:00Z
+    /// let rounds: Vec<u64> = info.rounds_between::<u64>(start, start + 60).map(|(round, _time)| round).collect();
+    /// assert_eq!(rounds.len(), 21);
+    /// assert_eq!(rounds[0], 14295412);
+    /// ```
+    pub fn rounds_between<T: Timestamp>(
+        &self,
+        start: impl Timestamp,
+        end: impl Timestamp,
+    ) -> RoundIterator<T> {
+        rounds::rounds_between(self.genesis_time, self.period, start, end)
+    }
+}
+
+impl ChainInfo {
+    /// Parses the scheme ID and checks that the public key fits the scheme.
+    ///
+    /// Call this after reading chain info from a relay to get a descriptive error for
+    /// inconsistent key/scheme pairs instead of an opaque point error during verification.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, ChainInfo, InvalidChainInfo, Scheme};
+    ///
+    /// let mut info = ChainInfo::from(&networks::QUICKNET);
+    /// assert_eq!(info.scheme(), Ok(Scheme::BlsUnchainedG1Rfc9380));
+    ///
+    /// // A pedersen key with the quicknet scheme
+    /// info.public_key = networks::MAINNET.public_key.to_vec();
+    /// assert!(matches!(info.scheme(), Err(InvalidChainInfo::KeyLength(_))));
+    /// ```
+    pub fn scheme(&self) -> Result<Scheme, InvalidChainInfo> {
+        let scheme =
+            Scheme::from_scheme_str(&self.scheme_id).map_err(InvalidChainInfo::UnknownScheme)?;
+        scheme
+            .check_key_length(self.public_key.len())
+            .map_err(InvalidChainInfo::KeyLength)?;
+        Ok(scheme)
+    }
+
+    /// Decodes the public key as the pubkey type `P`, which must verify the chain's scheme.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, ChainInfo, G1Pubkey, G2PubkeyRfc, InvalidChainInfo};
+    ///
+    /// let info = ChainInfo::from(&networks::QUICKNET);
+    /// let pk: G2PubkeyRfc = info.pubkey().unwrap();
+    /// assert!(matches!(
+    ///     info.pubkey::<G1Pubkey>(),
+    ///     Err(InvalidChainInfo::PubkeyType { .. })
+    /// ));
+    /// ```
+    pub fn pubkey<P: Pubkey>(&self) -> Result<P, InvalidChainInfo> {
+        let scheme = self.scheme()?;
+        match P::from_variable(&self.public_key) {
+            Ok(pubkey) if pubkey.scheme().pubkey_type() == scheme.pubkey_type() => Ok(pubkey),
+            Ok(_) => Err(InvalidChainInfo::PubkeyType { scheme }),
+            // Either the key is invalid or `P` has the wrong group
+            Err(_) => match self.beacon_key() {
+                Ok(_) => Err(InvalidChainInfo::PubkeyType { scheme }),
+                Err(err) => Err(err),
+            },
+        }
+    }
+
+    /// Decodes the public key with the pubkey type of the chain's scheme.
+    ///
+    /// This allows verifying beacons of networks that are only known at runtime.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::{networks, ChainInfo};
+    /// use hex_literal::hex;
+    ///
+    /// let info = ChainInfo::from(&networks::QUICKNET);
+    /// let key = info.beacon_key().unwrap();
+    /// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    /// assert!(key.verify_beacon(123, &[], &signature));
+    /// ```
+    pub fn beacon_key(&self) -> Result<Box<dyn BeaconKey + Send + Sync>, InvalidChainInfo> {
+        fn decode<P: Pubkey + Send + Sync + 'static>(
+            public_key: &[u8],
+        ) -> Result<Box<dyn BeaconKey + Send + Sync>, InvalidChainInfo> {
+            match P::from_variable(public_key) {
+                Ok(pubkey) => Ok(Box::new(pubkey)),
+                Err(_) => Err(InvalidChainInfo::InvalidPublicKey),
+            }
+        }
+
+        match self.scheme()? {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                decode::<G1Pubkey>(&self.public_key)
+            }
+            Scheme::BlsUnchainedOnG1 => decode::<G2PubkeyFastnet>(&self.public_key),
+            Scheme::BlsUnchainedG1Rfc9380 => decode::<G2PubkeyRfc>(&self.public_key),
+        }
+    }
+
+    /// Like [`ChainInfo::beacon_key`] but returns a [`DynPubkey`], which also gives access
+    /// to the verification errors.
+    pub fn dyn_pubkey(&self) -> Result<DynPubkey, InvalidChainInfo> {
+        DynPubkey::new(&self.scheme_id, &self.public_key)
+    }
+
+    /// Checks that this chain info (e.g. served by a relay) belongs to the given group.
+    ///
+    /// The group hash and the distributed public key are checked first, followed by the
+    /// period, genesis time and scheme ID. The first mismatch is returned.
+    pub fn check_group(&self, group: &GroupInfo) -> Result<(), GroupMismatch> {
+        let group_hash = self.group_hash.ok_or(GroupMismatch::MissingGroupHash)?;
+        if group_hash != group.group_hash {
+            return Err(GroupMismatch::GroupHash {
+                chain_info: group_hash,
+                group: group.group_hash,
+            });
+        }
+        if self.public_key != group.public_key {
+            return Err(GroupMismatch::PublicKey {
+                chain_info: self.public_key.clone(),
+                group: group.public_key.clone(),
+            });
+        }
+        if self.period != group.period {
+            return Err(GroupMismatch::Period {
+                chain_info: self.period,
+                group: group.period,
+            });
+        }
+        if self.genesis_time != group.genesis_time {
+            return Err(GroupMismatch::GenesisTime {
+                chain_info: self.genesis_time,
+                group: group.genesis_time,
+            });
+        }
+        if self.scheme_id != group.scheme_id {
+            return Err(GroupMismatch::SchemeId {
+                chain_info: self.scheme_id.clone(),
+                group: group.scheme_id.clone(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl From<&Network> for ChainInfo {
+    fn from(network: &Network) -> Self {
+        Self {
+            public_key: network.public_key.to_vec(),
+            period: network.period,
+            genesis_time: network.genesis_time,
+            hash: network.chain_hash,
+            scheme_id: network.scheme_id.to_string(),
+            group_hash: None,
+        }
+    }
+}
+
+/// The JSON of the `/info` endpoint
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ChainInfoJson {
+    public_key: String,
+    period: u64,
+    genesis_time: u64,
+    hash: String,
+    #[serde(rename = "groupHash", default, skip_serializing_if = "Option::is_none")]
+    group_hash: Option<String>,
+    #[serde(rename = "schemeID")]
+    scheme_id: String,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<ChainInfoJson> for ChainInfo {
+    type Error = String;
+
+    fn try_from(json: ChainInfoJson) -> Result<Self, String> {
+        fn hash(field: &str, value: &str) -> Result<[u8; 32], String> {
+            hex::decode(value)
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| format!("{} must be 32 hex encoded bytes", field))
+        }
+        Ok(ChainInfo {
+            public_key: hex::decode(&json.public_key)
+                .map_err(|_| "public_key must be hex encoded")?,
+            period: json.period,
+            genesis_time: json.genesis_time,
+            hash: ChainHash::from_hex(&json.hash)
+                .map_err(|_| "hash must be 32 hex encoded bytes")?,
+            scheme_id: json.scheme_id,
+            group_hash: json
+                .group_hash
+                .map(|value| hash("groupHash", &value))
+                .transpose()?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ChainInfo> for ChainInfoJson {
+    fn from(info: ChainInfo) -> Self {
+        ChainInfoJson {
+            public_key: hex::encode(&info.public_key),
+            period: info.period,
+            genesis_time: info.genesis_time,
+            hash: info.hash.to_hex(),
+            group_hash: info.group_hash.map(hex::encode),
+            scheme_id: info.scheme_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::MAINNET;
+
+    #[test]
+    fn from_network_works() {
+        let info = ChainInfo::from(&MAINNET);
+        assert_eq!(info.public_key, MAINNET.public_key);
+        assert_eq!(info.period, 30);
+        assert_eq!(info.genesis_time, 1595431050);
+        assert_eq!(info.hash, MAINNET.chain_hash);
+        assert_eq!(info.scheme_id, "pedersen-bls-chained");
+    }
+
+    #[test]
+    fn pubkey_works() {
+        use crate::networks::{FASTNET, QUICKNET};
+
+        let info = ChainInfo::from(&MAINNET);
+        assert!(info.pubkey::<G1Pubkey>().is_ok());
+        assert_eq!(
+            info.pubkey::<G2PubkeyRfc>().err(),
+            Some(InvalidChainInfo::PubkeyType {
+                scheme: Scheme::PedersenBlsChained
+            })
+        );
+        assert!(info.beacon_key().is_ok());
+        assert!(matches!(info.dyn_pubkey(), Ok(DynPubkey::G1(_))));
+
+        // Same key, different schemes
+        let info = ChainInfo::from(&FASTNET);
+        assert!(info.pubkey::<G2PubkeyFastnet>().is_ok());
+        let err = info.pubkey::<G2PubkeyRfc>().err().unwrap();
+        assert_eq!(
+            err,
+            InvalidChainInfo::PubkeyType {
+                scheme: Scheme::BlsUnchainedOnG1
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Scheme bls-unchained-on-g1 must be verified with G2PubkeyFastnet"
+        );
+
+        let mut info = ChainInfo::from(&QUICKNET);
+        info.public_key = vec![0xff; 96];
+        assert_eq!(
+            info.beacon_key().err(),
+            Some(InvalidChainInfo::InvalidPublicKey)
+        );
+        assert_eq!(
+            info.pubkey::<G2PubkeyRfc>().err(),
+            Some(InvalidChainInfo::InvalidPublicKey)
+        );
+        info.scheme_id = "unknown".to_string();
+        assert!(matches!(
+            info.beacon_key(),
+            Err(InvalidChainInfo::UnknownScheme(_))
+        ));
+        assert!(matches!(
+            info.dyn_pubkey(),
+            Err(InvalidChainInfo::UnknownScheme(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_works() {
+        use crate::networks::QUICKNET;
+
+        // curl -sS https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info
+        let json = r#"{"public_key":"83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a","period":3,"genesis_time":1692803367,"hash":"52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971","groupHash":"f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e","schemeID":"bls-unchained-g1-rfc9380","metadata":{"beaconID":"quicknet"}}"#;
+        let info: ChainInfo = serde_json::from_str(json).unwrap();
+        let mut expected = ChainInfo::from(&QUICKNET);
+        expected.group_hash = Some(hex_literal::hex!(
+            "f477d5c89f21a17c863a7f937c6a6d15859414d2be09cd448d4279af331c5d3e"
+        ));
+        assert_eq!(info, expected);
+        assert!(info.pubkey::<G2PubkeyRfc>().is_ok());
+
+        // roundtrip without metadata
+        let serialized = serde_json::to_string(&info).unwrap();
+        assert!(serialized.contains(r#""schemeID":"bls-unchained-g1-rfc9380""#));
+        assert_eq!(
+            serde_json::from_str::<ChainInfo>(&serialized).unwrap(),
+            info
+        );
+
+        let serialized = serde_json::to_string(&ChainInfo::from(&QUICKNET)).unwrap();
+        assert!(!serialized.contains("groupHash"));
+
+        let invalid = json.replace(r#""hash":"52db9ba7"#, r#""hash":""#);
+        let err = serde_json::from_str::<ChainInfo>(&invalid).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("hash must be 32 hex encoded bytes"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn rounds_between_works() {
+        let info = ChainInfo::from(&MAINNET);
+        let rounds: Vec<(u64, u64)> = info.rounds_between(1595471100u64, 1595471130u64).collect();
+        assert_eq!(rounds, [(1336, 1595471100), (1337, 1595471130)]);
+    }
+
+    #[test]
+    fn check_group_works() {
+        let mut info = ChainInfo::from(&MAINNET);
+        let group = GroupInfo {
+            public_key: MAINNET.public_key.to_vec(),
+            group_hash: [0x17; 32],
+            period: 30,
+            genesis_time: 1595431050,
+            scheme_id: "pedersen-bls-chained".to_string(),
+        };
+        assert_eq!(
+            info.check_group(&group),
+            Err(GroupMismatch::MissingGroupHash)
+        );
+
+        info.group_hash = Some([0x17; 32]);
+        assert_eq!(info.check_group(&group), Ok(()));
+
+        let mut other = group.clone();
+        other.group_hash = [0x18; 32];
+        assert_eq!(
+            info.check_group(&other),
+            Err(GroupMismatch::GroupHash {
+                chain_info: [0x17; 32],
+                group: [0x18; 32]
+            })
+        );
+
+        let mut other = group.clone();
+        other.public_key[47] ^= 1;
+        assert!(matches!(
+            info.check_group(&other),
+            Err(GroupMismatch::PublicKey { .. })
+        ));
+
+        let mut other = group.clone();
+        other.period = 3;
+        assert_eq!(
+            info.check_group(&other),
+            Err(GroupMismatch::Period {
+                chain_info: 30,
+                group: 3
+            })
+        );
+        assert_eq!(
+            info.check_group(&other).unwrap_err().to_string(),
+            "Period mismatch: chain info has 30, group has 3"
+        );
+
+        let mut other = group.clone();
+        other.genesis_time += 1;
+        assert!(matches!(
+            info.check_group(&other),
+            Err(GroupMismatch::GenesisTime { .. })
+        ));
+
+        let mut other = group;
+        other.scheme_id = "pedersen-bls-unchained".to_string();
+        assert!(matches!(
+            info.check_group(&other),
+            Err(GroupMismatch::SchemeId { .. })
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+//! Heuristics to find out what an unknown byte blob could be.
+//!
+//! This is useful for better error messages, e.g. when a signature is passed where a public key is expected.
+
+use bls12_381::{G1Affine, G2Affine};
+
+/// A kind of drand data that a blob of bytes can be
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PossibleKind {
+    /// A compressed G1 point used as public key (pedersen-bls-chained, pedersen-bls-unchained)
+    G1Pubkey,
+    /// A compressed G1 point used as signature (bls-unchained-on-g1, bls-unchained-g1-rfc9380)
+    G1Signature,
+    /// A compressed G2 point used as public key (bls-unchained-on-g1, bls-unchained-g1-rfc9380)
+    G2Pubkey,
+    /// A compressed G2 point used as signature (pedersen-bls-chained, pedersen-bls-unchained)
+    G2Signature,
+    /// A 32 byte hash like a randomness value or a chain hash
+    Hash,
+}
+
+/// Bit set in the first byte of compressed points
+const COMPRESSION_FLAG: u8 = 0b1000_0000;
+
+/// Returns all kinds of data `bytes` could be, considering the length,
+/// the flag bits of compressed points and whether the point can be decoded.
+///
+/// Points at infinity are not considered valid public keys or signatures.
+///
+/// ## Examples
+///
+/// ```
+/// use hex_literal::hex;
+/// use drand_verify::classify::{classify, PossibleKind};
+///
+/// let blob = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// assert_eq!(classify(&blob), [PossibleKind::G1Pubkey, PossibleKind::G1Signature]);
+///
+/// assert_eq!(classify(b"garbage"), []);
+/// ```
+pub fn classify(bytes: &[u8]) -> Vec<PossibleKind> {
+    match bytes.len() {
+        32 => vec![PossibleKind::Hash],
+        48 => {
+            let data: [u8; 48] = bytes.try_into().unwrap();
+            let valid = has_compression_flag(&data)
+                && Option::<G1Affine>::from(G1Affine::from_compressed(&data))
+                    .map_or(false, |point| !bool::from(point.is_identity()));
+            if valid {
+                vec![PossibleKind::G1Pubkey, PossibleKind::G1Signature]
+            } else {
+                vec![]
+            }
+        }
+        96 => {
+            let data: [u8; 96] = bytes.try_into().unwrap();
+            let valid = has_compression_flag(&data)
+                && Option::<G2Affine>::from(G2Affine::from_compressed(&data))
+                    .map_or(false, |point| !bool::from(point.is_identity()));
+            if valid {
+                vec![PossibleKind::G2Pubkey, PossibleKind::G2Signature]
+            } else {
+                vec![]
+            }
+        }
+        _ => vec![],
+    }
+}
+
+fn has_compression_flag(data: &[u8]) -> bool {
+    data[0] & COMPRESSION_FLAG != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn classify_works() {
+        // Mainnet public key
+        let g1 = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        assert_eq!(
+            classify(&g1),
+            [PossibleKind::G1Pubkey, PossibleKind::G1Signature]
+        );
+        // Mainnet signature
+        let g2 = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert_eq!(
+            classify(&g2),
+            [PossibleKind::G2Pubkey, PossibleKind::G2Signature]
+        );
+        // Randomness
+        let randomness = hex!("8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9");
+        assert_eq!(classify(&randomness), [PossibleKind::Hash]);
+
+        // Compression flag not set
+        let mut uncompressed = g1;
+        uncompressed[0] &= !COMPRESSION_FLAG;
+        assert_eq!(classify(&uncompressed), []);
+
+        // Not on curve
+        let mut corrupted = g2;
+        corrupted[95] ^= 0x02;
+        assert_eq!(classify(&corrupted), []);
+
+        // Point at infinity
+        let mut infinity = [0u8; 48];
+        infinity[0] = 0xc0;
+        assert_eq!(classify(&infinity), []);
+
+        assert_eq!(classify(&[]), []);
+        assert_eq!(classify(&g1[..47]), []);
+    }
+}
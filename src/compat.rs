@@ -0,0 +1,334 @@
+//! A differential test harness for BLS12-381 backends.
+//!
+//! A new backend must agree with the existing one on every input before it can be accepted.
+//! [`run_compat_suite`] verifies the known beacon of every scheme and deterministic mutations
+//! of it (flipped bits, other rounds, truncated and random inputs) with two [`VerifyBackend`]s
+//! and reports every input on which their results differ. [`Zkcrypto`] is the backend used by
+//! this crate.
+
+use sha2::{Digest, Sha256};
+
+use crate::backend::{KnownAnswer, BACKEND_NAME, KNOWN_ANSWERS};
+use crate::scheme::Scheme;
+use crate::verify::{ErrorCode, G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// A BLS12-381 implementation that can verify beacons of all schemes
+pub trait VerifyBackend {
+    fn name(&self) -> &str;
+
+    /// Verifies a beacon. Malformed inputs are reported with their [`ErrorCode`].
+    fn verify(
+        &self,
+        scheme: Scheme,
+        public_key: &[u8],
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, ErrorCode>;
+}
+
+/// The zkcrypto/bls12_381 backend used by the [`Pubkey`] implementations of this crate
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zkcrypto;
+
+impl VerifyBackend for Zkcrypto {
+    fn name(&self) -> &str {
+        BACKEND_NAME
+    }
+
+    fn verify(
+        &self,
+        scheme: Scheme,
+        public_key: &[u8],
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, ErrorCode> {
+        match scheme {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                verify_with::<G1Pubkey>(public_key, round, previous_signature, signature)
+            }
+            Scheme::BlsUnchainedOnG1 => {
+                verify_with::<G2PubkeyFastnet>(public_key, round, previous_signature, signature)
+            }
+            Scheme::BlsUnchainedG1Rfc9380 => {
+                verify_with::<G2PubkeyRfc>(public_key, round, previous_signature, signature)
+            }
+        }
+    }
+}
+
+fn verify_with<P: Pubkey>(
+    public_key: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Result<bool, ErrorCode> {
+    let pk = P::from_variable(public_key).map_err(|err| err.code())?;
+    pk.verify(round, previous_signature, signature)
+        .map_err(|err| err.code())
+}
+
+/// A change applied to a known beacon to derive a test input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mutation {
+    /// The known valid beacon
+    None,
+    /// One bit of the signature is flipped
+    FlipSignatureBit { bit: usize },
+    /// One bit of the previous signature is flipped, or appended to unchained beacons
+    FlipPreviousSignatureBit { bit: usize },
+    /// One bit of the public key is flipped
+    FlipPublicKeyBit { bit: usize },
+    /// The round is changed
+    OtherRound { round: u64 },
+    /// The signature is shortened to `len` bytes
+    TruncateSignature { len: usize },
+    /// The signature is replaced by random bytes of the same length
+    RandomSignature,
+}
+
+/// One input on which the backends disagree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatMismatch {
+    pub iteration: usize,
+    pub scheme: Scheme,
+    pub mutation: Mutation,
+    pub public_key: Vec<u8>,
+    pub round: u64,
+    pub previous_signature: Vec<u8>,
+    pub signature: Vec<u8>,
+    /// The result of the first backend
+    pub a: Result<bool, ErrorCode>,
+    /// The result of the second backend
+    pub b: Result<bool, ErrorCode>,
+}
+
+/// The result of [`run_compat_suite`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompatReport {
+    /// The number of inputs checked
+    pub iterations: usize,
+    pub mismatches: Vec<CompatMismatch>,
+}
+
+impl CompatReport {
+    /// Returns true if the backends agreed on all inputs
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Verifies `iterations` inputs with both backends and reports the inputs on which their
+/// results differ.
+///
+/// The inputs are derived deterministically from the iteration number, so a mismatch can be
+/// reproduced from its report. Every scheme is covered once the number of iterations is at least
+/// 4, every kind of [`Mutation`] at 28.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::compat::{run_compat_suite, Zkcrypto};
+///
+/// let report = run_compat_suite(&Zkcrypto, &Zkcrypto, 28);
+/// assert!(report.passed());
+/// ```
+pub fn run_compat_suite<A: VerifyBackend + ?Sized, B: VerifyBackend + ?Sized>(
+    backend_a: &A,
+    backend_b: &B,
+    iterations: usize,
+) -> CompatReport {
+    let mismatches = (0..iterations)
+        .filter_map(|iteration| {
+            let input = compat_input(iteration);
+            let a = backend_a.verify(
+                input.scheme,
+                &input.public_key,
+                input.round,
+                &input.previous_signature,
+                &input.signature,
+            );
+            let b = backend_b.verify(
+                input.scheme,
+                &input.public_key,
+                input.round,
+                &input.previous_signature,
+                &input.signature,
+            );
+            (a != b).then(|| CompatMismatch {
+                iteration,
+                scheme: input.scheme,
+                mutation: input.mutation,
+                public_key: input.public_key,
+                round: input.round,
+                previous_signature: input.previous_signature,
+                signature: input.signature,
+                a,
+                b,
+            })
+        })
+        .collect();
+    CompatReport {
+        iterations,
+        mismatches,
+    }
+}
+
+struct CompatInput {
+    scheme: Scheme,
+    mutation: Mutation,
+    public_key: Vec<u8>,
+    round: u64,
+    previous_signature: Vec<u8>,
+    signature: Vec<u8>,
+}
+
+/// The number of kinds of [`Mutation`]
+const MUTATIONS: usize = 7;
+
+fn compat_input(iteration: usize) -> CompatInput {
+    let answer: &KnownAnswer = &KNOWN_ANSWERS[iteration % KNOWN_ANSWERS.len()];
+    let mut hasher = Sha256::new();
+    hasher.update(b"drand-verify compat");
+    hasher.update((iteration as u64).to_be_bytes());
+    let seed: [u8; 32] = hasher.finalize().into();
+    let random = u64::from_be_bytes(seed[..8].try_into().unwrap());
+
+    let mut input = CompatInput {
+        scheme: answer.scheme,
+        mutation: Mutation::None,
+        public_key: answer.public_key.to_vec(),
+        round: answer.round,
+        previous_signature: answer.previous_signature.to_vec(),
+        signature: answer.signature.to_vec(),
+    };
+    input.mutation = match (iteration / KNOWN_ANSWERS.len()) % MUTATIONS {
+        0 => Mutation::None,
+        1 => {
+            let bit = random as usize % (input.signature.len() * 8);
+            input.signature[bit / 8] ^= 1 << (bit % 8);
+            Mutation::FlipSignatureBit { bit }
+        }
+        2 => {
+            if input.previous_signature.is_empty() {
+                input.previous_signature = answer.signature.to_vec();
+            }
+            let bit = random as usize % (input.previous_signature.len() * 8);
+            input.previous_signature[bit / 8] ^= 1 << (bit % 8);
+            Mutation::FlipPreviousSignatureBit { bit }
+        }
+        3 => {
+            let bit = random as usize % (input.public_key.len() * 8);
+            input.public_key[bit / 8] ^= 1 << (bit % 8);
+            Mutation::FlipPublicKeyBit { bit }
+        }
+        4 => {
+            input.round = random;
+            Mutation::OtherRound { round: random }
+        }
+        5 => {
+            let len = random as usize % input.signature.len();
+            input.signature.truncate(len);
+            Mutation::TruncateSignature { len }
+        }
+        _ => {
+            let mut bytes = Vec::with_capacity(input.signature.len());
+            let mut block = seed;
+            while bytes.len() < input.signature.len() {
+                block = Sha256::digest(&block[..]).into();
+                bytes.extend_from_slice(&block);
+            }
+            bytes.truncate(input.signature.len());
+            input.signature = bytes;
+            Mutation::RandomSignature
+        }
+    };
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A backend that accepts every beacon with a well-formed signature
+    struct AcceptAll;
+
+    impl VerifyBackend for AcceptAll {
+        fn name(&self) -> &str {
+            "accept-all"
+        }
+
+        fn verify(
+            &self,
+            scheme: Scheme,
+            _public_key: &[u8],
+            _round: u64,
+            _previous_signature: &[u8],
+            signature: &[u8],
+        ) -> Result<bool, ErrorCode> {
+            if signature.len() == scheme.signature_group().compressed_size() {
+                Ok(true)
+            } else {
+                Err(ErrorCode::InvalidSignature)
+            }
+        }
+    }
+
+    #[test]
+    fn run_compat_suite_works() {
+        let report = run_compat_suite(&Zkcrypto, &Zkcrypto, 2 * KNOWN_ANSWERS.len() * MUTATIONS);
+        assert_eq!(report.iterations, 56);
+        assert!(report.passed());
+
+        let report = run_compat_suite(&Zkcrypto, &AcceptAll, KNOWN_ANSWERS.len() * MUTATIONS);
+        assert!(!report.passed());
+        // the unmutated beacons are valid in both
+        assert!(report
+            .mismatches
+            .iter()
+            .all(|mismatch| mismatch.mutation != Mutation::None));
+        let mismatch = report
+            .mismatches
+            .iter()
+            .find(|mismatch| matches!(mismatch.mutation, Mutation::OtherRound { .. }))
+            .unwrap();
+        assert_eq!((mismatch.a, mismatch.b), (Ok(false), Ok(true)));
+    }
+
+    #[test]
+    fn compat_inputs_cover_all_mutations() {
+        let inputs: Vec<CompatInput> = (0..KNOWN_ANSWERS.len() * MUTATIONS)
+            .map(compat_input)
+            .collect();
+        let results: Vec<Result<bool, ErrorCode>> = inputs
+            .iter()
+            .map(|input| {
+                Zkcrypto.verify(
+                    input.scheme,
+                    &input.public_key,
+                    input.round,
+                    &input.previous_signature,
+                    &input.signature,
+                )
+            })
+            .collect();
+        assert!(results[..KNOWN_ANSWERS.len()]
+            .iter()
+            .all(|r| *r == Ok(true)));
+        assert!(results[KNOWN_ANSWERS.len()..]
+            .iter()
+            .all(|r| *r != Ok(true)));
+        assert!(matches!(
+            inputs[5 * KNOWN_ANSWERS.len()].mutation,
+            Mutation::TruncateSignature { .. }
+        ));
+        assert_eq!(
+            inputs[6 * KNOWN_ANSWERS.len()].mutation,
+            Mutation::RandomSignature
+        );
+
+        // deterministic
+        assert_eq!(compat_input(9).signature, inputs[9].signature);
+    }
+}
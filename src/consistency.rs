@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::beacon::{Beacon, VerifiedBeacon};
+use crate::randomness::derive_randomness;
+use crate::signature::constant_time_eq_signatures;
+use crate::verify::Pubkey;
+
+/// Evidence that different beacons were received for the same round of a chain.
+///
+/// See [`check_consistency`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Equivocation {
+    pub round: u64,
+    /// The distinct beacons received for this round, sorted by signature
+    pub beacons: Vec<Beacon>,
+}
+
+impl Equivocation {
+    /// The distinct randomness values of the conflicting beacons
+    pub fn randomness_values(&self) -> Vec<[u8; 32]> {
+        let mut values: Vec<[u8; 32]> = self
+            .beacons
+            .iter()
+            .map(|beacon| derive_randomness(&beacon.signature))
+            .collect();
+        values.sort();
+        values.dedup();
+        values
+    }
+
+    /// Returns true if at least two of the conflicting beacons are valid for the given key.
+    ///
+    /// If only one beacon is valid, the other relays served invalid data, which verification
+    /// catches anyway. Two valid beacons for the same round cannot be created by a correctly
+    /// operating network and are proof of misbehaviour.
+    pub fn is_proven<P: Pubkey>(&self, pubkey: &P) -> bool {
+        self.beacons
+            .iter()
+            .filter(|beacon| {
+                pubkey
+                    .verify(beacon.round, &beacon.previous_signature, &beacon.signature)
+                    .unwrap_or(false)
+            })
+            .take(2)
+            .count()
+            == 2
+    }
+}
+
+impl fmt::Display for Equivocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Round {}: {} conflicting beacons with {} distinct randomness values",
+            self.round,
+            self.beacons.len(),
+            self.randomness_values().len()
+        )
+    }
+}
+
+/// Checks that all beacons of the same round are identical.
+///
+/// The input is a collection of beacons of one chain, e.g. the same rounds fetched from multiple
+/// relays. For every round with conflicting signatures or previous signatures an [`Equivocation`]
+/// is returned, sorted by round. An empty result means the beacons are consistent.
+///
+/// No signatures are verified. Use [`Equivocation::is_proven`] to check if the conflict is caused
+/// by the network itself rather than a relay serving invalid data.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{check_consistency, Beacon};
+///
+/// let a = Beacon { round: 5, previous_signature: vec![], signature: vec![1; 48] };
+/// let b = Beacon { round: 5, previous_signature: vec![], signature: vec![2; 48] };
+/// assert!(check_consistency(&[a.clone(), a.clone()]).is_empty());
+///
+/// let conflicts = check_consistency(&[a, b]);
+/// assert_eq!(conflicts.len(), 1);
+/// assert_eq!(conflicts[0].round, 5);
+/// ```
+pub fn check_consistency(beacons: &[Beacon]) -> Vec<Equivocation> {
+    let mut by_round: BTreeMap<u64, Vec<&Beacon>> = BTreeMap::new();
+    for beacon in beacons {
+        by_round.entry(beacon.round).or_default().push(beacon);
+    }
+
+    by_round
+        .into_iter()
+        .filter_map(|(round, mut candidates)| {
+            candidates.sort_by(|a, b| {
+                (&a.signature, &a.previous_signature).cmp(&(&b.signature, &b.previous_signature))
+            });
+            candidates.dedup();
+            if candidates.len() < 2 {
+                return None;
+            }
+            Some(Equivocation {
+                round,
+                beacons: candidates.into_iter().cloned().collect(),
+            })
+        })
+        .collect()
+}
+
+/// A randomness value claimed by an auxiliary source that differs from the randomness derived
+/// from a verified beacon. See [`cross_check_randomness`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RandomnessMismatch {
+    /// The name of the source, e.g. the URL of a relay
+    pub source: String,
+    pub round: u64,
+    /// The randomness derived from the verified signature
+    pub expected: [u8; 32],
+    /// The randomness claimed by the source
+    pub claimed: Vec<u8>,
+    /// The indices of all bytes that differ, including missing and extra bytes
+    pub differing_bytes: Vec<usize>,
+}
+
+impl fmt::Display for RandomnessMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Randomness of round {} from {} differs in {} bytes",
+            self.round,
+            self.source,
+            self.differing_bytes.len()
+        )?;
+        if let Some(first) = self.differing_bytes.first() {
+            write!(f, ", first at index {}", first)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the randomness of a verified beacon with the randomness claimed by other sources.
+///
+/// Relays serve a `randomness` field next to the signature, which clients often use without
+/// recomputing it. This returns a [`RandomnessMismatch`] for every `(source, randomness)`
+/// claim that differs from [`VerifiedBeacon::randomness`], in the order of the claims.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{cross_check_randomness, networks, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let beacon = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+///
+/// let correct = beacon.randomness();
+/// let mut wrong = correct;
+/// wrong[3] ^= 1;
+/// let mismatches = cross_check_randomness(
+///     &beacon,
+///     [("relay-a", &correct[..]), ("relay-b", &wrong[..])],
+/// );
+/// assert_eq!(mismatches.len(), 1);
+/// assert_eq!(mismatches[0].source, "relay-b");
+/// assert_eq!(mismatches[0].differing_bytes, [3]);
+/// ```
+pub fn cross_check_randomness<'a, I>(beacon: &VerifiedBeacon, claims: I) -> Vec<RandomnessMismatch>
+where
+    I: IntoIterator<Item = (&'a str, &'a [u8])>,
+{
+    let expected = beacon.randomness();
+    claims
+        .into_iter()
+        .filter_map(|(source, claimed)| {
+            if constant_time_eq_signatures(claimed, &expected) {
+                return None;
+            }
+            let len = claimed.len().max(expected.len());
+            let differing_bytes: Vec<usize> = (0..len)
+                .filter(|&index| claimed.get(index) != expected.get(index))
+                .collect();
+            if differing_bytes.is_empty() {
+                return None;
+            }
+            Some(RandomnessMismatch {
+                source: source.to_string(),
+                round: beacon.round(),
+                expected,
+                claimed: claimed.to_vec(),
+                differing_bytes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::G2PubkeyRfc;
+    use hex_literal::hex;
+
+    fn beacon(round: u64, previous_signature: &[u8], signature: &[u8]) -> Beacon {
+        Beacon {
+            round,
+            previous_signature: previous_signature.to_vec(),
+            signature: signature.to_vec(),
+        }
+    }
+
+    #[test]
+    fn check_consistency_works() {
+        assert_eq!(check_consistency(&[]), []);
+
+        let beacons = [
+            beacon(1, &[], &[1; 48]),
+            beacon(2, &[], &[2; 48]),
+            beacon(1, &[], &[1; 48]),
+        ];
+        assert_eq!(check_consistency(&beacons), []);
+
+        let beacons = [
+            beacon(2, &[], &[2; 48]),
+            beacon(1, &[], &[1; 48]),
+            beacon(2, &[], &[3; 48]),
+            beacon(1, &[], &[4; 48]),
+            beacon(2, &[], &[2; 48]),
+        ];
+        let conflicts = check_consistency(&beacons);
+        assert_eq!(
+            conflicts,
+            [
+                Equivocation {
+                    round: 1,
+                    beacons: vec![beacon(1, &[], &[1; 48]), beacon(1, &[], &[4; 48])]
+                },
+                Equivocation {
+                    round: 2,
+                    beacons: vec![beacon(2, &[], &[2; 48]), beacon(2, &[], &[3; 48])]
+                }
+            ]
+        );
+        assert_eq!(conflicts[0].randomness_values().len(), 2);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "Round 1: 2 conflicting beacons with 2 distinct randomness values"
+        );
+
+        // Same signature with different previous signatures
+        let beacons = [beacon(7, &[0; 96], &[1; 96]), beacon(7, &[9; 96], &[1; 96])];
+        let conflicts = check_consistency(&beacons);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].randomness_values().len(), 1);
+    }
+
+    #[test]
+    fn cross_check_randomness_works() {
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let beacon = VerifiedBeacon::new(123, &[], &signature);
+        let correct = beacon.randomness();
+
+        assert!(cross_check_randomness(&beacon, []).is_empty());
+        assert!(cross_check_randomness(&beacon, [("a", &correct[..])]).is_empty());
+
+        let mut wrong = correct;
+        wrong[0] ^= 0xff;
+        wrong[31] ^= 0x01;
+        let truncated = &correct[..30];
+        let mismatches = cross_check_randomness(
+            &beacon,
+            [
+                ("a", &wrong[..]),
+                ("b", &correct[..]),
+                ("c", truncated),
+                ("d", &signature[..]),
+            ],
+        );
+        assert_eq!(mismatches.len(), 3);
+        assert_eq!(
+            mismatches[0],
+            RandomnessMismatch {
+                source: "a".to_string(),
+                round: 123,
+                expected: correct,
+                claimed: wrong.to_vec(),
+                differing_bytes: vec![0, 31],
+            }
+        );
+        assert_eq!(
+            mismatches[0].to_string(),
+            "Randomness of round 123 from a differs in 2 bytes, first at index 0"
+        );
+        assert_eq!(mismatches[1].source, "c");
+        assert_eq!(mismatches[1].differing_bytes, [30, 31]);
+        // extra bytes are reported as well
+        assert_eq!(mismatches[2].source, "d");
+        assert!(mismatches[2].differing_bytes.contains(&47));
+    }
+
+    #[test]
+    fn is_proven_works() {
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let valid = beacon(123, &[], &hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"));
+        let invalid = beacon(123, &[], &[0xaa; 48]);
+
+        let conflicts = check_consistency(&[valid, invalid]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(!conflicts[0].is_proven(&pk));
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn is_proven_works_for_forks() {
+        use crate::signer::SecretKey;
+        use crate::G1Pubkey;
+
+        // A chained network signing round 2 on top of two different versions of round 1
+        let sk = SecretKey::from_seed(b"fork");
+        let pk = G1Pubkey::from_variable(&sk.public_key::<G1Pubkey>()).unwrap();
+        let round1_a = sk.sign::<G1Pubkey>(1, &[1; 32]);
+        let round1_b = sk.sign::<G1Pubkey>(1, &[2; 32]);
+        let a = beacon(2, &round1_a, &sk.sign::<G1Pubkey>(2, &round1_a));
+        let b = beacon(2, &round1_b, &sk.sign::<G1Pubkey>(2, &round1_b));
+
+        let conflicts = check_consistency(&[a, b]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].is_proven(&pk));
+    }
+}
@@ -0,0 +1,164 @@
+//! Operation counts for deriving deterministic gas schedules.
+//!
+//! Contract platforms that embed this crate need to charge for beacon verification before
+//! running it. [`estimated_cost`] returns the number of expensive operations an operation
+//! performs for a scheme. The counts only depend on the scheme and operation, never on the input.
+
+use crate::scheme::Scheme;
+use crate::verify::Group;
+
+/// An operation of this crate whose cost can be estimated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// Decoding and checking a compressed public key, e.g. [`Pubkey::from_variable`](crate::Pubkey::from_variable)
+    DecodePublicKey,
+    /// Decoding and checking a compressed signature
+    DecodeSignature,
+    /// Verifying a beacon with an already decoded public key, e.g. [`Pubkey::verify`](crate::Pubkey::verify)
+    Verify,
+    /// [`derive_randomness`](crate::derive_randomness)
+    DeriveRandomness,
+}
+
+/// The number of expensive operations performed by an [`Operation`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Cost {
+    /// Pairings computed. The pairings of one check share a single final exponentiation.
+    pub pairings: u32,
+    pub hash_to_g1: u32,
+    pub hash_to_g2: u32,
+    pub subgroup_checks_g1: u32,
+    pub subgroup_checks_g2: u32,
+    /// SHA-256 hashes of inputs up to 128 bytes, not counting those inside hash-to-curve
+    pub sha256: u32,
+}
+
+/// The cost of `op` for `scheme`.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::cost::{estimated_cost, Operation};
+/// use drand_verify::Scheme;
+///
+/// let cost = estimated_cost(Scheme::BlsUnchainedG1Rfc9380, Operation::Verify);
+/// assert_eq!(cost.pairings, 2);
+/// assert_eq!(cost.hash_to_g1, 1);
+/// assert_eq!(cost.subgroup_checks_g1, 1);
+/// ```
+pub const fn estimated_cost(scheme: Scheme, op: Operation) -> Cost {
+    let signature_group = scheme.signature_group();
+    match op {
+        Operation::DecodePublicKey => subgroup_check(scheme.key_group()),
+        Operation::DecodeSignature => subgroup_check(signature_group),
+        Operation::Verify => {
+            // the message hash, hashing it to the signature group, decoding the
+            // signature (and the previous signature) and the pairing check
+            let checks = if scheme.is_chained() { 2 } else { 1 };
+            let mut cost = ZERO;
+            match signature_group {
+                Group::G1 => {
+                    cost.hash_to_g1 = 1;
+                    cost.subgroup_checks_g1 = checks;
+                }
+                Group::G2 => {
+                    cost.hash_to_g2 = 1;
+                    cost.subgroup_checks_g2 = checks;
+                }
+            }
+            cost.pairings = 2;
+            cost.sha256 = 1;
+            cost
+        }
+        Operation::DeriveRandomness => Cost { sha256: 1, ..ZERO },
+    }
+}
+
+const ZERO: Cost = Cost {
+    pairings: 0,
+    hash_to_g1: 0,
+    hash_to_g2: 0,
+    subgroup_checks_g1: 0,
+    subgroup_checks_g2: 0,
+    sha256: 0,
+};
+
+const fn subgroup_check(group: Group) -> Cost {
+    match group {
+        Group::G1 => Cost {
+            subgroup_checks_g1: 1,
+            ..ZERO
+        },
+        Group::G2 => Cost {
+            subgroup_checks_g2: 1,
+            ..ZERO
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimated_cost_works() {
+        // the previous signature is checked as well
+        assert_eq!(
+            estimated_cost(Scheme::PedersenBlsChained, Operation::Verify),
+            Cost {
+                pairings: 2,
+                hash_to_g2: 1,
+                subgroup_checks_g2: 2,
+                sha256: 1,
+                ..Cost::default()
+            }
+        );
+        assert_eq!(
+            estimated_cost(Scheme::PedersenBlsUnchained, Operation::Verify),
+            Cost {
+                pairings: 2,
+                hash_to_g2: 1,
+                subgroup_checks_g2: 1,
+                sha256: 1,
+                ..Cost::default()
+            }
+        );
+        for scheme in [Scheme::PedersenBlsChained, Scheme::PedersenBlsUnchained] {
+            assert_eq!(
+                estimated_cost(scheme, Operation::DecodePublicKey),
+                Cost {
+                    subgroup_checks_g1: 1,
+                    ..Cost::default()
+                }
+            );
+        }
+        for scheme in [Scheme::BlsUnchainedOnG1, Scheme::BlsUnchainedG1Rfc9380] {
+            assert_eq!(
+                estimated_cost(scheme, Operation::Verify),
+                Cost {
+                    pairings: 2,
+                    hash_to_g1: 1,
+                    subgroup_checks_g1: 1,
+                    sha256: 1,
+                    ..Cost::default()
+                }
+            );
+            assert_eq!(
+                estimated_cost(scheme, Operation::DecodeSignature),
+                Cost {
+                    subgroup_checks_g1: 1,
+                    ..Cost::default()
+                }
+            );
+        }
+        for scheme in Scheme::ALL {
+            assert_eq!(
+                estimated_cost(scheme, Operation::DeriveRandomness),
+                Cost {
+                    sha256: 1,
+                    ..Cost::default()
+                }
+            );
+        }
+    }
+}
@@ -0,0 +1,453 @@
+//! A file format describing a drand network.
+//!
+//! A network descriptor contains everything needed to verify beacons of a network and to find
+//! relays serving it. The format is a subset of TOML, so descriptors can be edited by hand and
+//! read by any TOML parser:
+//!
+//! ```toml
+//! name = "quicknet"
+//! scheme = "bls-unchained-g1-rfc9380"
+//! public_key = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"
+//! chain_hash = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"
+//! genesis_time = 1692803367
+//! period = 3
+//! relays = ["https://api.drand.sh", "https://api2.drand.sh"]
+//! ```
+//!
+//! Supported are `key = value` lines where values are strings, integers or arrays of strings
+//! on a single line. Empty lines and comments starting with `#` are ignored.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::chain_hash::ChainHash;
+use crate::chain_info::ChainInfo;
+use crate::networks::Network;
+use crate::scheme::Scheme;
+
+/// The description of a drand network, see the [module documentation](self) for the file format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkDescriptor {
+    /// A short, human readable name like "quicknet"
+    pub name: String,
+    pub scheme: Scheme,
+    /// The group public key in compressed format (48 or 96 bytes, depending on the scheme)
+    pub public_key: Vec<u8>,
+    /// The chain hash identifying the network
+    pub chain_hash: ChainHash,
+    /// The time of round 1 in seconds since the Unix epoch
+    pub genesis_time: u64,
+    /// The period between two rounds in seconds
+    pub period: u64,
+    /// Base URLs of HTTP relays serving this network
+    pub relays: Vec<String>,
+}
+
+#[derive(Debug)]
+pub enum DescriptorError {
+    Io(io::Error),
+    /// A line could not be parsed
+    Syntax {
+        line: usize,
+        msg: String,
+    },
+    MissingField {
+        field: &'static str,
+    },
+    InvalidField {
+        field: &'static str,
+        msg: String,
+    },
+}
+
+impl fmt::Display for DescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DescriptorError::Io(err) => write!(f, "I/O error: {}", err),
+            DescriptorError::Syntax { line, msg } => {
+                write!(f, "Syntax error in line {}: {}", line, msg)
+            }
+            DescriptorError::MissingField { field } => write!(f, "Missing field {}", field),
+            DescriptorError::InvalidField { field, msg } => {
+                write!(f, "Invalid field {}: {}", field, msg)
+            }
+        }
+    }
+}
+
+impl Error for DescriptorError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            DescriptorError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DescriptorError {
+    fn from(source: io::Error) -> Self {
+        DescriptorError::Io(source)
+    }
+}
+
+enum Value {
+    String(String),
+    Integer(u64),
+    Array(Vec<String>),
+}
+
+impl NetworkDescriptor {
+    /// Parses a descriptor. Unknown keys are ignored for forward compatibility.
+    pub fn parse(input: &str) -> Result<Self, DescriptorError> {
+        let mut name = None;
+        let mut scheme = None;
+        let mut public_key = None;
+        let mut chain_hash = None;
+        let mut genesis_time = None;
+        let mut period = None;
+        let mut relays = None;
+
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let syntax = |msg: &str| DescriptorError::Syntax {
+                line: index + 1,
+                msg: msg.to_string(),
+            };
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| syntax("Expected key = value"))?;
+            let value = parse_value(value.trim()).map_err(|msg| syntax(&msg))?;
+            match key.trim() {
+                "name" => name = Some(string_field("name", value)?),
+                "scheme" => {
+                    let value = string_field("scheme", value)?;
+                    scheme = Some(Scheme::from_scheme_str(&value).map_err(|err| {
+                        DescriptorError::InvalidField {
+                            field: "scheme",
+                            msg: err.to_string(),
+                        }
+                    })?);
+                }
+                "public_key" => public_key = Some(hex_field("public_key", value)?),
+                "chain_hash" => {
+                    let bytes = hex_field("chain_hash", value)?;
+                    let bytes: [u8; 32] =
+                        bytes
+                            .try_into()
+                            .map_err(|_| DescriptorError::InvalidField {
+                                field: "chain_hash",
+                                msg: "Must be 32 bytes".to_string(),
+                            })?;
+                    chain_hash = Some(ChainHash::new(bytes));
+                }
+                "genesis_time" => genesis_time = Some(integer_field("genesis_time", value)?),
+                "period" => period = Some(integer_field("period", value)?),
+                "relays" => match value {
+                    Value::Array(urls) => relays = Some(urls),
+                    _ => {
+                        return Err(DescriptorError::InvalidField {
+                            field: "relays",
+                            msg: "Must be an array of strings".to_string(),
+                        })
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        let descriptor = Self {
+            name: name.ok_or(DescriptorError::MissingField { field: "name" })?,
+            scheme: scheme.ok_or(DescriptorError::MissingField { field: "scheme" })?,
+            public_key: public_key.ok_or(DescriptorError::MissingField {
+                field: "public_key",
+            })?,
+            chain_hash: chain_hash.ok_or(DescriptorError::MissingField {
+                field: "chain_hash",
+            })?,
+            genesis_time: genesis_time.ok_or(DescriptorError::MissingField {
+                field: "genesis_time",
+            })?,
+            period: period.ok_or(DescriptorError::MissingField { field: "period" })?,
+            relays: relays.unwrap_or_default(),
+        };
+        descriptor.validate()?;
+        Ok(descriptor)
+    }
+
+    fn validate(&self) -> Result<(), DescriptorError> {
+        self.scheme
+            .check_key_length(self.public_key.len())
+            .map_err(|err| DescriptorError::InvalidField {
+                field: "public_key",
+                msg: err.to_string(),
+            })?;
+        if self.period == 0 {
+            return Err(DescriptorError::InvalidField {
+                field: "period",
+                msg: "Must not be 0".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Reads a descriptor from a file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DescriptorError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Writes the descriptor to a file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DescriptorError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    pub fn chain_info(&self) -> ChainInfo {
+        ChainInfo {
+            public_key: self.public_key.clone(),
+            period: self.period,
+            genesis_time: self.genesis_time,
+            hash: self.chain_hash,
+            scheme_id: self.scheme.as_str().to_string(),
+            group_hash: None,
+        }
+    }
+}
+
+impl fmt::Display for NetworkDescriptor {
+    /// Formats the descriptor in the file format
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "name = {}", quote(&self.name))?;
+        writeln!(f, "scheme = {}", quote(self.scheme.as_str()))?;
+        writeln!(f, "public_key = {}", quote(&hex::encode(&self.public_key)))?;
+        writeln!(f, "chain_hash = {}", quote(&self.chain_hash.to_hex()))?;
+        writeln!(f, "genesis_time = {}", self.genesis_time)?;
+        writeln!(f, "period = {}", self.period)?;
+        let relays: Vec<String> = self.relays.iter().map(|url| quote(url)).collect();
+        writeln!(f, "relays = [{}]", relays.join(", "))
+    }
+}
+
+impl From<&Network> for NetworkDescriptor {
+    fn from(network: &Network) -> Self {
+        Self {
+            name: network.name.to_string(),
+            scheme: Scheme::from_scheme_str(network.scheme_id)
+                .expect("Known networks use supported schemes"),
+            public_key: network.public_key.to_vec(),
+            chain_hash: network.chain_hash,
+            genesis_time: network.genesis_time,
+            period: network.period,
+            relays: network.relays.iter().map(|url| url.to_string()).collect(),
+        }
+    }
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn parse_value(input: &str) -> Result<Value, String> {
+    if input.starts_with('"') {
+        let (value, rest) = parse_string(input)?;
+        if !rest.trim().is_empty() && !rest.trim().starts_with('#') {
+            return Err("Unexpected content after string".to_string());
+        }
+        Ok(Value::String(value))
+    } else if let Some(mut rest) = input.strip_prefix('[') {
+        let mut items = Vec::new();
+        loop {
+            rest = rest.trim_start();
+            if let Some(after) = rest.strip_prefix(']') {
+                if !after.trim().is_empty() && !after.trim().starts_with('#') {
+                    return Err("Unexpected content after array".to_string());
+                }
+                return Ok(Value::Array(items));
+            }
+            let (item, after) = parse_string(rest)?;
+            items.push(item);
+            rest = after.trim_start();
+            rest = rest.strip_prefix(',').unwrap_or(rest);
+        }
+    } else {
+        let number = input.split('#').next().unwrap_or_default().trim();
+        number
+            .parse::<u64>()
+            .map(Value::Integer)
+            .map_err(|_| format!("Invalid value: {}", input))
+    }
+}
+
+/// Parses a basic string starting at the beginning of the input and returns the rest
+fn parse_string(input: &str) -> Result<(String, &str), String> {
+    let mut chars = input
+        .strip_prefix('"')
+        .ok_or("Expected string")?
+        .char_indices();
+    let mut value = String::new();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &input[pos + 2..])),
+            '\\' => match chars.next() {
+                Some((_, '"')) => value.push('"'),
+                Some((_, '\\')) => value.push('\\'),
+                _ => return Err("Unsupported escape sequence".to_string()),
+            },
+            c => value.push(c),
+        }
+    }
+    Err("Unterminated string".to_string())
+}
+
+fn string_field(field: &'static str, value: Value) -> Result<String, DescriptorError> {
+    match value {
+        Value::String(value) => Ok(value),
+        _ => Err(DescriptorError::InvalidField {
+            field,
+            msg: "Must be a string".to_string(),
+        }),
+    }
+}
+
+fn integer_field(field: &'static str, value: Value) -> Result<u64, DescriptorError> {
+    match value {
+        Value::Integer(value) => Ok(value),
+        _ => Err(DescriptorError::InvalidField {
+            field,
+            msg: "Must be an integer".to_string(),
+        }),
+    }
+}
+
+fn hex_field(field: &'static str, value: Value) -> Result<Vec<u8>, DescriptorError> {
+    hex::decode(string_field(field, value)?).map_err(|_| DescriptorError::InvalidField {
+        field,
+        msg: "Must be a hex string".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks;
+
+    #[test]
+    fn roundtrip_works() {
+        for network in networks::KNOWN_NETWORKS {
+            let descriptor = NetworkDescriptor::from(network);
+            let text = descriptor.to_string();
+            assert_eq!(NetworkDescriptor::parse(&text).unwrap(), descriptor);
+        }
+    }
+
+    #[test]
+    fn parse_works() {
+        let text = r#"
+# The quicknet
+name = "quick \"net\""  # with comment
+scheme = "bls-unchained-g1-rfc9380"
+public_key = "83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"
+chain_hash = "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"
+genesis_time = 1692803367
+period = 3 # seconds
+relays = [ "https://api.drand.sh" ,"https://api2.drand.sh", ]
+future_field = "ignored"
+"#;
+        let descriptor = NetworkDescriptor::parse(text).unwrap();
+        assert_eq!(descriptor.name, "quick \"net\"");
+        assert_eq!(descriptor.scheme, Scheme::BlsUnchainedG1Rfc9380);
+        assert_eq!(descriptor.public_key, networks::QUICKNET.public_key);
+        assert_eq!(descriptor.chain_hash, networks::QUICKNET.chain_hash);
+        assert_eq!(descriptor.period, 3);
+        assert_eq!(
+            descriptor.relays,
+            ["https://api.drand.sh", "https://api2.drand.sh"]
+        );
+        assert_eq!(
+            descriptor.chain_info(),
+            ChainInfo::from(&networks::QUICKNET)
+        );
+
+        let descriptor = NetworkDescriptor::parse(&text.replace("relays", "# relays")).unwrap();
+        assert!(descriptor.relays.is_empty());
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_input() {
+        let valid = NetworkDescriptor::from(&networks::QUICKNET).to_string();
+
+        match NetworkDescriptor::parse(&valid.replace("period = 3\n", "")).unwrap_err() {
+            DescriptorError::MissingField { field } => assert_eq!(field, "period"),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse(&valid.replace("period = 3", "period = \"3\"")).unwrap_err()
+        {
+            DescriptorError::InvalidField { field, .. } => assert_eq!(field, "period"),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse(&valid.replace("period = 3", "period = 0")).unwrap_err() {
+            DescriptorError::InvalidField { field, .. } => assert_eq!(field, "period"),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse(
+            &valid.replace("bls-unchained-g1-rfc9380", "pedersen-bls-chained"),
+        )
+        .unwrap_err()
+        {
+            DescriptorError::InvalidField { field, msg } => {
+                assert_eq!(field, "public_key");
+                assert_eq!(
+                    msg,
+                    "Public key of 96 bytes does not fit scheme pedersen-bls-chained which uses 48 byte keys on G1. \
+                     96 byte keys are on G2 as used by bls-unchained-on-g1, bls-unchained-g1-rfc9380."
+                );
+            }
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse(&valid.replace("bls-unchained-g1-rfc9380", "foo"))
+            .unwrap_err()
+        {
+            DescriptorError::InvalidField { field, .. } => assert_eq!(field, "scheme"),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse(&valid.replace("chain_hash = \"52", "chain_hash = \""))
+            .unwrap_err()
+        {
+            DescriptorError::InvalidField { field, .. } => assert_eq!(field, "chain_hash"),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse("name \"foo\"").unwrap_err() {
+            DescriptorError::Syntax { line, .. } => assert_eq!(line, 1),
+            err => panic!("Unexpected error: {}", err),
+        }
+        match NetworkDescriptor::parse("\n\nname = \"foo").unwrap_err() {
+            DescriptorError::Syntax { line, msg } => {
+                assert_eq!(line, 3);
+                assert_eq!(msg, "Unterminated string");
+            }
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+
+    #[test]
+    fn load_and_save_work() {
+        let path = std::env::temp_dir().join(format!(
+            "drand-verify-descriptor-{}.toml",
+            std::process::id()
+        ));
+        let descriptor = NetworkDescriptor::from(&networks::MAINNET);
+        descriptor.save(&path).unwrap();
+        assert_eq!(NetworkDescriptor::load(&path).unwrap(), descriptor);
+        fs::remove_file(&path).unwrap();
+
+        match NetworkDescriptor::load(&path).unwrap_err() {
+            DescriptorError::Io(err) => assert_eq!(err.kind(), io::ErrorKind::NotFound),
+            err => panic!("Unexpected error: {}", err),
+        }
+    }
+}
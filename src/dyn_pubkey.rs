@@ -0,0 +1,171 @@
+use crate::chain_info::InvalidChainInfo;
+use crate::randomness_beacon::BeaconKey;
+use crate::scheme::Scheme;
+use crate::verify::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey, VerificationError};
+
+/// A public key of any supported scheme, selected at runtime.
+///
+/// Use this when the network is only known at runtime, e.g. from configuration. The
+/// [`Pubkey`] trait and its associated types are better suited when the scheme is known at
+/// compile time.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::DynPubkey;
+/// use hex_literal::hex;
+///
+/// let pk = DynPubkey::new(
+///     "bls-unchained-g1-rfc9380",
+///     &hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"),
+/// )
+/// .unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// assert!(pk.verify(123, &[], &signature).unwrap());
+/// ```
+pub enum DynPubkey {
+    /// A key of the schemes pedersen-bls-chained and pedersen-bls-unchained
+    G1(G1Pubkey),
+    /// A key of the scheme bls-unchained-on-g1
+    G2Fastnet(G2PubkeyFastnet),
+    /// A key of the scheme bls-unchained-g1-rfc9380
+    G2Rfc(G2PubkeyRfc),
+}
+
+impl DynPubkey {
+    /// Decodes the compressed public key for the drand scheme ID, e.g. "bls-unchained-g1-rfc9380"
+    pub fn new(scheme_id: &str, public_key: &[u8]) -> Result<Self, InvalidChainInfo> {
+        let scheme = Scheme::from_scheme_str(scheme_id).map_err(InvalidChainInfo::UnknownScheme)?;
+        scheme
+            .check_key_length(public_key.len())
+            .map_err(InvalidChainInfo::KeyLength)?;
+        let pubkey = match scheme {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                G1Pubkey::from_variable(public_key).map(DynPubkey::G1)
+            }
+            Scheme::BlsUnchainedOnG1 => {
+                G2PubkeyFastnet::from_variable(public_key).map(DynPubkey::G2Fastnet)
+            }
+            Scheme::BlsUnchainedG1Rfc9380 => {
+                G2PubkeyRfc::from_variable(public_key).map(DynPubkey::G2Rfc)
+            }
+        };
+        pubkey.map_err(|_| InvalidChainInfo::InvalidPublicKey)
+    }
+
+    /// The scheme of the beacons verified with this key, see [`Pubkey::scheme`]
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            DynPubkey::G1(pk) => pk.scheme(),
+            DynPubkey::G2Fastnet(pk) => pk.scheme(),
+            DynPubkey::G2Rfc(pk) => pk.scheme(),
+        }
+    }
+
+    /// Verifies a beacon, see [`Pubkey::verify`].
+    ///
+    /// `previous_signature` should be set to an empty slice for the unchained mode.
+    pub fn verify(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, VerificationError> {
+        match self {
+            DynPubkey::G1(pk) => pk.verify(round, previous_signature, signature),
+            DynPubkey::G2Fastnet(pk) => pk.verify(round, previous_signature, signature),
+            DynPubkey::G2Rfc(pk) => pk.verify(round, previous_signature, signature),
+        }
+    }
+}
+
+impl BeaconKey for DynPubkey {
+    fn verify_beacon(&self, round: u64, previous_signature: &[u8], signature: &[u8]) -> bool {
+        self.verify(round, previous_signature, signature)
+            .unwrap_or(false)
+    }
+}
+
+impl From<G1Pubkey> for DynPubkey {
+    fn from(pubkey: G1Pubkey) -> Self {
+        DynPubkey::G1(pubkey)
+    }
+}
+
+impl From<G2PubkeyFastnet> for DynPubkey {
+    fn from(pubkey: G2PubkeyFastnet) -> Self {
+        DynPubkey::G2Fastnet(pubkey)
+    }
+}
+
+impl From<G2PubkeyRfc> for DynPubkey {
+    fn from(pubkey: G2PubkeyRfc) -> Self {
+        DynPubkey::G2Rfc(pubkey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::{FASTNET, MAINNET, QUICKNET};
+    use crate::scheme::{KeyLengthMismatch, UnknownScheme};
+    use hex_literal::hex;
+
+    #[test]
+    fn new_works() {
+        let pk = DynPubkey::new(MAINNET.scheme_id, MAINNET.public_key).unwrap();
+        assert!(matches!(pk, DynPubkey::G1(_)));
+        assert_eq!(pk.scheme(), Scheme::PedersenBlsChained);
+        let pk = DynPubkey::new("pedersen-bls-unchained", MAINNET.public_key).unwrap();
+        assert!(matches!(pk, DynPubkey::G1(_)));
+        let pk = DynPubkey::new(FASTNET.scheme_id, FASTNET.public_key).unwrap();
+        assert_eq!(pk.scheme(), Scheme::BlsUnchainedOnG1);
+        let pk = DynPubkey::new(QUICKNET.scheme_id, QUICKNET.public_key).unwrap();
+        assert_eq!(pk.scheme(), Scheme::BlsUnchainedG1Rfc9380);
+        let pk = DynPubkey::from(G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap());
+        assert!(matches!(pk, DynPubkey::G2Rfc(_)));
+
+        assert_eq!(
+            DynPubkey::new("bls-foo", QUICKNET.public_key).err(),
+            Some(InvalidChainInfo::UnknownScheme(UnknownScheme {
+                scheme_id: "bls-foo".to_string()
+            }))
+        );
+        assert_eq!(
+            DynPubkey::new(QUICKNET.scheme_id, MAINNET.public_key).err(),
+            Some(InvalidChainInfo::KeyLength(KeyLengthMismatch {
+                scheme: Scheme::BlsUnchainedG1Rfc9380,
+                actual: 48
+            }))
+        );
+        assert_eq!(
+            DynPubkey::new(QUICKNET.scheme_id, &[0xff; 96]).err(),
+            Some(InvalidChainInfo::InvalidPublicKey)
+        );
+    }
+
+    #[test]
+    fn verify_works() {
+        // https://api.drand.sh/public/72785
+        let mainnet = DynPubkey::new(MAINNET.scheme_id, MAINNET.public_key).unwrap();
+        let previous_signature = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert!(mainnet
+            .verify(72785, &previous_signature, &signature)
+            .unwrap());
+        assert!(!mainnet
+            .verify(72786, &previous_signature, &signature)
+            .unwrap());
+
+        let quicknet = DynPubkey::new(QUICKNET.scheme_id, QUICKNET.public_key).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        assert!(quicknet.verify(123, &[], &signature).unwrap());
+        assert!(!quicknet.verify(124, &[], &signature).unwrap());
+        assert!(quicknet.verify(123, &[], &[0xff; 48]).is_err());
+
+        // as trait object
+        let key: &dyn BeaconKey = &quicknet;
+        assert!(key.verify_beacon(123, &[], &signature));
+        assert!(!key.verify_beacon(123, &[], &[0xff; 48]));
+    }
+}
@@ -0,0 +1,313 @@
+//! A small base64 decoder and the [`Encoding`]s of signatures accepted by [`parse_signature`].
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::points::InvalidPoint;
+use crate::signature::Signature;
+use crate::verify::ErrorCode;
+
+/// The encoding of a signature passed to [`parse_signature`] and [`decode_signature`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// The compressed point as raw bytes (48 bytes for G1 and 96 bytes for G2), as used by drand
+    Compressed,
+    /// The uncompressed point as raw bytes (96 bytes for G1 and 192 bytes for G2)
+    Uncompressed,
+    /// The compressed point in hex (upper or lower case), as in the drand HTTP API
+    Hex,
+    /// The compressed point in standard base64 with optional padding
+    Base64,
+}
+
+impl Encoding {
+    pub const ALL: [Encoding; 4] = [
+        Encoding::Compressed,
+        Encoding::Uncompressed,
+        Encoding::Hex,
+        Encoding::Base64,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Encoding::Compressed => "compressed",
+            Encoding::Uncompressed => "uncompressed",
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+        }
+    }
+
+    /// True for the encodings of text inputs
+    pub fn is_text(self) -> bool {
+        matches!(self, Encoding::Hex | Encoding::Base64)
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = UnknownEncoding;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|encoding| encoding.as_str() == s)
+            .ok_or_else(|| UnknownEncoding {
+                name: s.to_string(),
+            })
+    }
+}
+
+/// The error returned when parsing an unsupported encoding name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownEncoding {
+    pub name: String,
+}
+
+impl fmt::Display for UnknownEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported encoding: {}", self.name)
+    }
+}
+
+impl Error for UnknownEncoding {}
+
+#[derive(Debug)]
+pub enum ParseSignatureError {
+    /// The input is not valid in the given encoding
+    InvalidEncoding { encoding: Encoding },
+    /// The decoded input is not a valid point
+    InvalidPoint(InvalidPoint),
+}
+
+impl ParseSignatureError {
+    /// The stable numeric code of this error, see [`ErrorCode`]
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ParseSignatureError::InvalidEncoding { .. } => ErrorCode::InvalidEncoding,
+            ParseSignatureError::InvalidPoint(err) => err.code_for("signature"),
+        }
+    }
+}
+
+impl fmt::Display for ParseSignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseSignatureError::InvalidEncoding { encoding } => {
+                write!(f, "Invalid {} encoding of signature", encoding)
+            }
+            ParseSignatureError::InvalidPoint(err) => write!(f, "Invalid signature: {}", err),
+        }
+    }
+}
+
+impl Error for ParseSignatureError {}
+
+impl From<InvalidPoint> for ParseSignatureError {
+    fn from(source: InvalidPoint) -> Self {
+        ParseSignatureError::InvalidPoint(source)
+    }
+}
+
+/// Decodes and checks a signature in the given encoding.
+///
+/// `input` can be bytes or a string. Hex and base64 inputs are text encodings of the
+/// compressed point.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{networks, parse_signature, Encoding, G2PubkeyRfc, Pubkey};
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+///
+/// let hex = "b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92";
+/// let base64 = "t1xp0LcqXZBuhU6Ai6firMsVQqw1WuSG1ZGqnUN2VILibNAt+DXTVG0jxLE+DfyS";
+/// let signature = parse_signature(hex, Encoding::Hex).unwrap();
+/// assert_eq!(parse_signature(base64, Encoding::Base64).unwrap(), signature);
+/// assert!(pk.verify_decompressed(123, &[], &signature).unwrap());
+/// ```
+pub fn parse_signature(
+    input: impl AsRef<[u8]>,
+    encoding: Encoding,
+) -> Result<Signature, ParseSignatureError> {
+    let input = input.as_ref();
+    match encoding {
+        Encoding::Uncompressed => Ok(Signature::from_uncompressed(input)?),
+        _ => Ok(Signature::from_variable(&decode_signature(
+            input, encoding,
+        )?)?),
+    }
+}
+
+/// Decodes a signature in the given encoding to the compressed format used by
+/// [`Pubkey::verify`](crate::Pubkey::verify) and [`derive_randomness`](crate::derive_randomness).
+///
+/// Unlike [`parse_signature`], the result is not checked to be a valid point except for
+/// uncompressed inputs, which need to be decoded to be compressed. This makes it suitable for
+/// previous signatures, which are only hashed.
+pub fn decode_signature(
+    input: impl AsRef<[u8]>,
+    encoding: Encoding,
+) -> Result<Vec<u8>, ParseSignatureError> {
+    let input = input.as_ref();
+    let invalid = ParseSignatureError::InvalidEncoding { encoding };
+    match encoding {
+        Encoding::Compressed => Ok(input.to_vec()),
+        Encoding::Uncompressed => Ok(Signature::from_uncompressed(input)?.to_bytes()),
+        Encoding::Hex => std::str::from_utf8(input)
+            .ok()
+            .and_then(|input| hex::decode(input).ok())
+            .ok_or(invalid),
+        Encoding::Base64 => std::str::from_utf8(input)
+            .ok()
+            .and_then(from_base64)
+            .ok_or(invalid),
+    }
+}
+
+/// Decodes standard base64 (`+` and `/`) with optional padding. Returns `None` for invalid
+/// input, including non-zero trailing bits, so every value has only one accepted encoding.
+pub(crate) fn from_base64(input: &str) -> Option<Vec<u8>> {
+    let (data, padding) = match input.strip_suffix("==") {
+        Some(data) => (data, 2),
+        None => match input.strip_suffix('=') {
+            Some(data) => (data, 1),
+            None => (input, 0),
+        },
+    };
+    if padding != 0 && (input.len() % 4 != 0 || data.len() % 4 != 4 - padding) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    // 6 remaining bits cannot encode a byte
+    if bits >= 6 || buffer != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_works() {
+        assert_eq!(from_base64(""), Some(vec![]));
+        assert_eq!(from_base64("AKv/"), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(from_base64("AKs="), Some(vec![0x00, 0xab]));
+        assert_eq!(from_base64("AKs"), Some(vec![0x00, 0xab]));
+        assert_eq!(from_base64("AA=="), Some(vec![0x00]));
+        assert_eq!(from_base64("AA"), Some(vec![0x00]));
+        // invalid padding
+        assert_eq!(from_base64("AA="), None);
+        assert_eq!(from_base64("AKs=="), None);
+        assert_eq!(from_base64("="), None);
+        // non-zero trailing bits
+        assert_eq!(from_base64("AB=="), None);
+        // too short
+        assert_eq!(from_base64("A"), None);
+        // URL safe alphabet and whitespace
+        assert_eq!(from_base64("AKv_"), None);
+        assert_eq!(from_base64("AKv/ "), None);
+    }
+
+    #[test]
+    fn encoding_from_str_works() {
+        for encoding in Encoding::ALL {
+            assert_eq!(encoding.as_str().parse::<Encoding>(), Ok(encoding));
+        }
+        assert_eq!(
+            "HEX".parse::<Encoding>(),
+            Err(UnknownEncoding {
+                name: "HEX".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn parse_signature_works() {
+        use bls12_381::{G1Affine, G2Affine};
+        use hex_literal::hex;
+
+        let g1 = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let expected = Signature::from_variable(&g1).unwrap();
+        assert_eq!(parse_signature(g1, Encoding::Compressed).unwrap(), expected);
+        assert_eq!(
+            parse_signature(hex::encode(g1).to_uppercase(), Encoding::Hex).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_signature(
+                "t1xp0LcqXZBuhU6Ai6firMsVQqw1WuSG1ZGqnUN2VILibNAt+DXTVG0jxLE+DfyS",
+                Encoding::Base64
+            )
+            .unwrap(),
+            expected
+        );
+        let uncompressed = G1Affine::from_compressed(&g1).unwrap().to_uncompressed();
+        assert_eq!(
+            parse_signature(uncompressed, Encoding::Uncompressed).unwrap(),
+            expected
+        );
+        assert_eq!(
+            decode_signature(uncompressed, Encoding::Uncompressed).unwrap(),
+            g1
+        );
+
+        let g2 = G2Affine::generator();
+        assert_eq!(
+            parse_signature(g2.to_uncompressed(), Encoding::Uncompressed)
+                .unwrap()
+                .to_bytes(),
+            g2.to_compressed()
+        );
+
+        // errors
+        let err = parse_signature("b75c=", Encoding::Base64).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidEncoding);
+        let err = parse_signature("zz", Encoding::Hex).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidEncoding);
+        let err = parse_signature("b75c", Encoding::Hex).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignatureLength);
+        // compressed G2 signatures have the length of uncompressed G1 signatures
+        let err = parse_signature(g2.to_compressed(), Encoding::Uncompressed).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignature);
+        let err = parse_signature([0u8; 48], Encoding::Compressed).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignature);
+
+        // previous signatures are not checked
+        assert_eq!(
+            decode_signature("", Encoding::Hex).unwrap(),
+            Vec::<u8>::new()
+        );
+        assert_eq!(
+            decode_signature("00ff", Encoding::Hex).unwrap(),
+            [0x00, 0xff]
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! A language independent representation of errors.
+//!
+//! [`ErrorReport`] describes an error as `{ code, kind, field, message }`. The JS bindings set
+//! these as properties of thrown errors and the example CLI prints them with `--output json`,
+//! so tooling in other languages can rely on one error format.
+
+use std::fmt::Write;
+
+use crate::batch::BatchTooLarge;
+use crate::encoding::ParseSignatureError;
+use crate::points::InvalidPoint;
+use crate::verify::{ErrorCode, VerificationError};
+
+/// An error as `{ code, kind, field, message }`.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::error_json::ErrorReport;
+/// use drand_verify::{G2PubkeyRfc, Pubkey};
+///
+/// let err = G2PubkeyRfc::from_variable(&[0; 96]).err().unwrap();
+/// let report = ErrorReport::from(&err).with_field("public_key");
+/// assert_eq!(
+///     report.to_json(),
+///     r#"{"code":4,"kind":"invalid_point","field":"public_key","message":"Invalid point"}"#
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ErrorReport {
+    /// The numeric [`ErrorCode`]
+    pub code: u8,
+    /// The [`ErrorCode::kind`]
+    pub kind: &'static str,
+    /// The input that caused the error, if known
+    pub field: Option<String>,
+    /// A human readable description. The wording may change between versions.
+    pub message: String,
+}
+
+impl ErrorReport {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: code.code(),
+            kind: code.kind(),
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn with_field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Serializes the report as a JSON object with the keys in the order
+    /// `code`, `kind`, `field`, `message`. A missing field is `null`.
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"code\":{},\"kind\":", self.code);
+        write_json_string(&mut out, self.kind);
+        out.push_str(",\"field\":");
+        match &self.field {
+            Some(field) => write_json_string(&mut out, field),
+            None => out.push_str("null"),
+        }
+        out.push_str(",\"message\":");
+        write_json_string(&mut out, &self.message);
+        out.push('}');
+        out
+    }
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                // Writing to a String cannot fail
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+impl From<&VerificationError> for ErrorReport {
+    fn from(source: &VerificationError) -> Self {
+        match source {
+            VerificationError::InvalidPoint { field, .. } => {
+                Self::new(source.code(), source.to_string()).with_field(field.as_str())
+            }
+        }
+    }
+}
+
+impl From<&InvalidPoint> for ErrorReport {
+    fn from(source: &InvalidPoint) -> Self {
+        Self::new(source.code(), source.to_string())
+    }
+}
+
+impl From<&BatchTooLarge> for ErrorReport {
+    fn from(source: &BatchTooLarge) -> Self {
+        Self::new(source.code(), source.to_string())
+    }
+}
+
+impl From<&ParseSignatureError> for ErrorReport {
+    fn from(source: &ParseSignatureError) -> Self {
+        Self::new(source.code(), source.to_string()).with_field("signature")
+    }
+}
+
+impl From<ErrorCode> for ErrorReport {
+    fn from(source: ErrorCode) -> Self {
+        Self::new(source, source.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_errors_works() {
+        let err = VerificationError::invalid_point("signature", InvalidPoint::DecodingError {});
+        assert_eq!(
+            ErrorReport::from(&err),
+            ErrorReport {
+                code: 2,
+                kind: "invalid_signature",
+                field: Some("signature".to_string()),
+                message: "Invalid point for field signature: Invalid point".to_string(),
+            }
+        );
+
+        let err = InvalidPoint::InvalidLength {
+            expected: 48,
+            actual: 3,
+        };
+        let report = ErrorReport::from(&err);
+        assert_eq!((report.code, report.kind), (3, "invalid_point_length"));
+        assert_eq!(report.field, None);
+
+        let report = ErrorReport::from(&BatchTooLarge { max: 1, actual: 2 });
+        assert_eq!((report.code, report.kind), (6, "batch_too_large"));
+
+        let report = ErrorReport::from(ErrorCode::InvalidEncoding);
+        assert_eq!(report.message, "Invalid encoding");
+    }
+
+    #[test]
+    fn to_json_works() {
+        let report = ErrorReport::new(ErrorCode::InvalidEncoding, "Invalid \"hex\"\n\\ \u{1}");
+        assert_eq!(
+            report.to_json(),
+            r#"{"code":5,"kind":"invalid_encoding","field":null,"message":"Invalid \"hex\"\n\\ \u0001"}"#
+        );
+        let report = report.with_field("round");
+        assert!(report.to_json().contains(r#""field":"round""#));
+    }
+}
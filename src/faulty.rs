@@ -0,0 +1,260 @@
+//! Failure injection for testing clients against misbehaving relays (feature `fixtures`).
+//!
+//! [`FaultySource`] wraps any [`BeaconSource`] and injects the faults seen from real relays:
+//! slow responses, beacons of the wrong round, corrupted signatures and stale data.
+//! Faults are chosen pseudo-randomly from a seed, so failing tests can be reproduced.
+
+use std::thread;
+use std::time::Duration;
+
+use crate::audit::random_u64;
+use crate::beacon::Beacon;
+use crate::source::BeaconSource;
+
+/// A fault injected by [`FaultySource`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The response was delayed
+    Delay,
+    /// The beacon of a neighbouring round was returned instead
+    WrongRound,
+    /// A single bit of the signature was flipped
+    FlippedSignatureBit,
+    /// The previously returned beacon was returned again
+    Stale,
+}
+
+/// A [`BeaconSource`] decorator that injects faults with configurable probabilities.
+///
+/// Each probability is checked independently per request. At most one of stale data and
+/// wrong round is injected per request. Stale data is only injected once a beacon
+/// was loaded before.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::faulty::{Fault, FaultySource};
+/// use drand_verify::{Beacon, BeaconSource};
+///
+/// struct Relay;
+///
+/// impl BeaconSource for Relay {
+///     type Error = String;
+///
+///     fn beacon(&mut self, round: u64) -> Result<Beacon, String> {
+///         Ok(Beacon { round, previous_signature: vec![], signature: vec![0; 48] })
+///     }
+/// }
+///
+/// let mut source = FaultySource::new(Relay, b"test seed").flip_signature_bit(1.0);
+/// let beacon = source.beacon(5).unwrap();
+/// assert_ne!(beacon.signature, vec![0; 48]);
+/// assert_eq!(source.last_faults(), [Fault::FlippedSignatureBit]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FaultySource<S> {
+    inner: S,
+    seed: Vec<u8>,
+    counter: u64,
+    delay_probability: f64,
+    delay: Duration,
+    wrong_round_probability: f64,
+    flip_probability: f64,
+    stale_probability: f64,
+    last_beacon: Option<Beacon>,
+    last_faults: Vec<Fault>,
+}
+
+impl<S: BeaconSource> FaultySource<S> {
+    /// Wraps the source without injecting any faults yet
+    pub fn new(inner: S, seed: &[u8]) -> Self {
+        Self {
+            inner,
+            seed: seed.to_vec(),
+            counter: 0,
+            delay_probability: 0.0,
+            delay: Duration::ZERO,
+            wrong_round_probability: 0.0,
+            flip_probability: 0.0,
+            stale_probability: 0.0,
+            last_beacon: None,
+            last_faults: Vec::new(),
+        }
+    }
+
+    /// Blocks the calling thread for `delay` before responding
+    pub fn delay(mut self, probability: f64, delay: Duration) -> Self {
+        self.delay_probability = probability;
+        self.delay = delay;
+        self
+    }
+
+    /// Returns the beacon of the previous round (or the next round for round 1)
+    pub fn wrong_round(mut self, probability: f64) -> Self {
+        self.wrong_round_probability = probability;
+        self
+    }
+
+    /// Flips a random bit of the signature
+    pub fn flip_signature_bit(mut self, probability: f64) -> Self {
+        self.flip_probability = probability;
+        self
+    }
+
+    /// Returns the previously loaded beacon again
+    pub fn stale(mut self, probability: f64) -> Self {
+        self.stale_probability = probability;
+        self
+    }
+
+    /// The faults injected into the last response
+    pub fn last_faults(&self) -> &[Fault] {
+        &self.last_faults
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    fn next_random(&mut self) -> u64 {
+        let value = random_u64(&self.seed, self.counter);
+        self.counter += 1;
+        value
+    }
+
+    fn roll(&mut self, probability: f64) -> bool {
+        // 53 random bits give a uniform value in [0, 1)
+        let value = (self.next_random() >> 11) as f64 / (1u64 << 53) as f64;
+        value < probability
+    }
+}
+
+impl<S: BeaconSource> BeaconSource for FaultySource<S> {
+    type Error = S::Error;
+
+    fn beacon(&mut self, round: u64) -> Result<Beacon, Self::Error> {
+        self.last_faults.clear();
+        if self.roll(self.delay_probability) {
+            thread::sleep(self.delay);
+            self.last_faults.push(Fault::Delay);
+        }
+
+        let stale = self.roll(self.stale_probability);
+        let mut beacon = match self.last_beacon.clone() {
+            Some(last) if stale => {
+                self.last_faults.push(Fault::Stale);
+                last
+            }
+            _ => {
+                let requested = if self.roll(self.wrong_round_probability) {
+                    self.last_faults.push(Fault::WrongRound);
+                    if round > 1 {
+                        round - 1
+                    } else {
+                        round + 1
+                    }
+                } else {
+                    round
+                };
+                let beacon = self.inner.beacon(requested)?;
+                self.last_beacon = Some(beacon.clone());
+                beacon
+            }
+        };
+
+        if self.roll(self.flip_probability) && !beacon.signature.is_empty() {
+            let bit = self.next_random() % (beacon.signature.len() as u64 * 8);
+            beacon.signature[(bit / 8) as usize] ^= 1 << (bit % 8);
+            self.last_faults.push(Fault::FlippedSignatureBit);
+        }
+        Ok(beacon)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    struct CountingSource;
+
+    impl BeaconSource for CountingSource {
+        type Error = String;
+
+        fn beacon(&mut self, round: u64) -> Result<Beacon, Self::Error> {
+            if round == 404 {
+                return Err("Not found".to_string());
+            }
+            Ok(Beacon {
+                round,
+                previous_signature: vec![],
+                signature: vec![round as u8; 48],
+            })
+        }
+    }
+
+    #[test]
+    fn passes_through_without_faults() {
+        let mut source = FaultySource::new(CountingSource, b"seed");
+        for round in 1..100 {
+            assert_eq!(
+                source.beacon(round).unwrap(),
+                CountingSource.beacon(round).unwrap()
+            );
+            assert_eq!(source.last_faults(), []);
+        }
+        assert_eq!(source.beacon(404), Err("Not found".to_string()));
+    }
+
+    #[test]
+    fn injects_faults() {
+        let mut source = FaultySource::new(CountingSource, b"seed").wrong_round(1.0);
+        assert_eq!(source.beacon(1).unwrap().round, 2);
+        assert_eq!(source.beacon(10).unwrap().round, 9);
+        assert_eq!(source.last_faults(), [Fault::WrongRound]);
+
+        let mut source = FaultySource::new(CountingSource, b"seed").stale(1.0);
+        assert_eq!(source.beacon(10).unwrap().round, 10);
+        assert_eq!(source.last_faults(), []);
+        assert_eq!(source.beacon(11).unwrap().round, 10);
+        assert_eq!(source.last_faults(), [Fault::Stale]);
+
+        let mut source = FaultySource::new(CountingSource, b"seed").flip_signature_bit(1.0);
+        let beacon = source.beacon(10).unwrap();
+        let flipped_bits: u32 = beacon
+            .signature
+            .iter()
+            .map(|byte| (byte ^ 10).count_ones())
+            .sum();
+        assert_eq!(flipped_bits, 1);
+
+        let delay = Duration::from_millis(5);
+        let mut source = FaultySource::new(CountingSource, b"seed").delay(1.0, delay);
+        let start = Instant::now();
+        source.beacon(10).unwrap();
+        assert!(start.elapsed() >= delay);
+        assert_eq!(source.last_faults(), [Fault::Delay]);
+    }
+
+    #[test]
+    fn faults_are_reproducible() {
+        let run = |seed: &[u8]| {
+            let mut source = FaultySource::new(CountingSource, seed)
+                .wrong_round(0.2)
+                .stale(0.2)
+                .flip_signature_bit(0.2);
+            (1..200)
+                .map(|round| {
+                    source.beacon(round).unwrap();
+                    source.last_faults().to_vec()
+                })
+                .collect::<Vec<_>>()
+        };
+        let faults = run(b"seed");
+        assert_eq!(faults, run(b"seed"));
+        assert_ne!(faults, run(b"other seed"));
+
+        let faulty = faults.iter().filter(|faults| !faults.is_empty()).count();
+        assert!((50..150).contains(&faulty), "{} faulty responses", faulty);
+    }
+}
@@ -0,0 +1,158 @@
+//! Known-good beacons, keys and chain infos for tests (feature `fixtures`).
+//!
+//! The beacons were fetched from public relays (see the comments for the URLs) and are
+//! checked by this crate's test suite. Use them to test code built on this crate without
+//! depending on network access.
+//!
+//! ## Examples
+//!
+//! ```
+//! use drand_verify::fixtures;
+//! use drand_verify::{G2PubkeyRfc, Pubkey};
+//!
+//! let fixture = fixtures::QUICKNET_123;
+//! let pk = G2PubkeyRfc::from_variable(fixture.public_key).unwrap();
+//! let beacon = fixture.beacon();
+//! assert!(pk.verify(beacon.round, &beacon.previous_signature, &beacon.signature).unwrap());
+//!
+//! let info = fixtures::chain_info(fixture.network.unwrap());
+//! assert_eq!(info.scheme(), Ok(fixture.scheme));
+//! ```
+
+use hex_literal::hex;
+
+use crate::beacon::Beacon;
+use crate::chain_info::ChainInfo;
+use crate::networks::{Network, FASTNET, KNOWN_NETWORKS, MAINNET, QUICKNET};
+use crate::scheme::Scheme;
+
+/// A beacon that is valid for the given public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BeaconFixture {
+    /// The network of the beacon if it is known to this crate
+    pub network: Option<&'static Network>,
+    pub scheme: Scheme,
+    pub public_key: &'static [u8],
+    pub round: u64,
+    /// Empty for unchained schemes
+    pub previous_signature: &'static [u8],
+    pub signature: &'static [u8],
+}
+
+impl BeaconFixture {
+    pub fn beacon(&self) -> Beacon {
+        Beacon {
+            round: self.round,
+            previous_signature: self.previous_signature.to_vec(),
+            signature: self.signature.to_vec(),
+        }
+    }
+}
+
+/// Public key of the unchained League of Entropy testnet (pedersen-bls-unchained).
+///
+/// See <https://pl-us.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf/info>
+pub const UNCHAINED_TESTNET_PUBLIC_KEY: &[u8] = &hex!("8200fc249deb0148eb918d6e213980c5d01acd7fc251900d9260136da3b54836ce125172399ddc69c4e3e11429b62c11");
+
+/// <https://api.drand.sh/public/1337>
+pub const MAINNET_1337: BeaconFixture = BeaconFixture {
+    network: Some(&MAINNET),
+    scheme: Scheme::PedersenBlsChained,
+    public_key: MAINNET.public_key,
+    round: 1337,
+    previous_signature: &hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2"),
+    signature: &hex!("945b08dcb30e24da281ccf14a646f0630ceec515af5c5895e18cc1b19edd65d156b71c776a369af3487f1bc6af1062500b059e01095cc0eedce91713977d7735cac675554edfa0d0481bb991ed93d333d08286192c05bf6b65d20f23a37fc7bb"),
+};
+
+/// <https://api.drand.sh/public/72785>
+pub const MAINNET_72785: BeaconFixture = BeaconFixture {
+    network: Some(&MAINNET),
+    scheme: Scheme::PedersenBlsChained,
+    public_key: MAINNET.public_key,
+    round: 72785,
+    previous_signature: &hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747"),
+    signature: &hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42"),
+};
+
+/// <https://pl-us.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf/public/223344>
+pub const UNCHAINED_TESTNET_223344: BeaconFixture = BeaconFixture {
+    network: None,
+    scheme: Scheme::PedersenBlsUnchained,
+    public_key: UNCHAINED_TESTNET_PUBLIC_KEY,
+    round: 223344,
+    previous_signature: &[],
+    signature: &hex!("94f6b85df7cce7237e8e7df66d794ddad092de5d8bb6a791b97e905aa89852e506ac36a792eba7021e22eebf34891f8914bf9a8dd9233ea0a4c5ca00ef8404999f899073dd2eade61fe54077fee8168f83dcb61a758b6883b38904054e64a433"),
+};
+
+/// <https://api3.drand.sh/dbd506d6ef76e5f386f41c651dcb808c5bcbd75471cc4eafa3f4df7ad4e4c493/public/23456>
+pub const FASTNET_23456: BeaconFixture = BeaconFixture {
+    network: Some(&FASTNET),
+    scheme: Scheme::BlsUnchainedOnG1,
+    public_key: FASTNET.public_key,
+    round: 23456,
+    previous_signature: &[],
+    signature: &hex!("98401ef9833e75bf06fda3243e4fcf6d075d62b45c2a59d26df5d5fcbdfd0c14ee89fc035abd5528a8c25b68fbecae65"),
+};
+
+/// <https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123>
+pub const QUICKNET_123: BeaconFixture = BeaconFixture {
+    network: Some(&QUICKNET),
+    scheme: Scheme::BlsUnchainedG1Rfc9380,
+    public_key: QUICKNET.public_key,
+    round: 123,
+    previous_signature: &[],
+    signature: &hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"),
+};
+
+/// All beacon fixtures, covering every supported scheme
+pub const ALL_BEACONS: &[BeaconFixture] = &[
+    MAINNET_1337,
+    MAINNET_72785,
+    UNCHAINED_TESTNET_223344,
+    FASTNET_23456,
+    QUICKNET_123,
+];
+
+/// The chain info of a network known to this crate
+pub fn chain_info(network: &Network) -> ChainInfo {
+    ChainInfo::from(network)
+}
+
+/// The chain infos of all networks known to this crate
+pub fn chain_infos() -> Vec<ChainInfo> {
+    KNOWN_NETWORKS.iter().map(ChainInfo::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+    fn verify<P: Pubkey>(fixture: &BeaconFixture) -> bool {
+        let pk = P::from_variable(fixture.public_key).unwrap();
+        pk.verify(fixture.round, fixture.previous_signature, fixture.signature)
+            .unwrap()
+    }
+
+    #[test]
+    fn all_beacons_are_valid() {
+        for fixture in ALL_BEACONS {
+            let valid = match fixture.scheme {
+                Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                    verify::<G1Pubkey>(fixture)
+                }
+                Scheme::BlsUnchainedOnG1 => verify::<G2PubkeyFastnet>(fixture),
+                Scheme::BlsUnchainedG1Rfc9380 => verify::<G2PubkeyRfc>(fixture),
+            };
+            assert!(valid, "round {} is invalid", fixture.round);
+            assert_eq!(
+                fixture.previous_signature.is_empty(),
+                !fixture.scheme.is_chained()
+            );
+            if let Some(network) = fixture.network {
+                assert_eq!(chain_info(network).scheme(), Ok(fixture.scheme));
+            }
+        }
+        assert_eq!(chain_infos().len(), KNOWN_NETWORKS.len());
+    }
+}
@@ -0,0 +1,255 @@
+//! A compact binary encoding of single beacons for constrained transports.
+//!
+//! IoT gateways pushing beacons over MQTT, serial lines or radio links cannot afford JSON.
+//! A frame is the round as 8 bytes big endian followed by the previous signature (chained
+//! networks only) and the signature, i.e. the same layout as a record of the
+//! [archive](crate::archive) format. The kind of frame follows from its length:
+//!
+//! | Length | Content                                                   |
+//! | ------ | --------------------------------------------------------- |
+//! | 56     | round + G1 signature (e.g. quicknet)                      |
+//! | 104    | round + G2 signature (e.g. pedersen-bls-unchained)        |
+//! | 200    | round + previous G2 signature + G2 signature (mainnet)    |
+//!
+//! Message based transports like MQTT carry one frame per message, use [`Frame::parse`].
+//! For byte streams like serial lines, prefix every frame with its length as a single byte
+//! and use [`FrameDecoder`].
+
+use std::error::Error;
+use std::fmt;
+
+use crate::beacon::Beacon;
+use crate::outcome::VerificationOutcome;
+use crate::verify::Pubkey;
+
+const ROUND_SIZE: usize = 8;
+
+/// A beacon decoded from a frame without copying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+    pub round: u64,
+    /// Empty for unchained networks
+    pub previous_signature: &'a [u8],
+    pub signature: &'a [u8],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The length does not match any kind of frame
+    InvalidLength { actual: usize },
+}
+
+impl fmt::Display for FrameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FrameError::InvalidLength { actual } => write!(
+                f,
+                "Invalid frame length {}, expected 56, 104 or 200 bytes",
+                actual
+            ),
+        }
+    }
+}
+
+impl Error for FrameError {}
+
+impl<'a> Frame<'a> {
+    /// Decodes a frame. The signatures are not checked until [`Frame::verify`].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::frame::Frame;
+    /// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+    /// use hex_literal::hex;
+    ///
+    /// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+    ///
+    /// // The payload of an MQTT message
+    /// let payload = hex!("000000000000007bb75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    ///
+    /// let beacon = Frame::parse(&payload).unwrap().verify(&pk).into_verified().unwrap();
+    /// assert_eq!(beacon.round(), 123);
+    /// ```
+    pub fn parse(data: &'a [u8]) -> Result<Self, FrameError> {
+        let (previous_size, signature_size) = match data.len() {
+            56 => (0, 48),
+            104 => (0, 96),
+            200 => (96, 96),
+            actual => return Err(FrameError::InvalidLength { actual }),
+        };
+        let (round, rest) = data.split_at(ROUND_SIZE);
+        let (previous_signature, signature) = rest.split_at(previous_size);
+        debug_assert_eq!(signature.len(), signature_size);
+        Ok(Self {
+            round: u64::from_be_bytes(round.try_into().unwrap()),
+            previous_signature,
+            signature,
+        })
+    }
+
+    /// Verifies the beacon of this frame
+    pub fn verify<P: Pubkey>(&self, pubkey: &P) -> VerificationOutcome {
+        pubkey.verify_with_outcome(self.round, self.previous_signature, self.signature)
+    }
+
+    /// Encodes the frame
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut out =
+            Vec::with_capacity(ROUND_SIZE + self.previous_signature.len() + self.signature.len());
+        out.extend_from_slice(&self.round.to_be_bytes());
+        out.extend_from_slice(self.previous_signature);
+        out.extend_from_slice(self.signature);
+        out
+    }
+
+    pub fn to_beacon(&self) -> Beacon {
+        Beacon {
+            round: self.round,
+            previous_signature: self.previous_signature.to_vec(),
+            signature: self.signature.to_vec(),
+        }
+    }
+}
+
+impl<'a> From<&'a Beacon> for Frame<'a> {
+    fn from(beacon: &'a Beacon) -> Self {
+        Self {
+            round: beacon.round,
+            previous_signature: &beacon.previous_signature,
+            signature: &beacon.signature,
+        }
+    }
+}
+
+/// Splits a byte stream into frames, each prefixed by its length as a single byte.
+///
+/// Bytes can be pushed in chunks of any size as they arrive from the transport.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::frame::{Frame, FrameDecoder};
+/// use drand_verify::Beacon;
+/// use hex_literal::hex;
+///
+/// let beacon = Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// };
+/// let frame = Frame::from(&beacon).to_vec();
+/// let mut stream = vec![frame.len() as u8];
+/// stream.extend_from_slice(&frame);
+///
+/// let mut decoder = FrameDecoder::new();
+/// decoder.push(&stream[..10]);
+/// assert_eq!(decoder.next_beacon(), None);
+/// decoder.push(&stream[10..]);
+/// assert_eq!(decoder.next_beacon(), Some(Ok(beacon)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends bytes received from the transport
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next complete frame, or `None` if more bytes are needed.
+    ///
+    /// A frame with an invalid length is skipped and reported as an error, so decoding
+    /// continues with the following frame.
+    pub fn next_beacon(&mut self) -> Option<Result<Beacon, FrameError>> {
+        let (&length, rest) = self.buffer.split_first()?;
+        let length = usize::from(length);
+        if rest.len() < length {
+            return None;
+        }
+        let result = Frame::parse(&rest[..length]).map(|frame| frame.to_beacon());
+        self.buffer.drain(..1 + length);
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G1Pubkey, InvalidReason};
+    use hex_literal::hex;
+
+    fn mainnet_beacon() -> Beacon {
+        Beacon {
+            round: 72785,
+            previous_signature: hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").to_vec(),
+            signature: hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").to_vec(),
+        }
+    }
+
+    #[test]
+    fn parse_works_for_chained_frames() {
+        let pk = G1Pubkey::from_fixed(hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31")).unwrap();
+        let beacon = mainnet_beacon();
+        let data = Frame::from(&beacon).to_vec();
+        assert_eq!(data.len(), 200);
+
+        let frame = Frame::parse(&data).unwrap();
+        assert_eq!(frame.to_beacon(), beacon);
+        assert!(frame.verify(&pk).is_valid());
+
+        let mut data = data;
+        data[7] ^= 1;
+        assert_eq!(
+            Frame::parse(&data).unwrap().verify(&pk),
+            VerificationOutcome::Invalid(InvalidReason::SignatureMismatch)
+        );
+    }
+
+    #[test]
+    fn parse_fails_for_invalid_length() {
+        assert_eq!(
+            Frame::parse(&[0u8; 8]),
+            Err(FrameError::InvalidLength { actual: 8 })
+        );
+        assert_eq!(
+            Frame::parse(&[0u8; 152]),
+            Err(FrameError::InvalidLength { actual: 152 })
+        );
+    }
+
+    #[test]
+    fn decoder_works() {
+        let beacon = mainnet_beacon();
+        let frame = Frame::from(&beacon).to_vec();
+        let mut stream = Vec::new();
+        for payload in [&frame[..], &[1, 2, 3], &frame[..]] {
+            stream.push(payload.len() as u8);
+            stream.extend_from_slice(payload);
+        }
+
+        let mut decoder = FrameDecoder::new();
+        assert_eq!(decoder.next_beacon(), None);
+        let mut decoded = Vec::new();
+        for byte in stream {
+            decoder.push(&[byte]);
+            while let Some(result) = decoder.next_beacon() {
+                decoded.push(result);
+            }
+        }
+        assert_eq!(
+            decoded,
+            [
+                Ok(beacon.clone()),
+                Err(FrameError::InvalidLength { actual: 3 }),
+                Ok(beacon)
+            ]
+        );
+    }
+}
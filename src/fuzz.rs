@@ -0,0 +1,181 @@
+//! Entry points for coverage guided fuzzing.
+//!
+//! The functions take arbitrary bytes, run them through the parsing and verification code
+//! and check that the different APIs agree with each other. They never panic for any input
+//! unless this crate has a bug, so they can be called directly from a `cargo fuzz` target:
+//!
+//! ```ignore
+//! #![no_main]
+//! use libfuzzer_sys::fuzz_target;
+//!
+//! fuzz_target!(|data: &[u8]| drand_verify::fuzz::fuzz_verify(data));
+//! ```
+//!
+//! All functions are deterministic.
+
+use crate::frame::Frame;
+use crate::scheme::Scheme;
+use crate::signature::Signature;
+use crate::verify::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// Decodes `bytes` as public keys, signatures and frames.
+///
+/// Whatever decodes successfully must encode back to the same bytes.
+pub fn fuzz_point_decode(bytes: &[u8]) {
+    crate::classify::classify(bytes);
+    G1Pubkey::from_variable(bytes).ok();
+    G2PubkeyFastnet::from_variable(bytes).ok();
+    G2PubkeyRfc::from_variable(bytes).ok();
+    if let Ok(signature) = Signature::from_variable(bytes) {
+        assert_eq!(signature.to_bytes(), bytes, "Signature does not roundtrip");
+    }
+    if let Ok(frame) = Frame::parse(bytes) {
+        assert_eq!(frame.to_vec(), bytes, "Frame does not roundtrip");
+    }
+}
+
+/// Verifies a beacon built from `bytes` with all verification APIs and checks that they
+/// come to the same result.
+///
+/// The input is read as
+///
+/// ```text
+/// scheme selector (1) || round (8, big endian) || public key (48 or 96, depending on the scheme)
+///   || previous signature length (1) || previous signature || signature (rest)
+/// ```
+///
+/// Inputs that are too short are ignored.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::fuzz::fuzz_verify;
+///
+/// fuzz_verify(b"");
+/// fuzz_verify(&[0xff; 300]);
+/// ```
+pub fn fuzz_verify(bytes: &[u8]) {
+    let (&selector, rest) = match bytes.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let scheme = Scheme::ALL[usize::from(selector) % Scheme::ALL.len()];
+    if rest.len() < 8 {
+        return;
+    }
+    let (round, rest) = rest.split_at(8);
+    let round = u64::from_be_bytes(round.try_into().unwrap());
+    let key_size = scheme.key_group().compressed_size();
+    if rest.len() < key_size + 1 {
+        return;
+    }
+    let (public_key, rest) = rest.split_at(key_size);
+    let (&previous_size, rest) = rest.split_first().unwrap();
+    let previous_size = usize::from(previous_size);
+    if rest.len() < previous_size {
+        return;
+    }
+    let (previous_signature, signature) = rest.split_at(previous_size);
+
+    match scheme {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+            check_verify::<G1Pubkey>(public_key, round, previous_signature, signature)
+        }
+        Scheme::BlsUnchainedOnG1 => {
+            check_verify::<G2PubkeyFastnet>(public_key, round, previous_signature, signature)
+        }
+        Scheme::BlsUnchainedG1Rfc9380 => {
+            check_verify::<G2PubkeyRfc>(public_key, round, previous_signature, signature)
+        }
+    }
+}
+
+fn check_verify<P: Pubkey>(
+    public_key: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) {
+    let pk = match P::from_variable(public_key) {
+        Ok(pk) => pk,
+        Err(_) => return,
+    };
+    let result = pk.verify(round, previous_signature, signature).ok();
+    let no_alloc = pk
+        .verify_no_alloc(round, previous_signature, signature)
+        .ok();
+    assert_eq!(result, no_alloc, "verify and verify_no_alloc disagree");
+
+    let outcome = pk.verify_with_outcome(round, previous_signature, signature);
+    assert_eq!(
+        outcome.is_valid(),
+        result == Some(true),
+        "verify and verify_with_outcome disagree"
+    );
+
+    if let Ok(decompressed) = Signature::from_variable(signature) {
+        let decompressed = pk
+            .verify_decompressed(round, previous_signature, &decompressed)
+            .ok();
+        assert_eq!(
+            result, decompressed,
+            "verify and verify_decompressed disagree"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::QUICKNET;
+    use crate::randomness::derive_randomness;
+    use hex_literal::hex;
+
+    fn quicknet_input() -> Vec<u8> {
+        let mut input = vec![3];
+        input.extend_from_slice(&123u64.to_be_bytes());
+        input.extend_from_slice(QUICKNET.public_key);
+        input.push(0);
+        input.extend_from_slice(&hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92"));
+        input
+    }
+
+    #[test]
+    fn fuzz_verify_works_for_valid_input() {
+        let input = quicknet_input();
+        assert_eq!(Scheme::ALL[3], Scheme::BlsUnchainedG1Rfc9380);
+        fuzz_verify(&input);
+
+        // every prefix and a bit flip in every byte
+        for len in 0..input.len() {
+            fuzz_verify(&input[..len]);
+        }
+        for index in 0..input.len() {
+            let mut input = input.clone();
+            input[index] ^= 1 << (index % 8);
+            fuzz_verify(&input);
+        }
+    }
+
+    #[test]
+    fn fuzz_point_decode_does_not_panic() {
+        let input = quicknet_input();
+        fuzz_point_decode(&input[10..106]);
+        fuzz_point_decode(&input[107..]);
+        fuzz_point_decode(&input[1..]);
+
+        // pseudo random inputs of all relevant lengths
+        for len in 0..=200 {
+            let mut data = Vec::new();
+            while data.len() < len {
+                data.extend_from_slice(&derive_randomness(&[len as u8, data.len() as u8]));
+            }
+            data.truncate(len);
+            fuzz_point_decode(&data);
+            if let Some(byte) = data.first_mut() {
+                *byte |= 0b1000_0000;
+                fuzz_point_decode(&data);
+            }
+        }
+    }
+}
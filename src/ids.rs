@@ -0,0 +1,152 @@
+//! Identifiers derived from verified beacons.
+//!
+//! All identifiers are derived from the round, the randomness and a caller provided context
+//! (e.g. `b"my-app/lottery-ticket"`) using SHA-256 with a domain separation tag. Different
+//! contexts lead to independent identifiers for the same beacon.
+
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::beacon::VerifiedBeacon;
+
+const UUID_V1: &str = "drand-verify/uuid/v1";
+const SHORT_CODE_V1: &str = "drand-verify/short-code/v1";
+
+/// The alphabet of Crockford's Base32 (<https://www.crockford.com/base32.html>)
+const CROCKFORD_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// The maximum length of a short code, limited by the 256 bit hash it is derived from
+pub const MAX_SHORT_CODE_LEN: usize = 51;
+
+/// An RFC 9562 (formerly RFC 4122) UUID of version 8 (custom)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.0
+    }
+}
+
+impl fmt::Display for Uuid {
+    /// Formats the UUID in the hyphenated lower case form, e.g. `e2d3c1b8-42f0-8a4c-9d1e-6b2f0c7a5e31`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hex = hex::encode(self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+fn hash(tag: &str, beacon: &VerifiedBeacon, context: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(tag.as_bytes());
+    hasher.update(beacon.round().to_be_bytes());
+    hasher.update(beacon.randomness());
+    hasher.update(context);
+    hasher.finalize().into()
+}
+
+/// Derives a version 8 UUID from the beacon and context.
+///
+/// The first 16 bytes of `SHA-256("drand-verify/uuid/v1" || round || randomness || context)`
+/// (round as 8 byte big endian) are used with the version and variant bits set.
+pub fn derive_uuid(beacon: &VerifiedBeacon, context: &[u8]) -> Uuid {
+    let hash = hash(UUID_V1, beacon, context);
+    let mut bytes: [u8; 16] = hash[0..16].try_into().unwrap();
+    bytes[6] = (bytes[6] & 0x0f) | 0x80; // version 8
+    bytes[8] = (bytes[8] & 0x3f) | 0x80; // variant 0b10
+    Uuid(bytes)
+}
+
+/// Derives a human readable code of `len` characters from the beacon and context.
+///
+/// The code uses Crockford's Base32 alphabet (digits and upper case letters without I, L, O, U),
+/// encoding the bits of `SHA-256("drand-verify/short-code/v1" || round || randomness || context)`
+/// from the most significant bit on. Each character carries 5 bits, so e.g. 8 characters
+/// are 40 bits.
+///
+/// # Panics
+///
+/// Panics if `len` is greater than [`MAX_SHORT_CODE_LEN`].
+pub fn derive_short_code(beacon: &VerifiedBeacon, context: &[u8], len: usize) -> String {
+    assert!(
+        len <= MAX_SHORT_CODE_LEN,
+        "Short code length must not exceed {}",
+        MAX_SHORT_CODE_LEN
+    );
+    let hash = hash(SHORT_CODE_V1, beacon, context);
+    (0..len)
+        .map(|i| {
+            let bit = i * 5;
+            let byte = bit / 8;
+            let window = u16::from_be_bytes([hash[byte], *hash.get(byte + 1).unwrap_or(&0)]);
+            let index = (window >> (11 - bit % 8)) & 0x1f;
+            CROCKFORD_ALPHABET[index as usize] as char
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G2PubkeyRfc, Pubkey};
+    use hex_literal::hex;
+
+    fn quicknet_123() -> VerifiedBeacon {
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        pk.verify_with_outcome(123, &[], &signature)
+            .into_verified()
+            .unwrap()
+    }
+
+    #[test]
+    fn derive_uuid_works() {
+        let beacon = quicknet_123();
+        let uuid = derive_uuid(&beacon, b"ticket");
+        assert_eq!(uuid.as_bytes()[6] >> 4, 8);
+        assert_eq!(uuid.as_bytes()[8] >> 6, 0b10);
+        assert_eq!(uuid, derive_uuid(&beacon, b"ticket"));
+        assert_ne!(uuid, derive_uuid(&beacon, b"other"));
+
+        let formatted = uuid.to_string();
+        assert_eq!(formatted.len(), 36);
+        assert_eq!(&formatted[14..15], "8");
+        assert_eq!(formatted, "bde4c387-e719-8fe6-b767-e07da7aa9f85");
+    }
+
+    #[test]
+    fn derive_short_code_works() {
+        let beacon = quicknet_123();
+        let code = derive_short_code(&beacon, b"ticket", 8);
+        assert_eq!(code.len(), 8);
+        assert!(code.bytes().all(|c| CROCKFORD_ALPHABET.contains(&c)));
+        assert_eq!(code, "MZG5EGB1");
+        assert_eq!(derive_short_code(&beacon, b"ticket", 4), code[0..4]);
+        assert_ne!(derive_short_code(&beacon, b"other", 8), code);
+        assert_eq!(derive_short_code(&beacon, b"ticket", 0), "");
+        assert_eq!(
+            derive_short_code(&beacon, b"ticket", MAX_SHORT_CODE_LEN).len(),
+            MAX_SHORT_CODE_LEN
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Short code length must not exceed 51")]
+    fn derive_short_code_panics_for_long_codes() {
+        derive_short_code(&quicknet_123(), b"ticket", 52);
+    }
+}
@@ -0,0 +1,483 @@
+//! A lookup table from round to randomness for analytic workloads.
+//!
+//! [`build_index`] derives the randomness of every beacon of an [archive](crate::archive) and
+//! writes it to a flat file with one 32 byte slot per round. [`RandomnessIndex`] answers
+//! lookups by round in O(1) directly from the bytes of the file, so it works on top of a
+//! memory map (e.g. `memmap2::Mmap`) without loading the whole table.
+//!
+//! The file consists of
+//!
+//! | Field       | Size                                                             |
+//! |-------------|------------------------------------------------------------------|
+//! | magic       | 8 bytes, `DRANDIDX`                                              |
+//! | version     | 1 byte                                                           |
+//! | chain hash  | 32 bytes                                                         |
+//! | first round | 8 bytes, big endian                                              |
+//! | slots       | 32 bytes per round from the first to the last round              |
+//! | checksum    | 32 bytes, SHA-256 of everything before                           |
+//!
+//! Rounds missing in the archive have an all-zero slot. At most [`MAX_GAP`] consecutive rounds
+//! may be missing.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use sha2::{Digest, Sha256};
+
+use crate::archive::{ArchiveError, ArchiveReader};
+use crate::audit::random_u64;
+use crate::chain_hash::ChainHash;
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::randomness::derive_randomness;
+use crate::verify::Pubkey;
+
+const MAGIC: &[u8; 8] = b"DRANDIDX";
+const VERSION: u8 = 1;
+const HEADER_SIZE: usize = 8 + 1 + 32 + 8;
+const SLOT_SIZE: usize = 32;
+const CHECKSUM_SIZE: usize = 32;
+
+/// The maximum number of consecutive rounds missing in an archive that [`build_index`] fills
+/// with empty slots (32 MiB)
+pub const MAX_GAP: u64 = 1 << 20;
+
+#[derive(Debug)]
+pub enum IndexError {
+    Io(io::Error),
+    Archive(ArchiveError),
+    /// The data does not start with the index magic bytes
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    /// The data is too short or ends in the middle of a slot
+    InvalidLength {
+        actual: usize,
+    },
+    /// The checksum does not match the content of the index
+    ChecksumMismatch,
+    /// The index and the archive belong to different chains
+    ChainMismatch {
+        index: ChainHash,
+        archive: ChainHash,
+    },
+    /// More than [`MAX_GAP`] rounds are missing between two beacons of the archive
+    GapTooLarge {
+        previous: u64,
+        round: u64,
+    },
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::Io(err) => write!(f, "I/O error: {}", err),
+            IndexError::Archive(err) => err.fmt(f),
+            IndexError::InvalidMagic => write!(f, "Not a randomness index"),
+            IndexError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported index version: {}", version)
+            }
+            IndexError::InvalidLength { actual } => {
+                write!(f, "Invalid randomness index length {}", actual)
+            }
+            IndexError::ChecksumMismatch => write!(f, "Randomness index checksum mismatch"),
+            IndexError::ChainMismatch { .. } => {
+                write!(f, "Index and archive belong to different chains")
+            }
+            IndexError::GapTooLarge { previous, round } => write!(
+                f,
+                "Too many rounds missing between round {} and round {}",
+                previous, round
+            ),
+        }
+    }
+}
+
+impl Error for IndexError {}
+
+impl From<io::Error> for IndexError {
+    fn from(source: io::Error) -> Self {
+        IndexError::Io(source)
+    }
+}
+
+impl From<ArchiveError> for IndexError {
+    fn from(source: ArchiveError) -> Self {
+        IndexError::Archive(source)
+    }
+}
+
+/// Writes an index of the remaining beacons of `archive` to `writer`.
+///
+/// The signatures are not verified. Only build indices from archives that were verified with
+/// [`ArchiveReader::verify`] before, or check the result with [`RandomnessIndex::spot_check`].
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::archive::{ArchiveReader, ArchiveWriter};
+/// use drand_verify::index::{build_index, RandomnessIndex};
+/// use drand_verify::{derive_randomness, networks, Beacon, ChainInfo};
+/// use hex_literal::hex;
+///
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let mut writer = ArchiveWriter::new(Vec::new(), &ChainInfo::from(&networks::QUICKNET)).unwrap();
+/// writer.append(&Beacon { round: 123, previous_signature: vec![], signature: signature.to_vec() }).unwrap();
+/// let archive = writer.finish().unwrap();
+///
+/// let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+/// let data = build_index(&mut reader, Vec::new()).unwrap();
+///
+/// // Usually `data` is a memory mapped file
+/// let index = RandomnessIndex::new(data).unwrap();
+/// index.check_integrity().unwrap();
+/// assert_eq!(index.get(123), Some(derive_randomness(&signature)));
+/// assert_eq!(index.get(124), None);
+/// ```
+pub fn build_index<R: Read, W: Write>(
+    archive: &mut ArchiveReader<R>,
+    mut writer: W,
+) -> Result<W, IndexError> {
+    let mut hasher = Sha256::new();
+    let mut write = |data: &[u8]| -> Result<(), IndexError> {
+        hasher.update(data);
+        writer.write_all(data)?;
+        Ok(())
+    };
+
+    let first = archive.next_beacon()?;
+    let first_round = first.as_ref().map_or(1, |beacon| beacon.round);
+    write(MAGIC)?;
+    write(&[VERSION])?;
+    write(archive.chain_info().hash.as_bytes())?;
+    write(&first_round.to_be_bytes())?;
+
+    let mut previous: Option<u64> = None;
+    let mut beacon = first;
+    while let Some(current) = beacon {
+        if let Some(previous) = previous {
+            // rounds are strictly increasing in an archive, so this does not underflow
+            let missing = current.round - previous - 1;
+            if missing > MAX_GAP {
+                return Err(IndexError::GapTooLarge {
+                    previous,
+                    round: current.round,
+                });
+            }
+            for _ in 0..missing {
+                write(&[0u8; SLOT_SIZE])?;
+            }
+        }
+        write(&derive_randomness(&current.signature))?;
+        previous = Some(current.round);
+        beacon = archive.next_beacon()?;
+    }
+
+    let checksum = hasher.finalize();
+    writer.write_all(&checksum)?;
+    writer.flush()?;
+    Ok(writer)
+}
+
+/// The result of [`RandomnessIndex::spot_check`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpotCheckReport {
+    /// Number of sampled rounds whose signature and index entry are correct
+    pub valid: usize,
+    /// Sampled rounds whose signature failed verification
+    pub invalid: Vec<(u64, InvalidReason)>,
+    /// Sampled rounds whose index entry differs from the randomness of the signature
+    pub mismatched: Vec<u64>,
+}
+
+impl SpotCheckReport {
+    /// The number of rounds that were checked
+    pub fn checked(&self) -> usize {
+        self.valid + self.invalid.len() + self.mismatched.len()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.invalid.is_empty() && self.mismatched.is_empty()
+    }
+}
+
+/// Read access to an index created by [`build_index`].
+///
+/// The data can be anything that dereferences to bytes, e.g. a `Vec<u8>` or a memory map.
+#[derive(Debug, Clone)]
+pub struct RandomnessIndex<B> {
+    data: B,
+    first_round: u64,
+    len: u64,
+}
+
+impl<B: AsRef<[u8]>> RandomnessIndex<B> {
+    /// Opens an index by checking its header and length.
+    ///
+    /// This does not read the slots. Use [`RandomnessIndex::check_integrity`] to check the
+    /// checksum.
+    pub fn new(data: B) -> Result<Self, IndexError> {
+        let bytes = data.as_ref();
+        if bytes.len() < HEADER_SIZE + CHECKSUM_SIZE {
+            return Err(IndexError::InvalidLength {
+                actual: bytes.len(),
+            });
+        }
+        if &bytes[..8] != MAGIC {
+            return Err(IndexError::InvalidMagic);
+        }
+        if bytes[8] != VERSION {
+            return Err(IndexError::UnsupportedVersion(bytes[8]));
+        }
+        let slots = bytes.len() - HEADER_SIZE - CHECKSUM_SIZE;
+        if slots % SLOT_SIZE != 0 {
+            return Err(IndexError::InvalidLength {
+                actual: bytes.len(),
+            });
+        }
+        let first_round = u64::from_be_bytes(bytes[41..49].try_into().unwrap());
+        let len = (slots / SLOT_SIZE) as u64;
+        // the last round must fit into a u64
+        if len > 0 && first_round.checked_add(len - 1).is_none() {
+            return Err(IndexError::InvalidLength {
+                actual: bytes.len(),
+            });
+        }
+        Ok(Self {
+            data,
+            first_round,
+            len,
+        })
+    }
+
+    /// The chain hash of the archive the index was built from
+    pub fn chain_hash(&self) -> ChainHash {
+        ChainHash::new(self.data.as_ref()[9..41].try_into().unwrap())
+    }
+
+    pub fn first_round(&self) -> u64 {
+        self.first_round
+    }
+
+    /// The last round with a slot, or `None` for an empty index
+    pub fn last_round(&self) -> Option<u64> {
+        (self.len > 0).then(|| self.first_round + (self.len - 1))
+    }
+
+    /// Compares the checksum with the content. This reads the whole index.
+    pub fn check_integrity(&self) -> Result<(), IndexError> {
+        let bytes = self.data.as_ref();
+        let (content, checksum) = bytes.split_at(bytes.len() - CHECKSUM_SIZE);
+        if &Sha256::digest(content)[..] != checksum {
+            return Err(IndexError::ChecksumMismatch);
+        }
+        Ok(())
+    }
+
+    /// The randomness of `round`, or `None` if the round is not in the index
+    pub fn get(&self, round: u64) -> Option<[u8; 32]> {
+        let offset = round.checked_sub(self.first_round)?;
+        if offset >= self.len {
+            return None;
+        }
+        let start = HEADER_SIZE + offset as usize * SLOT_SIZE;
+        let slot: [u8; 32] = self.data.as_ref()[start..start + SLOT_SIZE]
+            .try_into()
+            .unwrap();
+        (slot != [0u8; SLOT_SIZE]).then(|| slot)
+    }
+
+    /// Verifies a pseudo-random sample of about one in `every` beacons of `archive` and
+    /// compares their randomness with the index.
+    ///
+    /// The sample is chosen deterministically from `seed`. Rounds of the archive missing in
+    /// the index count as mismatched.
+    pub fn spot_check<R: Read, P: Pubkey>(
+        &self,
+        archive: &mut ArchiveReader<R>,
+        pubkey: &P,
+        every: u64,
+        seed: &[u8],
+    ) -> Result<SpotCheckReport, IndexError> {
+        let archive_hash = archive.chain_info().hash;
+        if archive_hash != self.chain_hash() {
+            return Err(IndexError::ChainMismatch {
+                index: self.chain_hash(),
+                archive: archive_hash,
+            });
+        }
+        if pubkey.scheme().pubkey_type() != archive.scheme().pubkey_type() {
+            return Err(ArchiveError::SchemeMismatch {
+                archive: archive.scheme(),
+                pubkey: pubkey.scheme(),
+            }
+            .into());
+        }
+
+        let mut report = SpotCheckReport::default();
+        while let Some(beacon) = archive.next_beacon()? {
+            if random_u64(seed, beacon.round) % every.max(1) != 0 {
+                continue;
+            }
+            match pubkey.verify_with_outcome(
+                beacon.round,
+                &beacon.previous_signature,
+                &beacon.signature,
+            ) {
+                VerificationOutcome::Valid(verified) => {
+                    if self.get(beacon.round) == Some(verified.randomness()) {
+                        report.valid += 1;
+                    } else {
+                        report.mismatched.push(beacon.round);
+                    }
+                }
+                VerificationOutcome::Invalid(reason) => report.invalid.push((beacon.round, reason)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::ArchiveWriter;
+    use crate::beacon::Beacon;
+    use crate::chain_info::ChainInfo;
+    use crate::networks::QUICKNET;
+    use crate::G2PubkeyRfc;
+    use hex_literal::hex;
+
+    const SIG_123: [u8; 48] = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+
+    fn archive(rounds: &[u64]) -> Vec<u8> {
+        let mut writer = ArchiveWriter::new(Vec::new(), &ChainInfo::from(&QUICKNET)).unwrap();
+        for &round in rounds {
+            writer
+                .append(&Beacon {
+                    round,
+                    previous_signature: vec![],
+                    signature: SIG_123.to_vec(),
+                })
+                .unwrap();
+        }
+        writer.finish().unwrap()
+    }
+
+    fn index(archive: &[u8]) -> RandomnessIndex<Vec<u8>> {
+        let mut reader = ArchiveReader::new(archive).unwrap();
+        RandomnessIndex::new(build_index(&mut reader, Vec::new()).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn build_index_works() {
+        let index = index(&archive(&[120, 121, 123]));
+        index.check_integrity().unwrap();
+        assert_eq!(index.chain_hash(), QUICKNET.chain_hash);
+        assert_eq!(index.first_round(), 120);
+        assert_eq!(index.last_round(), Some(123));
+
+        let randomness = derive_randomness(&SIG_123);
+        assert_eq!(index.get(119), None);
+        assert_eq!(index.get(120), Some(randomness));
+        assert_eq!(index.get(121), Some(randomness));
+        assert_eq!(index.get(122), None);
+        assert_eq!(index.get(123), Some(randomness));
+        assert_eq!(index.get(124), None);
+        assert_eq!(index.get(u64::MAX), None);
+
+        let last = self::index(&archive(&[u64::MAX - 1, u64::MAX]));
+        last.check_integrity().unwrap();
+        assert_eq!(last.last_round(), Some(u64::MAX));
+        assert_eq!(last.get(u64::MAX), Some(randomness));
+
+        let empty = self::index(&archive(&[]));
+        empty.check_integrity().unwrap();
+        assert_eq!(empty.last_round(), None);
+        assert_eq!(empty.get(1), None);
+    }
+
+    #[test]
+    fn build_index_rejects_large_gaps() {
+        let data = archive(&[1, MAX_GAP + 2]);
+        let mut reader = ArchiveReader::new(data.as_slice()).unwrap();
+        let index = RandomnessIndex::new(build_index(&mut reader, Vec::new()).unwrap()).unwrap();
+        assert_eq!(index.last_round(), Some(MAX_GAP + 2));
+
+        let data = archive(&[1, MAX_GAP + 3]);
+        let mut reader = ArchiveReader::new(data.as_slice()).unwrap();
+        assert!(matches!(
+            build_index(&mut reader, Vec::new()),
+            Err(IndexError::GapTooLarge {
+                previous: 1,
+                round,
+            }) if round == MAX_GAP + 3
+        ));
+    }
+
+    #[test]
+    fn new_rejects_invalid_data() {
+        let data = index(&archive(&[123])).data;
+        assert!(matches!(
+            RandomnessIndex::new(&data[..data.len() - 1]),
+            Err(IndexError::InvalidLength { .. })
+        ));
+        assert!(matches!(
+            RandomnessIndex::new(&data[..10]),
+            Err(IndexError::InvalidLength { actual: 10 })
+        ));
+        let mut modified = data.clone();
+        modified[0] ^= 1;
+        assert!(matches!(
+            RandomnessIndex::new(modified),
+            Err(IndexError::InvalidMagic)
+        ));
+        let mut modified = data.clone();
+        modified[8] = 2;
+        assert!(matches!(
+            RandomnessIndex::new(modified),
+            Err(IndexError::UnsupportedVersion(2))
+        ));
+
+        let mut modified = data;
+        modified[HEADER_SIZE] ^= 1;
+        let index = RandomnessIndex::new(modified).unwrap();
+        assert!(matches!(
+            index.check_integrity(),
+            Err(IndexError::ChecksumMismatch)
+        ));
+    }
+
+    #[test]
+    fn spot_check_works() {
+        let pk = G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap();
+        let archive = archive(&[123]);
+        let index = index(&archive);
+        let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+        let report = index.spot_check(&mut reader, &pk, 1, b"seed").unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.valid, 1);
+
+        // the index entry was modified
+        let mut data = index.data.clone();
+        data[HEADER_SIZE] ^= 1;
+        let modified = RandomnessIndex::new(data).unwrap();
+        let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+        let report = modified.spot_check(&mut reader, &pk, 1, b"seed").unwrap();
+        assert_eq!(report.mismatched, [123]);
+
+        // the signature does not belong to the round
+        let archive = self::archive(&[122, 123]);
+        let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+        let report = index.spot_check(&mut reader, &pk, 1, b"seed").unwrap();
+        assert_eq!(report.checked(), 2);
+        assert_eq!(report.invalid.len(), 1);
+        assert_eq!(report.invalid[0].0, 122);
+
+        // sampling
+        let archive = self::archive(&(1..=200).collect::<Vec<_>>());
+        let index = self::index(&archive);
+        let mut reader = ArchiveReader::new(archive.as_slice()).unwrap();
+        let report = index.spot_check(&mut reader, &pk, 10, b"seed").unwrap();
+        assert!((5..50).contains(&report.checked()), "{:?}", report);
+    }
+}
@@ -1,11 +1,96 @@
+pub mod accumulator;
+pub mod aggregate;
+pub mod archive;
+#[cfg(feature = "tokio")]
+pub mod async_tokio;
+pub mod audit;
+#[cfg(feature = "audit-log")]
+pub mod audit_log;
+pub mod backend;
+pub mod batch;
+mod beacon;
+pub mod caching;
+pub mod catchup;
+#[cfg(feature = "cbor")]
+pub mod cbor;
+mod chain_hash;
+mod chain_info;
+pub mod classify;
+pub mod compat;
+mod consistency;
+pub mod cost;
+pub mod descriptor;
+mod dyn_pubkey;
+mod encoding;
+pub mod error_json;
+#[cfg(feature = "fixtures")]
+pub mod faulty;
+#[cfg(feature = "fixtures")]
+pub mod fixtures;
+pub mod frame;
+pub mod fuzz;
+pub mod ids;
+pub mod index;
+pub mod light_client;
+pub mod networks;
+mod outcome;
+pub mod pinning;
 mod points;
-mod randomness;
+pub mod pop;
+mod previous_signature;
+pub mod proof_pack;
+pub mod randomness;
+mod randomness_beacon;
+#[cfg(feature = "receipts")]
+pub mod receipt;
+pub mod reorder;
+pub mod report;
+pub mod rounds;
+#[cfg(feature = "rand")]
+pub mod sampler;
+pub mod scheduler;
+mod scheme;
+mod signature;
+#[cfg(feature = "signer")]
+pub mod signer;
+#[cfg(feature = "signer")]
+pub mod simulate;
+mod source;
+#[cfg(feature = "store")]
+pub mod store;
 mod verify;
+#[cfg(feature = "jni")]
+mod verify_jni;
 #[cfg(feature = "js")]
 mod verify_js;
 
-pub use points::InvalidPoint;
-pub use randomness::derive_randomness;
+pub use beacon::{
+    check_link, Beacon, LinkError, StoredBeacon, VerifiedBeacon, CANONICAL_BEACON_V1,
+};
+pub use chain_hash::{ChainHash, InvalidChainHash};
+pub use chain_info::{ChainInfo, GroupInfo, GroupMismatch, InvalidChainInfo};
+pub use consistency::{
+    check_consistency, cross_check_randomness, Equivocation, RandomnessMismatch,
+};
+pub use dyn_pubkey::DynPubkey;
+pub use encoding::{
+    decode_signature, parse_signature, Encoding, ParseSignatureError, UnknownEncoding,
+};
+pub use outcome::{InvalidReason, VerificationOutcome};
+pub use points::{assume_inputs_valid, InvalidPoint, UncheckedCapability};
+pub use previous_signature::{InvalidPreviousSignature, PreviousSignature};
+pub use randomness::{derive_randomness, derive_randomness_into};
+pub use randomness_beacon::{BeaconKey, RandomnessBeacon};
+pub use scheme::{
+    signature_size_for_scheme, validate_lengths, KeyLengthMismatch, LengthMismatch, Scheme,
+    UnknownScheme,
+};
+pub use signature::{constant_time_eq_signatures, Signature};
+pub use source::BeaconSource;
 #[allow(deprecated)]
-pub use verify::G2Pubkey;
-pub use verify::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey, VerificationError};
+pub use verify::LegacyG2Pubkey;
+pub use verify::{
+    BlsUnchainedG1Rfc9380, BlsUnchainedOnG1, DrandPubkey, ErrorCode, G1Pubkey, G2Pubkey,
+    G2PubkeyFastnet, G2PubkeyRfc, Group, KeyGroup, MessageHash, PedersenBls, PreparedPubkey,
+    Pubkey, SchemeMarker, VerificationError, G1, G1_POINT_SIZE, G2, G2_POINT_SIZE,
+};
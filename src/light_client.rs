@@ -0,0 +1,239 @@
+//! A minimal light client core.
+//!
+//! The [`LightClient`] only keeps the public key of the network and the latest verified beacon.
+//! It accepts beacons with strictly increasing rounds, which protects consumers from
+//! replayed or rolled back beacons. Fetching beacons is up to the caller.
+
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+use crate::beacon::{Beacon, VerifiedBeacon};
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::verify::Pubkey;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LightClientError {
+    /// The beacon's round is not higher than the latest verified round
+    NotMonotonic { latest: u64, actual: u64 },
+    /// The beacon failed verification
+    Invalid(InvalidReason),
+}
+
+impl fmt::Display for LightClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LightClientError::NotMonotonic { latest, actual } => write!(
+                f,
+                "Round must be higher than the latest verified round {}, actual: {}",
+                latest, actual
+            ),
+            LightClientError::Invalid(reason) => write!(f, "Invalid beacon: {}", reason),
+        }
+    }
+}
+
+impl Error for LightClientError {}
+
+/// Verifies beacons of one network and keeps track of the latest one.
+///
+/// ## Examples
+///
+/// ```
+/// use hex_literal::hex;
+/// use drand_verify::light_client::LightClient;
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+///
+/// const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+/// let mut client = LightClient::new(G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+///
+/// let beacon = Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// };
+/// client.verify_and_advance(&beacon).unwrap();
+/// assert_eq!(client.latest_round(), 123);
+///
+/// // Replays are rejected
+/// client.verify_and_advance(&beacon).unwrap_err();
+/// ```
+pub struct LightClient<P: Pubkey> {
+    pubkey: P,
+    latest: Option<VerifiedBeacon>,
+}
+
+impl<P: Pubkey> LightClient<P> {
+    pub fn new(pubkey: P) -> Self {
+        Self {
+            pubkey,
+            latest: None,
+        }
+    }
+
+    /// Creates a client that continues after a beacon verified earlier, e.g. before a restart
+    pub fn from_checkpoint(pubkey: P, latest: VerifiedBeacon) -> Self {
+        Self {
+            pubkey,
+            latest: Some(latest),
+        }
+    }
+
+    /// Verifies the beacon and makes it the latest one if its round is higher than
+    /// the latest verified round.
+    ///
+    /// The client state is not changed if an error is returned.
+    pub fn verify_and_advance(
+        &mut self,
+        beacon: &Beacon,
+    ) -> Result<&VerifiedBeacon, LightClientError> {
+        let latest = self.latest_round();
+        if beacon.round <= latest {
+            return Err(LightClientError::NotMonotonic {
+                latest,
+                actual: beacon.round,
+            });
+        }
+        match self.pubkey.verify_with_outcome(
+            beacon.round,
+            &beacon.previous_signature,
+            &beacon.signature,
+        ) {
+            VerificationOutcome::Valid(verified) => Ok(self.latest.insert(verified)),
+            VerificationOutcome::Invalid(reason) => Err(LightClientError::Invalid(reason)),
+        }
+    }
+
+    /// Calls [`LightClient::verify_and_advance`] for the beacons in order until all are
+    /// processed or the deadline passed.
+    ///
+    /// Returns one result per processed beacon. If there are fewer results than beacons,
+    /// the deadline passed and the remaining beacons were not looked at. The deadline is
+    /// checked before each beacon. Note that `Instant::now` panics on wasm32-unknown-unknown.
+    pub fn verify_and_advance_with_deadline(
+        &mut self,
+        beacons: &[Beacon],
+        deadline: Instant,
+    ) -> Vec<Result<VerifiedBeacon, LightClientError>> {
+        let mut results = Vec::with_capacity(beacons.len());
+        for beacon in beacons {
+            if Instant::now() >= deadline {
+                break;
+            }
+            results.push(self.verify_and_advance(beacon).cloned());
+        }
+        results
+    }
+
+    /// The latest verified beacon
+    pub fn latest(&self) -> Option<&VerifiedBeacon> {
+        self.latest.as_ref()
+    }
+
+    /// The latest verified round or 0 if nothing was verified yet
+    pub fn latest_round(&self) -> u64 {
+        self.latest.as_ref().map_or(0, VerifiedBeacon::round)
+    }
+
+    /// The randomness of the latest verified beacon
+    pub fn latest_randomness(&self) -> Option<[u8; 32]> {
+        self.latest.as_ref().map(VerifiedBeacon::randomness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::G1Pubkey;
+    use hex_literal::hex;
+
+    /// Public key League of Entropy Mainnet (curl -sS https://drand.cloudflare.com/info)
+    const PK_LEO_MAINNET: [u8; 48] = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+
+    fn beacon_72785() -> Beacon {
+        // curl -sS https://drand.cloudflare.com/public/72785
+        Beacon {
+            round: 72785,
+            previous_signature: hex::decode("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").unwrap(),
+            signature: hex::decode("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").unwrap(),
+        }
+    }
+
+    #[test]
+    fn verify_and_advance_works() {
+        let mut client = LightClient::new(G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap());
+        assert_eq!(client.latest_round(), 0);
+        assert_eq!(client.latest(), None);
+        assert_eq!(client.latest_randomness(), None);
+
+        // invalid beacon does not change state
+        let mut invalid = beacon_72785();
+        invalid.round = 72786;
+        assert_eq!(
+            client.verify_and_advance(&invalid).unwrap_err(),
+            LightClientError::Invalid(InvalidReason::SignatureMismatch)
+        );
+        assert_eq!(client.latest_round(), 0);
+
+        let verified = client.verify_and_advance(&beacon_72785()).unwrap();
+        assert_eq!(verified.round(), 72785);
+        assert_eq!(client.latest_round(), 72785);
+        assert_eq!(
+            client.latest_randomness(),
+            Some(hex!(
+                "8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9"
+            ))
+        );
+
+        // same round again
+        assert_eq!(
+            client.verify_and_advance(&beacon_72785()).unwrap_err(),
+            LightClientError::NotMonotonic {
+                latest: 72785,
+                actual: 72785
+            }
+        );
+    }
+
+    #[test]
+    fn verify_and_advance_with_deadline_works() {
+        use std::time::Duration;
+
+        let mut client = LightClient::new(G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap());
+        let beacons = [beacon_72785(), beacon_72785()];
+
+        assert!(client
+            .verify_and_advance_with_deadline(&beacons, Instant::now())
+            .is_empty());
+        assert_eq!(client.latest_round(), 0);
+
+        let results = client
+            .verify_and_advance_with_deadline(&beacons, Instant::now() + Duration::from_secs(60));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].as_ref().unwrap().round(), 72785);
+        assert!(matches!(
+            results[1],
+            Err(LightClientError::NotMonotonic { .. })
+        ));
+        assert_eq!(client.latest_round(), 72785);
+    }
+
+    #[test]
+    fn from_checkpoint_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let beacon = beacon_72785();
+        let checkpoint = pk
+            .verify_with_outcome(beacon.round, &beacon.previous_signature, &beacon.signature)
+            .into_verified()
+            .unwrap();
+        let mut client = LightClient::from_checkpoint(pk, checkpoint);
+        assert_eq!(client.latest_round(), 72785);
+        assert_eq!(
+            client.verify_and_advance(&beacon_72785()).unwrap_err(),
+            LightClientError::NotMonotonic {
+                latest: 72785,
+                actual: 72785
+            }
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Constants for well-known public drand networks.
+//!
+//! All values are taken from the `/info` endpoint of the respective network.
+
+use std::error::Error;
+use std::fmt;
+
+use sha2::{Digest, Sha256};
+
+use crate::chain_hash::ChainHash;
+use crate::rounds::{self, RoundIterator, Timestamp};
+
+/// Description of a drand network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Network {
+    /// A short, human readable name like "quicknet"
+    pub name: &'static str,
+    /// The chain hash identifying the network
+    pub chain_hash: ChainHash,
+    /// The group public key in compressed format (48 or 96 bytes, depending on the scheme)
+    pub public_key: &'static [u8],
+    /// The drand scheme ID, e.g. "bls-unchained-g1-rfc9380"
+    pub scheme_id: &'static str,
+    /// The period between two rounds in seconds
+    pub period: u64,
+    /// The time of round 1 in seconds since the Unix epoch
+    pub genesis_time: u64,
+    /// Base URLs of public HTTP relays serving this network
+    pub relays: &'static [&'static str],
+}
+
+/// The HTTP relays operated by members of the League of Entropy.
+///
+/// Networks other than the classic mainnet are served under `/<chain hash>/` on these relays.
+const LOE_RELAYS: &[&str] = &[
+    "https://api.drand.sh",
+    "https://api2.drand.sh",
+    "https://api3.drand.sh",
+    "https://drand.cloudflare.com",
+];
+
+/// The classic League of Entropy mainnet (chained, 30s rounds).
+///
+/// See <https://api.drand.sh/8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce/info>
+pub const MAINNET: Network = Network {
+    name: "mainnet",
+    chain_hash: ChainHash::new(hex_literal::hex!(
+        "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce"
+    )),
+    public_key: &hex_literal::hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31"),
+    scheme_id: "pedersen-bls-chained",
+    period: 30,
+    genesis_time: 1595431050,
+    relays: LOE_RELAYS,
+};
+
+/// The League of Entropy quicknet (unchained, 3s rounds, signatures on G1).
+///
+/// See <https://api.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/info>
+pub const QUICKNET: Network = Network {
+    name: "quicknet",
+    chain_hash: ChainHash::new(hex_literal::hex!(
+        "52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971"
+    )),
+    public_key: &hex_literal::hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a"),
+    scheme_id: "bls-unchained-g1-rfc9380",
+    period: 3,
+    genesis_time: 1692803367,
+    relays: LOE_RELAYS,
+};
+
+/// The deprecated League of Entropy fastnet (unchained, 3s rounds, signatures on G1).
+///
+/// See <https://api.drand.sh/dbd506d6ef76e5f386f41c651dcb808c5bcbd75471cc4eafa3f4df7ad4e4c493/info>
+pub const FASTNET: Network = Network {
+    name: "fastnet",
+    chain_hash: ChainHash::new(hex_literal::hex!(
+        "dbd506d6ef76e5f386f41c651dcb808c5bcbd75471cc4eafa3f4df7ad4e4c493"
+    )),
+    public_key: &hex_literal::hex!("a0b862a7527fee3a731bcb59280ab6abd62d5c0b6ea03dc4ddf6612fdfc9d01f01c31542541771903475eb1ec6615f8d0df0b8b6dce385811d6dcf8cbefb8759e5e616a3dfd054c928940766d9a5b9db91e3b697e5d70a975181e007f87fca5e"),
+    scheme_id: "bls-unchained-on-g1",
+    period: 3,
+    genesis_time: 1677685200,
+    relays: LOE_RELAYS,
+};
+
+impl Network {
+    /// Returns the latest round published at or before `time`. See [`rounds::round_at`].
+    pub fn round_at(&self, time: impl Timestamp) -> u64 {
+        rounds::round_at(self.genesis_time, self.period, time)
+    }
+
+    /// Returns the time at which `round` is published. See [`rounds::round_time`].
+    pub fn round_time<T: Timestamp>(&self, round: u64) -> T {
+        rounds::round_time(self.genesis_time, self.period, round)
+    }
+
+    /// Returns an iterator over all rounds published in the time range from `start` to `end`
+    /// (inclusive) together with their publishing times. See [`rounds::rounds_between`].
+    pub fn rounds_between<T: Timestamp>(
+        &self,
+        start: impl Timestamp,
+        end: impl Timestamp,
+    ) -> RoundIterator<T> {
+        rounds::rounds_between(self.genesis_time, self.period, start, end)
+    }
+
+    /// Computes the checksum of the security relevant constants: SHA-256 of the chain hash,
+    /// period and genesis time (8 byte big endian each), public key length (1 byte),
+    /// public key and scheme ID.
+    pub fn checksum(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.chain_hash.as_bytes());
+        hasher.update(self.period.to_be_bytes());
+        hasher.update(self.genesis_time.to_be_bytes());
+        hasher.update([self.public_key.len() as u8]);
+        hasher.update(self.public_key);
+        hasher.update(self.scheme_id.as_bytes());
+        hasher.finalize().into()
+    }
+}
+
+/// All networks known to this crate
+pub const KNOWN_NETWORKS: &[Network] = &[MAINNET, QUICKNET, FASTNET];
+
+/// The expected [`Network::checksum`] of every known network.
+///
+/// These are kept separate from the constants, so a change of the key material
+/// requires a matching change here. Since both live in this file, this detects accidental or
+/// partial edits of the constants, not a modified copy of the crate.
+const CHECKSUMS: &[(&str, [u8; 32])] = &[
+    (
+        "mainnet",
+        hex_literal::hex!("3a0ef5d9ce48b3348046c89773d5ed61078dd391fc6e61ccf5c255ae361ab986"),
+    ),
+    (
+        "quicknet",
+        hex_literal::hex!("8696a0af54206c152d37507b60d1a916380e2ac2239fd321c9c03af16ef2ed97"),
+    ),
+    (
+        "fastnet",
+        hex_literal::hex!("5e8c89c91305badb987e84e487915063ab5fd5f00c1b28af8acdb859c44bfe93"),
+    ),
+];
+
+/// The error returned by [`verify_network_constants`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChecksumMismatch {
+    pub network: &'static str,
+}
+
+impl fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Checksum mismatch for the constants of network {}",
+            self.network
+        )
+    }
+}
+
+impl Error for ChecksumMismatch {}
+
+/// The error returned by [`by_name`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkLookupError {
+    /// No known network has this name
+    UnknownNetwork { name: String },
+    /// The constants of the network do not match their checksum
+    ChecksumMismatch(ChecksumMismatch),
+}
+
+impl fmt::Display for NetworkLookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetworkLookupError::UnknownNetwork { name } => write!(f, "Unknown network '{}'", name),
+            NetworkLookupError::ChecksumMismatch(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for NetworkLookupError {}
+
+impl From<ChecksumMismatch> for NetworkLookupError {
+    fn from(err: ChecksumMismatch) -> Self {
+        NetworkLookupError::ChecksumMismatch(err)
+    }
+}
+
+fn verify_checksum(network: &'static Network) -> Result<(), ChecksumMismatch> {
+    let expected = CHECKSUMS
+        .iter()
+        .find(|(name, _)| *name == network.name)
+        .map(|(_, checksum)| checksum);
+    if expected == Some(&network.checksum()) {
+        Ok(())
+    } else {
+        Err(ChecksumMismatch {
+            network: network.name,
+        })
+    }
+}
+
+/// Checks the embedded constants of all known networks against their checksums.
+///
+/// Lookups via [`by_name`] check the checksum of the returned network. The constants
+/// [`MAINNET`], [`QUICKNET`] and [`FASTNET`] are not checked when used directly, so call this
+/// once at startup when using them.
+///
+/// ## Examples
+///
+/// ```
+/// drand_verify::networks::verify_network_constants().expect("network constants were modified");
+/// ```
+pub fn verify_network_constants() -> Result<(), ChecksumMismatch> {
+    KNOWN_NETWORKS.iter().try_for_each(verify_checksum)
+}
+
+/// Looks up a known network by its name (e.g. "quicknet") and checks its constants against
+/// the embedded checksum
+pub fn by_name(name: &str) -> Result<&'static Network, NetworkLookupError> {
+    let network = KNOWN_NETWORKS
+        .iter()
+        .find(|network| network.name == name)
+        .ok_or_else(|| NetworkLookupError::UnknownNetwork {
+            name: name.to_string(),
+        })?;
+    verify_checksum(network)?;
+    Ok(network)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+    #[test]
+    fn by_name_works() {
+        assert_eq!(by_name("mainnet"), Ok(&MAINNET));
+        assert_eq!(by_name("quicknet"), Ok(&QUICKNET));
+        assert_eq!(by_name("fastnet"), Ok(&FASTNET));
+        assert_eq!(
+            by_name("Quicknet"),
+            Err(NetworkLookupError::UnknownNetwork {
+                name: "Quicknet".to_string()
+            })
+        );
+        assert_eq!(
+            by_name(""),
+            Err(NetworkLookupError::UnknownNetwork {
+                name: "".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn public_keys_are_valid() {
+        G1Pubkey::from_variable(MAINNET.public_key).unwrap();
+        G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap();
+        G2PubkeyFastnet::from_variable(FASTNET.public_key).unwrap();
+    }
+
+    #[test]
+    fn verify_network_constants_works() {
+        assert_eq!(verify_network_constants(), Ok(()));
+
+        // tampered copy
+        static TAMPERED: Network = Network {
+            period: 31,
+            ..MAINNET
+        };
+        assert_ne!(TAMPERED.checksum(), MAINNET.checksum());
+        assert_eq!(
+            verify_checksum(&TAMPERED),
+            Err(ChecksumMismatch { network: "mainnet" })
+        );
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt;
+
+use crate::beacon::VerifiedBeacon;
+use crate::verify::VerificationError;
+
+/// The result of a beacon verification.
+///
+/// ## Examples
+///
+/// ```
+/// use hex_literal::hex;
+/// use drand_verify::{G2PubkeyRfc, Pubkey, VerificationOutcome};
+///
+/// const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+/// let pk = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+///
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// match pk.verify_with_outcome(123, b"", &signature) {
+///     VerificationOutcome::Valid(beacon) => {
+///         assert_eq!(beacon.round(), 123);
+///         assert_eq!(beacon.randomness(), hex!("fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc"));
+///     }
+///     VerificationOutcome::Invalid(reason) => panic!("Invalid beacon: {}", reason),
+/// }
+/// ```
+#[must_use = "the beacon must not be used if it is invalid"]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    Valid(VerifiedBeacon),
+    Invalid(InvalidReason),
+}
+
+impl VerificationOutcome {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, VerificationOutcome::Valid(_))
+    }
+
+    /// Returns the verified beacon if valid and `None` otherwise
+    pub fn into_verified(self) -> Option<VerifiedBeacon> {
+        match self {
+            VerificationOutcome::Valid(beacon) => Some(beacon),
+            VerificationOutcome::Invalid(_) => None,
+        }
+    }
+}
+
+/// The reason why a beacon is invalid
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum InvalidReason {
+    /// An input could not be decoded as a point of the expected group
+    InvalidPoint { field: String, msg: String },
+    /// The signature is a valid point but does not match the public key, round and previous signature
+    SignatureMismatch,
+}
+
+impl fmt::Display for InvalidReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidReason::InvalidPoint { field, msg } => {
+                write!(f, "Invalid point for field {}: {}", field, msg)
+            }
+            InvalidReason::SignatureMismatch => write!(f, "Signature does not match"),
+        }
+    }
+}
+
+impl From<VerificationError> for InvalidReason {
+    fn from(source: VerificationError) -> Self {
+        match source {
+            VerificationError::InvalidPoint { field, msg } => {
+                InvalidReason::InvalidPoint { field, msg }
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Trust-on-first-use pinning of chain info.
+//!
+//! Long-running clients usually fetch the chain info from a relay once and then verify beacons
+//! against its public key. A compromised relay could later serve a different key.
+//! [`PinnedChain`] stores the first chain info it sees in a [`PinStore`] and rejects every later
+//! document whose public key, period or genesis time differs from the pinned one.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::chain_info::ChainInfo;
+
+/// Storage for the pinned chain info
+pub trait PinStore {
+    type Error;
+
+    /// Loads the pinned chain info if it was saved before
+    fn load(&self) -> Result<Option<ChainInfo>, Self::Error>;
+
+    fn save(&mut self, info: &ChainInfo) -> Result<(), Self::Error>;
+}
+
+/// A pin store keeping the chain info in memory, e.g. for tests
+#[derive(Debug, Default)]
+pub struct MemoryPinStore {
+    pinned: Option<ChainInfo>,
+}
+
+impl MemoryPinStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PinStore for MemoryPinStore {
+    type Error = std::convert::Infallible;
+
+    fn load(&self) -> Result<Option<ChainInfo>, Self::Error> {
+        Ok(self.pinned.clone())
+    }
+
+    fn save(&mut self, info: &ChainInfo) -> Result<(), Self::Error> {
+        self.pinned = Some(info.clone());
+        Ok(())
+    }
+}
+
+/// A difference between a chain info document and the pinned one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinMismatch {
+    PublicKey { pinned: Vec<u8>, actual: Vec<u8> },
+    Period { pinned: u64, actual: u64 },
+    GenesisTime { pinned: u64, actual: u64 },
+}
+
+impl fmt::Display for PinMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinMismatch::PublicKey { pinned, actual } => write!(
+                f,
+                "Public key differs from pinned chain: pinned {}, actual {}",
+                hex::encode(pinned),
+                hex::encode(actual)
+            ),
+            PinMismatch::Period { pinned, actual } => write!(
+                f,
+                "Period differs from pinned chain: pinned {}, actual {}",
+                pinned, actual
+            ),
+            PinMismatch::GenesisTime { pinned, actual } => write!(
+                f,
+                "Genesis time differs from pinned chain: pinned {}, actual {}",
+                pinned, actual
+            ),
+        }
+    }
+}
+
+impl Error for PinMismatch {}
+
+/// The error returned by [`PinnedChain::check`]
+#[derive(Debug)]
+pub enum PinError<E> {
+    /// The store failed
+    Store(E),
+    /// The chain info differs from the pinned one
+    Mismatch(PinMismatch),
+}
+
+impl<E: fmt::Display> fmt::Display for PinError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinError::Store(err) => write!(f, "Pin store error: {}", err),
+            PinError::Mismatch(err) => err.fmt(f),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for PinError<E> {}
+
+/// Pins the first chain info seen (trust-on-first-use) and checks later documents against it.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::pinning::{MemoryPinStore, PinError, PinnedChain};
+/// use drand_verify::{networks, ChainInfo};
+///
+/// let mut chain = PinnedChain::new(MemoryPinStore::new()).unwrap();
+/// let info = ChainInfo::from(&networks::QUICKNET);
+///
+/// // The first document is pinned
+/// chain.check(&info).unwrap();
+/// chain.check(&info).unwrap();
+///
+/// // A relay serving another key is rejected
+/// let mut substituted = info.clone();
+/// substituted.public_key = networks::MAINNET.public_key.to_vec();
+/// assert!(matches!(chain.check(&substituted), Err(PinError::Mismatch(_))));
+/// ```
+#[derive(Debug)]
+pub struct PinnedChain<S: PinStore> {
+    store: S,
+    pinned: Option<ChainInfo>,
+}
+
+impl<S: PinStore> PinnedChain<S> {
+    /// Loads the pinned chain info from the store, if any
+    pub fn new(store: S) -> Result<Self, S::Error> {
+        let pinned = store.load()?;
+        Ok(Self { store, pinned })
+    }
+
+    /// The pinned chain info or `None` if nothing was seen yet
+    pub fn pinned(&self) -> Option<&ChainInfo> {
+        self.pinned.as_ref()
+    }
+
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// Pins `info` if nothing is pinned yet. Otherwise returns an error if the public key,
+    /// period or genesis time of `info` differs from the pinned chain info.
+    pub fn check(&mut self, info: &ChainInfo) -> Result<(), PinError<S::Error>> {
+        match &self.pinned {
+            Some(pinned) => compare(pinned, info).map_err(PinError::Mismatch),
+            None => {
+                self.store.save(info).map_err(PinError::Store)?;
+                self.pinned = Some(info.clone());
+                Ok(())
+            }
+        }
+    }
+}
+
+fn compare(pinned: &ChainInfo, actual: &ChainInfo) -> Result<(), PinMismatch> {
+    if pinned.public_key != actual.public_key {
+        return Err(PinMismatch::PublicKey {
+            pinned: pinned.public_key.clone(),
+            actual: actual.public_key.clone(),
+        });
+    }
+    if pinned.period != actual.period {
+        return Err(PinMismatch::Period {
+            pinned: pinned.period,
+            actual: actual.period,
+        });
+    }
+    if pinned.genesis_time != actual.genesis_time {
+        return Err(PinMismatch::GenesisTime {
+            pinned: pinned.genesis_time,
+            actual: actual.genesis_time,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks;
+
+    #[test]
+    fn pinned_chain_works() {
+        let info = ChainInfo::from(&networks::QUICKNET);
+        let mut chain = PinnedChain::new(MemoryPinStore::new()).unwrap();
+        assert_eq!(chain.pinned(), None);
+        chain.check(&info).unwrap();
+        assert_eq!(chain.pinned(), Some(&info));
+
+        // Fields other than key, period and genesis may change
+        let mut other = info.clone();
+        other.group_hash = Some([1; 32]);
+        chain.check(&other).unwrap();
+
+        let mut other = info.clone();
+        other.public_key = networks::MAINNET.public_key.to_vec();
+        assert!(matches!(
+            chain.check(&other),
+            Err(PinError::Mismatch(PinMismatch::PublicKey { .. }))
+        ));
+        let mut other = info.clone();
+        other.period = 30;
+        match chain.check(&other) {
+            Err(PinError::Mismatch(err)) => {
+                assert_eq!(
+                    err,
+                    PinMismatch::Period {
+                        pinned: 3,
+                        actual: 30
+                    }
+                );
+                assert_eq!(
+                    err.to_string(),
+                    "Period differs from pinned chain: pinned 3, actual 30"
+                );
+            }
+            _ => panic!("Expected mismatch"),
+        }
+        let mut other = info.clone();
+        other.genesis_time += 1;
+        assert!(matches!(
+            chain.check(&other),
+            Err(PinError::Mismatch(PinMismatch::GenesisTime { .. }))
+        ));
+
+        // The pin survives a restart
+        let mut chain = PinnedChain::new(chain.into_store()).unwrap();
+        assert_eq!(chain.pinned(), Some(&info));
+        assert!(chain.check(&other).is_err());
+    }
+}
@@ -2,17 +2,83 @@ use std::fmt;
 
 use bls12_381::{G1Affine, G2Affine};
 
+use crate::verify::ErrorCode;
+
+/// The start of the message of [`InvalidPoint::InvalidLength`]
+const INVALID_LENGTH_MSG: &str = "Invalid input length for point (must be in compressed format)";
+
 #[derive(Debug)]
 pub enum InvalidPoint {
     InvalidLength { expected: usize, actual: usize },
     DecodingError {},
 }
 
+/// Permission to skip the subgroup checks of `*_unchecked` constructors.
+///
+/// The only way to obtain this token is [`assume_inputs_valid`], which makes it easy to find
+/// all call sites skipping the checks in a large codebase.
+#[derive(Debug, Clone, Copy)]
+pub struct UncheckedCapability {
+    _private: (),
+}
+
+/// Creates an [`UncheckedCapability`].
+///
+/// Only call this for inputs you know for sure are valid points, e.g. public keys that
+/// are compiled into the binary. Invalid inputs can lead to wrong verification results.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{assume_inputs_valid, networks, G2PubkeyRfc, Pubkey};
+///
+/// // The key is a constant of this crate and known to be valid
+/// let key: [u8; 96] = networks::QUICKNET.public_key.try_into().unwrap();
+/// let pk = G2PubkeyRfc::from_fixed_unchecked(key, assume_inputs_valid()).unwrap();
+/// ```
+pub fn assume_inputs_valid() -> UncheckedCapability {
+    UncheckedCapability { _private: () }
+}
+
+impl InvalidPoint {
+    /// The stable numeric code of this error for inputs other than the signature,
+    /// see [`ErrorCode`] and [`InvalidPoint::code_for`].
+    pub fn code(&self) -> ErrorCode {
+        self.code_for("")
+    }
+
+    /// The stable numeric code of this error when decoding the input `field`.
+    ///
+    /// Errors of the `"signature"` field map to the signature specific codes. All error codes
+    /// of invalid points are derived from this mapping.
+    pub fn code_for(&self, field: &str) -> ErrorCode {
+        error_code(field, matches!(self, InvalidPoint::InvalidLength { .. }))
+    }
+}
+
+fn error_code(field: &str, invalid_length: bool) -> ErrorCode {
+    match (field == "signature", invalid_length) {
+        (true, true) => ErrorCode::InvalidSignatureLength,
+        (true, false) => ErrorCode::InvalidSignature,
+        (false, true) => ErrorCode::InvalidPointLength,
+        (false, false) => ErrorCode::InvalidPoint,
+    }
+}
+
+/// The code of [`InvalidPoint::code_for`] for the error of `field` with the message `msg`
+pub(crate) fn code_for_message(field: &str, msg: &str) -> ErrorCode {
+    error_code(field, msg.starts_with(INVALID_LENGTH_MSG))
+}
+
 impl fmt::Display for InvalidPoint {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             InvalidPoint::InvalidLength { expected, actual } => {
-                write!(f, "Invalid input length for point (must be in compressed format): Expected {}, actual: {}", expected, actual)
+                write!(
+                    f,
+                    "{}: Expected {}, actual: {}",
+                    INVALID_LENGTH_MSG, expected, actual
+                )
             }
             InvalidPoint::DecodingError {} => {
                 write!(f, "Invalid point")
@@ -47,24 +113,47 @@ pub fn g2_from_variable(data: &[u8]) -> Result<G2Affine, InvalidPoint> {
     g2_from_fixed(buf)
 }
 
+/// Rejects the point at infinity, which is neither a valid public key nor a valid signature
+fn non_identity<P>(point: Option<P>, is_identity: impl Fn(&P) -> bool) -> Result<P, InvalidPoint> {
+    point
+        .filter(|point| !is_identity(point))
+        .ok_or(InvalidPoint::DecodingError {})
+}
+
+pub(crate) fn g1_non_identity(point: Option<G1Affine>) -> Result<G1Affine, InvalidPoint> {
+    non_identity(point, |point| point.is_identity().into())
+}
+
+pub(crate) fn g2_non_identity(point: Option<G2Affine>) -> Result<G2Affine, InvalidPoint> {
+    non_identity(point, |point| point.is_identity().into())
+}
+
+/// Decodes a compressed G1 point. The point at infinity is rejected.
 pub fn g1_from_fixed(data: [u8; 48]) -> Result<G1Affine, InvalidPoint> {
-    Option::from(G1Affine::from_compressed(&data)).ok_or(InvalidPoint::DecodingError {})
+    g1_non_identity(G1Affine::from_compressed(&data).into())
 }
 
 /// Like [`g1_from_fixed`] without guaranteeing that the encoding represents a valid element.
 /// Only use this when you know for sure the encoding is correct.
-pub fn g1_from_fixed_unchecked(data: [u8; 48]) -> Result<G1Affine, InvalidPoint> {
-    Option::from(G1Affine::from_compressed_unchecked(&data)).ok_or(InvalidPoint::DecodingError {})
+pub fn g1_from_fixed_unchecked(
+    data: [u8; 48],
+    _capability: UncheckedCapability,
+) -> Result<G1Affine, InvalidPoint> {
+    g1_non_identity(G1Affine::from_compressed_unchecked(&data).into())
 }
 
+/// Decodes a compressed G2 point. The point at infinity is rejected.
 pub fn g2_from_fixed(data: [u8; 96]) -> Result<G2Affine, InvalidPoint> {
-    Option::from(G2Affine::from_compressed(&data)).ok_or(InvalidPoint::DecodingError {})
+    g2_non_identity(G2Affine::from_compressed(&data).into())
 }
 
 /// Like [`g2_from_fixed`] without guaranteeing that the encoding represents a valid element.
 /// Only use this when you know for sure the encoding is correct.
-pub fn g2_from_fixed_unchecked(data: [u8; 96]) -> Result<G2Affine, InvalidPoint> {
-    Option::from(G2Affine::from_compressed_unchecked(&data)).ok_or(InvalidPoint::DecodingError {})
+pub fn g2_from_fixed_unchecked(
+    data: [u8; 96],
+    _capability: UncheckedCapability,
+) -> Result<G2Affine, InvalidPoint> {
+    g2_non_identity(G2Affine::from_compressed_unchecked(&data).into())
 }
 
 #[cfg(test)]
@@ -123,7 +212,7 @@ mod tests {
     #[test]
     fn g1_from_fixed_unchecked_works() {
         let data = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
-        let a = g1_from_fixed_unchecked(data).unwrap();
+        let a = g1_from_fixed_unchecked(data, assume_inputs_valid()).unwrap();
         let b = g1_from_fixed(data).unwrap();
         assert_eq!(a, b);
     }
@@ -149,7 +238,7 @@ mod tests {
     #[test]
     fn g2_from_fixed_unchecked_works() {
         let data = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
-        let a = g2_from_fixed_unchecked(data).unwrap();
+        let a = g2_from_fixed_unchecked(data, assume_inputs_valid()).unwrap();
         let b = g2_from_fixed(data).unwrap();
         assert_eq!(a, b);
     }
@@ -0,0 +1,138 @@
+//! Verification of BLS proofs of possession (PoP).
+//!
+//! A proof of possession is a signature of a public key over its own compressed encoding.
+//! It shows that the owner of the key knows the corresponding secret key, which protects
+//! against rogue key attacks when keys of several parties are combined. Check the PoP of each
+//! node's published key (e.g. from a DKG transcript) before trusting keys derived from them.
+//!
+//! This implements `PopVerify` of the proof of possession scheme of
+//! [draft-irtf-cfrg-bls-signature-05](https://datatracker.ietf.org/doc/html/draft-irtf-cfrg-bls-signature-05#section-3.3)
+//! with the ciphersuites `BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_` (keys on G1) and
+//! `BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_` (keys on G2).
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    pairing, G1Affine, G1Projective, G2Affine, G2Projective,
+};
+
+use crate::points::{g1_from_variable, g2_from_variable};
+use crate::verify::{Group, VerificationError};
+
+/// The domain separation tag for proofs on G2 (public keys on G1)
+pub const POP_DOMAIN_G2: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The domain separation tag for proofs on G1 (public keys on G2)
+pub const POP_DOMAIN_G1: &[u8] = b"BLS_POP_BLS12381G1_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Verifies the proof of possession of a compressed public key on the given group.
+///
+/// The proof lives on the other group. Like all public keys and signatures of this crate,
+/// an identity key or proof is rejected as an invalid point.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::pop::verify_pop;
+/// use drand_verify::Scheme;
+///
+/// use hex_literal::hex;
+///
+/// let public_key = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+/// // A beacon signature is not a proof of possession
+/// let proof = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+///
+/// // Keys of pedersen-bls-chained nodes are on G1
+/// let key_group = Scheme::PedersenBlsChained.key_group();
+/// assert!(!verify_pop(key_group, &public_key, &proof).unwrap());
+/// ```
+pub fn verify_pop(
+    key_group: Group,
+    public_key: &[u8],
+    proof: &[u8],
+) -> Result<bool, VerificationError> {
+    match key_group {
+        Group::G1 => {
+            let public_key =
+                g1_from_variable(public_key).map_err(|err| invalid("public_key", err))?;
+            let proof = g2_from_variable(proof).map_err(|err| invalid("proof", err))?;
+            let msg_on_curve: G2Projective =
+                HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(
+                    public_key.to_compressed(),
+                    POP_DOMAIN_G2,
+                );
+            Ok(pairing(&public_key, &G2Affine::from(msg_on_curve))
+                == pairing(&G1Affine::generator(), &proof))
+        }
+        Group::G2 => {
+            let public_key =
+                g2_from_variable(public_key).map_err(|err| invalid("public_key", err))?;
+            let proof = g1_from_variable(proof).map_err(|err| invalid("proof", err))?;
+            let msg_on_curve: G1Projective =
+                HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(
+                    public_key.to_compressed(),
+                    POP_DOMAIN_G1,
+                );
+            Ok(pairing(&G1Affine::from(msg_on_curve), &public_key)
+                == pairing(&proof, &G2Affine::generator()))
+        }
+    }
+}
+
+fn invalid(field: &str, err: crate::InvalidPoint) -> VerificationError {
+    VerificationError::invalid_point(field, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn verify_pop_rejects_invalid_inputs() {
+        let pk = hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31");
+        // A valid G2 point that is not a proof for this key
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert!(!verify_pop(Group::G1, &pk, &signature).unwrap());
+
+        match verify_pop(Group::G1, &pk, &signature[..48]).unwrap_err() {
+            VerificationError::InvalidPoint { field, .. } => assert_eq!(field, "proof"),
+        }
+        match verify_pop(Group::G2, &pk, &signature).unwrap_err() {
+            VerificationError::InvalidPoint { field, .. } => assert_eq!(field, "public_key"),
+        }
+
+        // identity key and proof
+        let mut identity_g1 = [0u8; 48];
+        identity_g1[0] = 0xc0;
+        let mut identity_g2 = [0u8; 96];
+        identity_g2[0] = 0xc0;
+        match verify_pop(Group::G1, &identity_g1, &identity_g2).unwrap_err() {
+            VerificationError::InvalidPoint { field, .. } => assert_eq!(field, "public_key"),
+        }
+        match verify_pop(Group::G2, &identity_g2, &identity_g1).unwrap_err() {
+            VerificationError::InvalidPoint { field, .. } => assert_eq!(field, "public_key"),
+        }
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn verify_pop_works() {
+        use crate::signer::SecretKey;
+        use crate::{G1Pubkey, G2PubkeyRfc};
+
+        let sk = SecretKey::from_seed(b"drand-verify test key");
+        let other = SecretKey::from_seed(b"other key");
+
+        let pk = sk.public_key::<G1Pubkey>();
+        let proof = sk.prove_possession(Group::G1);
+        assert_eq!(proof.len(), 96);
+        assert!(verify_pop(Group::G1, &pk, &proof).unwrap());
+        assert!(!verify_pop(Group::G1, &pk, &other.prove_possession(Group::G1)).unwrap());
+
+        let pk = sk.public_key::<G2PubkeyRfc>();
+        let proof = sk.prove_possession(Group::G2);
+        assert_eq!(proof.len(), 48);
+        assert!(verify_pop(Group::G2, &pk, &proof).unwrap());
+        assert!(!verify_pop(Group::G2, &pk, &other.prove_possession(Group::G2)).unwrap());
+    }
+}
@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::fmt;
+
+/// The previous signature that is part of the message of a round.
+///
+/// In chained networks (pedersen-bls-chained) every round signs the signature of the previous
+/// round. In all other schemes there is no previous signature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviousSignature {
+    Chained([u8; 96]),
+    None,
+}
+
+impl PreviousSignature {
+    /// The bytes as used in the message, i.e. an empty slice for [`PreviousSignature::None`]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            PreviousSignature::Chained(signature) => signature,
+            PreviousSignature::None => &[],
+        }
+    }
+
+    pub fn is_chained(&self) -> bool {
+        matches!(self, PreviousSignature::Chained(_))
+    }
+}
+
+impl From<[u8; 96]> for PreviousSignature {
+    fn from(signature: [u8; 96]) -> Self {
+        PreviousSignature::Chained(signature)
+    }
+}
+
+/// Converts the legacy representation where an empty slice means no previous signature
+impl TryFrom<&[u8]> for PreviousSignature {
+    type Error = InvalidPreviousSignature;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        match data.len() {
+            0 => Ok(PreviousSignature::None),
+            _ => data
+                .try_into()
+                .map(PreviousSignature::Chained)
+                .map_err(|_| InvalidPreviousSignature { length: data.len() }),
+        }
+    }
+}
+
+/// The error returned when converting a slice that is neither empty nor 96 bytes long
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPreviousSignature {
+    pub length: usize,
+}
+
+impl fmt::Display for InvalidPreviousSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Previous signature must be empty or 96 bytes, actual: {}",
+            self.length
+        )
+    }
+}
+
+impl Error for InvalidPreviousSignature {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_works() {
+        assert_eq!(
+            PreviousSignature::try_from(&[][..]),
+            Ok(PreviousSignature::None)
+        );
+        assert_eq!(
+            PreviousSignature::try_from(&[7; 96][..]),
+            Ok(PreviousSignature::Chained([7; 96]))
+        );
+        assert_eq!(
+            PreviousSignature::try_from(&[7; 48][..]),
+            Err(InvalidPreviousSignature { length: 48 })
+        );
+    }
+
+    #[test]
+    fn as_bytes_works() {
+        assert_eq!(PreviousSignature::None.as_bytes(), b"");
+        assert_eq!(PreviousSignature::from([7; 96]).as_bytes(), [7; 96]);
+        assert!(PreviousSignature::from([7; 96]).is_chained());
+        assert!(!PreviousSignature::None.is_chained());
+    }
+}
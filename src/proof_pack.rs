@@ -0,0 +1,369 @@
+//! Self-contained bundles to verify a beacon without network access or prior state.
+//!
+//! A [`ProofPack`] contains the chain info of the network, the beacon and, for chained
+//! networks, optional links back to an earlier checkpoint round. Every link is verified. It can be stored or
+//! transferred to an air-gapped machine using the [`archive`](crate::archive) format.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::archive::{ArchiveError, ArchiveReader, ArchiveWriter};
+use crate::batch::DEFAULT_MAX_BATCH_SIZE;
+use crate::beacon::{check_link, Beacon, LinkError, VerifiedBeacon};
+use crate::chain_info::{ChainInfo, InvalidChainInfo};
+use crate::networks::KNOWN_NETWORKS;
+use crate::outcome::{InvalidReason, VerificationOutcome};
+use crate::scheme::Scheme;
+use crate::verify::{
+    BlsUnchainedG1Rfc9380, BlsUnchainedOnG1, DrandPubkey, PedersenBls, Pubkey, SchemeMarker,
+};
+
+/// A beacon together with everything needed to verify it.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::proof_pack::ProofPack;
+/// use drand_verify::{networks, Beacon, ChainInfo};
+/// use hex_literal::hex;
+///
+/// let pack = ProofPack {
+///     chain_info: ChainInfo::from(&networks::QUICKNET),
+///     links: vec![],
+///     beacon: Beacon {
+///         round: 123,
+///         previous_signature: vec![],
+///         signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+///     },
+/// };
+///
+/// // Transfer to an air-gapped machine
+/// let data = pack.to_bytes().unwrap();
+///
+/// let verified = ProofPack::from_bytes(&data).unwrap().verify().unwrap();
+/// assert_eq!(verified.randomness(), hex!("fb8f7bc29bf24db51871ec8c79f3a1e4bd0557bc0dfcee9ed1d924e69d1c60dc"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofPack {
+    pub chain_info: ChainInfo,
+    /// Consecutive beacons from a checkpoint round up to the round before `beacon`.
+    /// Only supported for chained networks, empty otherwise.
+    pub links: Vec<Beacon>,
+    pub beacon: Beacon,
+}
+
+#[derive(Debug)]
+pub enum ProofPackError {
+    ChainInfo(InvalidChainInfo),
+    InvalidPublicKey {
+        msg: String,
+    },
+    /// The chain hash belongs to a network known to this crate but the
+    /// chain info does not match the built-in constants
+    KnownNetworkMismatch {
+        network: &'static str,
+    },
+    /// Links were provided for an unchained network
+    LinksNotSupported,
+    Link(LinkError),
+    /// The link of the given round failed verification
+    InvalidLink {
+        round: u64,
+        reason: InvalidReason,
+    },
+    InvalidBeacon(InvalidReason),
+    /// The data does not contain a beacon
+    Empty,
+    Archive(ArchiveError),
+}
+
+impl fmt::Display for ProofPackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofPackError::ChainInfo(err) => write!(f, "Invalid chain info: {}", err),
+            ProofPackError::InvalidPublicKey { msg } => write!(f, "Invalid public key: {}", msg),
+            ProofPackError::KnownNetworkMismatch { network } => {
+                write!(f, "Chain info does not match the known network {}", network)
+            }
+            ProofPackError::LinksNotSupported => {
+                write!(f, "Links are only supported for chained networks")
+            }
+            ProofPackError::Link(err) => write!(f, "Invalid link: {}", err),
+            ProofPackError::InvalidLink { round, reason } => {
+                write!(f, "Invalid link for round {}: {}", round, reason)
+            }
+            ProofPackError::InvalidBeacon(reason) => write!(f, "Invalid beacon: {}", reason),
+            ProofPackError::Empty => write!(f, "Proof pack does not contain a beacon"),
+            ProofPackError::Archive(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ProofPackError {}
+
+impl From<ArchiveError> for ProofPackError {
+    fn from(source: ArchiveError) -> Self {
+        ProofPackError::Archive(source)
+    }
+}
+
+impl ProofPack {
+    /// Verifies the beacon and all links against the chain info and checks that the links
+    /// connect to the beacon.
+    ///
+    /// The signature of a beacon covers the bytes of the previous signature, not whether that
+    /// signature is valid. So every link is verified, using one pairing check per
+    /// [`DEFAULT_MAX_BATCH_SIZE`] beacons.
+    ///
+    /// If the chain hash is the one of a network known to this crate, the chain info must
+    /// match the built-in constants. For other networks the chain info is trusted, so compare
+    /// it to a trusted source before relying on the result.
+    pub fn verify(&self) -> Result<VerifiedBeacon, ProofPackError> {
+        let info = &self.chain_info;
+        let scheme = info.scheme().map_err(ProofPackError::ChainInfo)?;
+        if let Some(network) = KNOWN_NETWORKS
+            .iter()
+            .find(|network| network.chain_hash == info.hash)
+        {
+            let expected = ChainInfo::from(network);
+            let matches = expected.public_key == info.public_key
+                && expected.period == info.period
+                && expected.genesis_time == info.genesis_time
+                && expected.scheme_id == info.scheme_id;
+            if !matches {
+                return Err(ProofPackError::KnownNetworkMismatch {
+                    network: network.name,
+                });
+            }
+        }
+
+        if !self.links.is_empty() && !scheme.is_chained() {
+            return Err(ProofPackError::LinksNotSupported);
+        }
+        for pair in self.links.windows(2) {
+            check_link(&pair[0], &pair[1]).map_err(ProofPackError::Link)?;
+        }
+        if let Some(last) = self.links.last() {
+            check_link(last, &self.beacon).map_err(ProofPackError::Link)?;
+        }
+
+        match scheme {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+                self.verify_with::<PedersenBls>(&info.public_key)
+            }
+            Scheme::BlsUnchainedOnG1 => self.verify_with::<BlsUnchainedOnG1>(&info.public_key),
+            Scheme::BlsUnchainedG1Rfc9380 => {
+                self.verify_with::<BlsUnchainedG1Rfc9380>(&info.public_key)
+            }
+        }
+    }
+
+    fn verify_with<S: SchemeMarker>(
+        &self,
+        public_key: &[u8],
+    ) -> Result<VerifiedBeacon, ProofPackError> {
+        let pubkey = DrandPubkey::<S>::from_variable(public_key).map_err(|err| {
+            ProofPackError::InvalidPublicKey {
+                msg: err.to_string(),
+            }
+        })?;
+        for chunk in self.links.chunks(DEFAULT_MAX_BATCH_SIZE) {
+            if pubkey.verify_beacon_batch(chunk) == Ok(true) {
+                continue;
+            }
+            // Verify one by one to find the invalid link
+            for link in chunk {
+                let outcome = pubkey.verify_with_outcome(
+                    link.round,
+                    &link.previous_signature,
+                    &link.signature,
+                );
+                if let VerificationOutcome::Invalid(reason) = outcome {
+                    return Err(ProofPackError::InvalidLink {
+                        round: link.round,
+                        reason,
+                    });
+                }
+            }
+        }
+        let beacon = &self.beacon;
+        match pubkey.verify_with_outcome(
+            beacon.round,
+            &beacon.previous_signature,
+            &beacon.signature,
+        ) {
+            VerificationOutcome::Valid(beacon) => Ok(beacon),
+            VerificationOutcome::Invalid(reason) => Err(ProofPackError::InvalidBeacon(reason)),
+        }
+    }
+
+    /// The earliest round covered by this pack
+    pub fn checkpoint_round(&self) -> u64 {
+        self.links
+            .first()
+            .map_or(self.beacon.round, |link| link.round)
+    }
+
+    /// Encodes the pack as an archive with the links followed by the beacon
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ProofPackError> {
+        let mut writer = ArchiveWriter::new(Vec::new(), &self.chain_info)?;
+        for beacon in self.links.iter().chain([&self.beacon]) {
+            writer.append(beacon)?;
+        }
+        Ok(writer.finish()?)
+    }
+
+    /// Decodes a pack created by [`ProofPack::to_bytes`]
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ProofPackError> {
+        let reader = ArchiveReader::new(data)?;
+        let chain_info = reader.chain_info().clone();
+        let mut links = reader.collect::<Result<Vec<_>, _>>()?;
+        let beacon = links.pop().ok_or(ProofPackError::Empty)?;
+        Ok(Self {
+            chain_info,
+            links,
+            beacon,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::{MAINNET, QUICKNET};
+    use hex_literal::hex;
+
+    fn mainnet_pack() -> ProofPack {
+        ProofPack {
+            chain_info: ChainInfo::from(&MAINNET),
+            links: vec![Beacon {
+                round: 1336,
+                previous_signature: vec![0xaa; 96], // made up, so this link does not verify
+                signature: hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2").to_vec(),
+            }],
+            // curl -sS https://api.drand.sh/public/1337
+            beacon: Beacon {
+                round: 1337,
+                previous_signature: hex!("80d95247ddf1bb3acf5738497a5f10406be283144603f63d714bb1a44ff6b93285ae2697fffeb50c68862bd9fbecd4b204b1798d2686b4ac5d573615031d9d67e6168bde9a7adf1161430a498ca701a25c216aee3e38ffd5290369034fa050a2").to_vec(),
+                signature: hex!("945b08dcb30e24da281ccf14a646f0630ceec515af5c5895e18cc1b19edd65d156b71c776a369af3487f1bc6af1062500b059e01095cc0eedce91713977d7735cac675554edfa0d0481bb991ed93d333d08286192c05bf6b65d20f23a37fc7bb").to_vec(),
+            },
+        }
+    }
+
+    #[test]
+    fn verify_works() {
+        let mut pack = mainnet_pack();
+        let data = pack.to_bytes().unwrap();
+        assert_eq!(ProofPack::from_bytes(&data).unwrap(), pack);
+        assert_eq!(pack.checkpoint_round(), 1336);
+
+        pack.links.clear();
+        let verified = pack.verify().unwrap();
+        assert_eq!(verified.round(), 1337);
+        assert_eq!(pack.checkpoint_round(), 1337);
+    }
+
+    #[cfg(feature = "signer")]
+    #[test]
+    fn verify_checks_every_link() {
+        use crate::signer::SecretKey;
+        use crate::{ChainHash, G1Pubkey};
+
+        let sk = SecretKey::from_seed(b"proof pack");
+        let mut chain_info = ChainInfo::from(&MAINNET);
+        chain_info.public_key = sk.public_key::<G1Pubkey>();
+        chain_info.hash = ChainHash::from_hex(&"ab".repeat(32)).unwrap();
+        let s1 = sk.sign::<G1Pubkey>(1, &[0x11; 96]);
+        let s2 = sk.sign::<G1Pubkey>(2, &s1);
+        let s3 = sk.sign::<G1Pubkey>(3, &s2);
+        let beacon = |round, previous_signature: &[u8], signature: &[u8]| Beacon {
+            round,
+            previous_signature: previous_signature.to_vec(),
+            signature: signature.to_vec(),
+        };
+
+        let pack = ProofPack {
+            chain_info: chain_info.clone(),
+            links: vec![beacon(1, &[0x11; 96], &s1), beacon(2, &s1, &s2)],
+            beacon: beacon(3, &s2, &s3),
+        };
+        assert_eq!(pack.verify().unwrap().round(), 3);
+        assert_eq!(pack.checkpoint_round(), 1);
+
+        // The checkpoint is forged. Its link to round 2 is intact, but round 2 was signed
+        // over a different previous signature.
+        let forged = sk.sign::<G1Pubkey>(9, &[0x11; 96]);
+        let pack = ProofPack {
+            chain_info,
+            links: vec![beacon(1, &[0x11; 96], &forged), beacon(2, &forged, &s2)],
+            beacon: beacon(3, &s2, &s3),
+        };
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::InvalidLink { round: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn verify_rejects_broken_packs() {
+        // broken link
+        let mut pack = mainnet_pack();
+        pack.links[0].signature[0] ^= 1;
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::Link(LinkError::SignatureMismatch {
+                round: 1337
+            }))
+        ));
+
+        // unverifiable link
+        let pack = mainnet_pack();
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::InvalidLink { round: 1336, .. })
+        ));
+
+        // wrong round
+        let mut pack = mainnet_pack();
+        pack.links.clear();
+        pack.beacon.round = 1338;
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::InvalidBeacon(
+                InvalidReason::SignatureMismatch
+            ))
+        ));
+
+        // replaced key of a known network
+        let mut pack = mainnet_pack();
+        pack.chain_info.public_key = hex!("8200fc249deb0148eb918d6e213980c5d01acd7fc251900d9260136da3b54836ce125172399ddc69c4e3e11429b62c11").to_vec();
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::KnownNetworkMismatch { network: "mainnet" })
+        ));
+
+        // key does not fit the scheme
+        let mut pack = mainnet_pack();
+        pack.chain_info.public_key = QUICKNET.public_key.to_vec();
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::ChainInfo(InvalidChainInfo::KeyLength(_)))
+        ));
+
+        // links in unchained networks
+        let mut pack = mainnet_pack();
+        pack.chain_info = ChainInfo::from(&QUICKNET);
+        assert!(matches!(
+            pack.verify(),
+            Err(ProofPackError::LinksNotSupported)
+        ));
+
+        // no beacon
+        let writer = ArchiveWriter::new(Vec::new(), &ChainInfo::from(&QUICKNET)).unwrap();
+        let data = writer.finish().unwrap();
+        assert!(matches!(
+            ProofPack::from_bytes(&data),
+            Err(ProofPackError::Empty)
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+use crate::beacon::{Beacon, VerifiedBeacon};
+use crate::randomness::derive_randomness;
+use crate::verify::Pubkey;
+
+/// A key that can verify drand beacons of any scheme.
+///
+/// This is implemented for all [`Pubkey`] types and allows using them as trait objects.
+pub trait BeaconKey {
+    /// Returns true if the signature is valid for the round and previous signature.
+    /// Malformed inputs are treated as invalid.
+    fn verify_beacon(&self, round: u64, previous_signature: &[u8], signature: &[u8]) -> bool;
+}
+
+impl<P: Pubkey> BeaconKey for P {
+    fn verify_beacon(&self, round: u64, previous_signature: &[u8], signature: &[u8]) -> bool {
+        self.verify(round, previous_signature, signature)
+            .unwrap_or(false)
+    }
+}
+
+/// A beacon of a publicly verifiable randomness source.
+///
+/// This is implemented for drand beacons. Implement it for other sources (e.g. forks of
+/// the League of Entropy networks or internal beacons) to use them in code that is
+/// generic over the beacon type.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey, RandomnessBeacon};
+/// use hex_literal::hex;
+///
+/// /// Returns the randomness of the first valid beacon
+/// fn first_randomness<B: RandomnessBeacon>(beacons: &[B], key: &B::Key) -> Option<[u8; 32]> {
+///     beacons.iter().find(|b| b.verify(key)).map(|b| b.randomness())
+/// }
+///
+/// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+/// let beacon = Beacon {
+///     round: 123,
+///     previous_signature: vec![],
+///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+/// };
+/// assert!(first_randomness(&[beacon], &pk).is_some());
+/// ```
+pub trait RandomnessBeacon {
+    /// The key type the beacon is verified against
+    type Key: ?Sized;
+
+    fn round(&self) -> u64;
+
+    fn signature(&self) -> &[u8];
+
+    /// The randomness of this beacon. Defaults to the SHA-256 hash of the signature.
+    fn randomness(&self) -> [u8; 32] {
+        derive_randomness(self.signature())
+    }
+
+    /// Returns true if the beacon is valid for the given key
+    fn verify(&self, key: &Self::Key) -> bool;
+}
+
+impl RandomnessBeacon for Beacon {
+    type Key = dyn BeaconKey;
+
+    fn round(&self) -> u64 {
+        self.round
+    }
+
+    fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    fn verify(&self, key: &Self::Key) -> bool {
+        key.verify_beacon(self.round, &self.previous_signature, &self.signature)
+    }
+}
+
+impl RandomnessBeacon for VerifiedBeacon {
+    type Key = dyn BeaconKey;
+
+    fn round(&self) -> u64 {
+        VerifiedBeacon::round(self)
+    }
+
+    fn signature(&self) -> &[u8] {
+        VerifiedBeacon::signature(self)
+    }
+
+    fn randomness(&self) -> [u8; 32] {
+        VerifiedBeacon::randomness(self)
+    }
+
+    /// Verifies the beacon again, e.g. to check it against a different key
+    fn verify(&self, key: &Self::Key) -> bool {
+        key.verify_beacon(
+            VerifiedBeacon::round(self),
+            self.previous_signature(),
+            VerifiedBeacon::signature(self),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constant_time_eq_signatures, G1Pubkey, G2PubkeyRfc};
+    use hex_literal::hex;
+    use sha2::{Digest, Sha256};
+
+    const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+
+    fn beacon_123() -> Beacon {
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        Beacon {
+            round: 123,
+            previous_signature: vec![],
+            signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+        }
+    }
+
+    /// A toy beacon whose signature is the SHA-256 of a shared secret and the round
+    struct ToyBeacon {
+        round: u64,
+        signature: [u8; 32],
+    }
+
+    impl RandomnessBeacon for ToyBeacon {
+        type Key = [u8];
+
+        fn round(&self) -> u64 {
+            self.round
+        }
+
+        fn signature(&self) -> &[u8] {
+            &self.signature
+        }
+
+        fn verify(&self, key: &[u8]) -> bool {
+            let mut hasher = Sha256::new();
+            hasher.update(key);
+            hasher.update(self.round.to_be_bytes());
+            constant_time_eq_signatures(&self.signature, &hasher.finalize())
+        }
+    }
+
+    fn latest_valid<B: RandomnessBeacon>(beacons: &[B], key: &B::Key) -> Option<u64> {
+        beacons
+            .iter()
+            .filter(|beacon| beacon.verify(key))
+            .map(RandomnessBeacon::round)
+            .max()
+    }
+
+    #[test]
+    fn beacon_implements_randomness_beacon() {
+        let rfc = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        let beacon = beacon_123();
+        assert_eq!(RandomnessBeacon::round(&beacon), 123);
+        assert_eq!(beacon.randomness(), derive_randomness(&beacon.signature));
+        assert!(RandomnessBeacon::verify(&beacon, &rfc));
+
+        // Key of a different scheme
+        let pedersen = G1Pubkey::from_fixed(hex!("868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31")).unwrap();
+        assert!(!RandomnessBeacon::verify(&beacon, &pedersen));
+
+        let mut invalid = beacon.clone();
+        invalid.round = 124;
+        assert_eq!(latest_valid(&[beacon, invalid], &rfc), Some(123));
+    }
+
+    #[test]
+    fn verified_beacon_implements_randomness_beacon() {
+        let rfc = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        let beacon = beacon_123();
+        let verified = rfc
+            .verify_with_outcome(beacon.round, &beacon.previous_signature, &beacon.signature)
+            .into_verified()
+            .unwrap();
+        assert_eq!(RandomnessBeacon::round(&verified), 123);
+        assert_eq!(
+            RandomnessBeacon::randomness(&verified),
+            verified.randomness()
+        );
+        assert!(RandomnessBeacon::verify(&verified, &rfc));
+    }
+
+    #[test]
+    fn custom_beacons_can_be_used() {
+        let key = b"secret";
+        let beacons: Vec<ToyBeacon> = (1..=3)
+            .map(|round: u64| {
+                let mut hasher = Sha256::new();
+                hasher.update(key);
+                hasher.update(round.to_be_bytes());
+                ToyBeacon {
+                    round,
+                    signature: hasher.finalize().into(),
+                }
+            })
+            .collect();
+        assert_eq!(latest_valid(&beacons, key), Some(3));
+        assert_eq!(latest_valid(&beacons, b"other"), None);
+        assert_eq!(
+            beacons[0].randomness(),
+            derive_randomness(&beacons[0].signature)
+        );
+    }
+}
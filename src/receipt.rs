@@ -0,0 +1,227 @@
+//! Signed receipts attesting that a beacon was verified (feature `receipts`).
+//!
+//! A gateway that verifies beacons on behalf of downstream services can hand out a
+//! [`Receipt`] for every beacon it verified. The receipt is signed with an ed25519 key of the
+//! gateway, so the services can later audit which gateway performed the verification and when.
+//!
+//! The signed message is
+//!
+//! ```text
+//! RECEIPT_V1 || chain_hash || round || randomness || verified_at
+//! ```
+//!
+//! where `round` and `verified_at` (Unix timestamp in seconds) are 8 byte big endian integers.
+//! The randomness binds the receipt to the signature that was verified.
+
+use std::error::Error;
+use std::fmt;
+
+use ed25519_dalek::{Signer, Verifier};
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+
+use crate::beacon::VerifiedBeacon;
+use crate::chain_hash::ChainHash;
+use crate::rounds::Timestamp;
+
+/// Domain separation tag of the message signed in a [`Receipt`]
+pub const RECEIPT_V1: &str = "drand-verify/receipt/v1";
+
+/// The length of a serialized [`Receipt`] in bytes
+pub const RECEIPT_SIZE: usize = 32 + 8 + 32 + 8 + 32 + 64;
+
+/// An attestation by a verifier that it verified a round of a chain at a certain time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Receipt {
+    /// The chain hash identifying the network
+    pub chain_hash: ChainHash,
+    pub round: u64,
+    /// The randomness of the verified beacon
+    pub randomness: [u8; 32],
+    /// Unix timestamp in seconds
+    pub verified_at: u64,
+    /// The ed25519 public key of the verifier
+    pub verifier: [u8; 32],
+    /// The ed25519 signature of the verifier
+    pub signature: [u8; 64],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReceiptError {
+    /// The data does not have the length of a serialized receipt
+    InvalidLength { actual: usize },
+    /// The receipt was issued by a different verifier
+    WrongVerifier,
+    /// The ed25519 signature does not match the content of the receipt
+    InvalidSignature,
+}
+
+impl fmt::Display for ReceiptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReceiptError::InvalidLength { actual } => write!(
+                f,
+                "Invalid receipt length {}, expected {} bytes",
+                actual, RECEIPT_SIZE
+            ),
+            ReceiptError::WrongVerifier => write!(f, "Receipt was issued by a different verifier"),
+            ReceiptError::InvalidSignature => write!(f, "Invalid receipt signature"),
+        }
+    }
+}
+
+impl Error for ReceiptError {}
+
+/// Issues a receipt for a beacon verified at `verified_at`.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::receipt::{notarize, SigningKey};
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let beacon = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+///
+/// // The key of the gateway
+/// let key = SigningKey::from_bytes(&[7; 32]);
+/// let receipt = notarize(&key, networks::QUICKNET.chain_hash, &beacon, 1692803400u64);
+///
+/// // Later, by an auditor who knows the key of the gateway
+/// assert_eq!(receipt.verify(&key.verifying_key()), Ok(()));
+/// assert_eq!(receipt.round, 123);
+/// ```
+pub fn notarize(
+    key: &SigningKey,
+    chain_hash: ChainHash,
+    beacon: &VerifiedBeacon,
+    verified_at: impl Timestamp,
+) -> Receipt {
+    let mut receipt = Receipt {
+        chain_hash,
+        round: beacon.round(),
+        randomness: beacon.randomness(),
+        verified_at: verified_at.to_unix_seconds(),
+        verifier: key.verifying_key().to_bytes(),
+        signature: [0; 64],
+    };
+    receipt.signature = key.sign(&receipt.message()).to_bytes();
+    receipt
+}
+
+impl Receipt {
+    /// The message signed by the verifier
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(RECEIPT_V1.len() + 32 + 8 + 32 + 8);
+        message.extend_from_slice(RECEIPT_V1.as_bytes());
+        message.extend_from_slice(self.chain_hash.as_bytes());
+        message.extend_from_slice(&self.round.to_be_bytes());
+        message.extend_from_slice(&self.randomness);
+        message.extend_from_slice(&self.verified_at.to_be_bytes());
+        message
+    }
+
+    /// Checks that the receipt was issued by `verifier` and was not modified
+    pub fn verify(&self, verifier: &VerifyingKey) -> Result<(), ReceiptError> {
+        if self.verifier != verifier.to_bytes() {
+            return Err(ReceiptError::WrongVerifier);
+        }
+        let signature = ed25519_dalek::Signature::from_bytes(&self.signature);
+        verifier
+            .verify(&self.message(), &signature)
+            .map_err(|_| ReceiptError::InvalidSignature)
+    }
+
+    /// Serializes the receipt as the concatenation of its fields in declaration order
+    pub fn to_bytes(&self) -> [u8; RECEIPT_SIZE] {
+        let mut out = [0u8; RECEIPT_SIZE];
+        out[..32].copy_from_slice(self.chain_hash.as_bytes());
+        out[32..40].copy_from_slice(&self.round.to_be_bytes());
+        out[40..72].copy_from_slice(&self.randomness);
+        out[72..80].copy_from_slice(&self.verified_at.to_be_bytes());
+        out[80..112].copy_from_slice(&self.verifier);
+        out[112..].copy_from_slice(&self.signature);
+        out
+    }
+
+    /// The inverse of [`Receipt::to_bytes`]. The signature is not checked.
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ReceiptError> {
+        if data.len() != RECEIPT_SIZE {
+            return Err(ReceiptError::InvalidLength { actual: data.len() });
+        }
+        Ok(Self {
+            chain_hash: ChainHash::new(data[..32].try_into().unwrap()),
+            round: u64::from_be_bytes(data[32..40].try_into().unwrap()),
+            randomness: data[40..72].try_into().unwrap(),
+            verified_at: u64::from_be_bytes(data[72..80].try_into().unwrap()),
+            verifier: data[80..112].try_into().unwrap(),
+            signature: data[112..].try_into().unwrap(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::QUICKNET;
+    use crate::{G2PubkeyRfc, Pubkey};
+    use hex_literal::hex;
+
+    fn quicknet_beacon() -> VerifiedBeacon {
+        let pk = G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap();
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        pk.verify_with_outcome(123, &[], &signature)
+            .into_verified()
+            .unwrap()
+    }
+
+    #[test]
+    fn notarize_works() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let beacon = quicknet_beacon();
+        let receipt = notarize(&key, QUICKNET.chain_hash, &beacon, 1692803400u64);
+        assert_eq!(receipt.round, 123);
+        assert_eq!(receipt.randomness, beacon.randomness());
+        assert_eq!(receipt.verified_at, 1692803400);
+        assert_eq!(receipt.verify(&key.verifying_key()), Ok(()));
+
+        // ed25519 signatures are deterministic
+        let again = notarize(&key, QUICKNET.chain_hash, &beacon, 1692803400u64);
+        assert_eq!(again, receipt);
+
+        let other = SigningKey::from_bytes(&[8; 32]);
+        assert_eq!(
+            receipt.verify(&other.verifying_key()),
+            Err(ReceiptError::WrongVerifier)
+        );
+
+        let mut modified = receipt.clone();
+        modified.verified_at += 1;
+        assert_eq!(
+            modified.verify(&key.verifying_key()),
+            Err(ReceiptError::InvalidSignature)
+        );
+        let mut modified = receipt;
+        modified.round = 124;
+        assert_eq!(
+            modified.verify(&key.verifying_key()),
+            Err(ReceiptError::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn bytes_roundtrip() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let receipt = notarize(&key, QUICKNET.chain_hash, &quicknet_beacon(), 1692803400u64);
+        let bytes = receipt.to_bytes();
+        assert_eq!(&bytes[32..40], &123u64.to_be_bytes());
+        assert_eq!(Receipt::from_bytes(&bytes), Ok(receipt));
+        assert_eq!(
+            Receipt::from_bytes(&bytes[1..]),
+            Err(ReceiptError::InvalidLength {
+                actual: RECEIPT_SIZE - 1
+            })
+        );
+    }
+}
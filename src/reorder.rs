@@ -0,0 +1,225 @@
+//! Ordering of verified beacons that arrive out of order.
+//!
+//! Beacons received from several channels (e.g. gossip and HTTP) race each other, so rounds
+//! can arrive late, twice or not at all. The [`ReorderBuffer`] turns them into a strictly
+//! ordered stream without duplicates.
+//!
+//! The buffer does not read the clock. Callers pass the current [`Instant`], which keeps
+//! the behaviour deterministic and testable.
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::beacon::VerifiedBeacon;
+
+/// An item released by the [`ReorderBuffer`], in order of rounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Released {
+    Beacon(VerifiedBeacon),
+    /// The rounds `first..=last` were skipped because they did not arrive within the
+    /// gap timeout or the buffer was full
+    Skipped {
+        first: u64,
+        last: u64,
+    },
+}
+
+/// Buffers verified beacons and releases them strictly ordered by round.
+///
+/// Beacons of rounds that were released or skipped already are dropped, as are duplicates.
+/// If the next round is missing, later beacons are held back until it arrives or the gap
+/// timeout expires. At most `capacity` beacons are held back. When a beacon arrives at a full
+/// buffer, the missing rounds are skipped immediately.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use hex_literal::hex;
+/// use drand_verify::reorder::{Released, ReorderBuffer};
+/// use drand_verify::{G2PubkeyRfc, Pubkey};
+///
+/// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// let beacon = pk.verify_with_outcome(123, &[], &signature).into_verified().unwrap();
+///
+/// let mut buffer = ReorderBuffer::new(122, 100, Duration::from_secs(5));
+/// let start = Instant::now();
+///
+/// // Round 122 is missing, so round 123 is held back
+/// assert_eq!(buffer.push(beacon.clone(), start), []);
+///
+/// // After the timeout round 122 is skipped
+/// let released = buffer.poll(start + Duration::from_secs(5));
+/// assert_eq!(released, [Released::Skipped { first: 122, last: 122 }, Released::Beacon(beacon.clone())]);
+///
+/// // Duplicates are dropped
+/// assert_eq!(buffer.push(beacon, start), []);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReorderBuffer {
+    next_round: u64,
+    pending: BTreeMap<u64, VerifiedBeacon>,
+    capacity: usize,
+    gap_timeout: Duration,
+    /// Since when beacons are held back because of a missing round
+    waiting_since: Option<Instant>,
+}
+
+impl ReorderBuffer {
+    /// Creates a buffer that releases beacons starting at `next_round`.
+    ///
+    /// `capacity` is the maximum number of beacons held back. A capacity of 0 releases all
+    /// beacons immediately, skipping missing rounds.
+    pub fn new(next_round: u64, capacity: usize, gap_timeout: Duration) -> Self {
+        Self {
+            next_round,
+            pending: BTreeMap::new(),
+            capacity,
+            gap_timeout,
+            waiting_since: None,
+        }
+    }
+
+    /// The round that is released next
+    pub fn next_round(&self) -> u64 {
+        self.next_round
+    }
+
+    /// The number of beacons held back
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Adds a beacon and returns everything that can be released now
+    pub fn push(&mut self, beacon: VerifiedBeacon, now: Instant) -> Vec<Released> {
+        if beacon.round() >= self.next_round {
+            self.pending.entry(beacon.round()).or_insert(beacon);
+        }
+        let mut released = Vec::new();
+        self.release_ready(&mut released);
+        if self.pending.len() > self.capacity {
+            self.skip_gap(&mut released);
+        }
+        self.expire(now, &mut released);
+        released
+    }
+
+    /// Skips missing rounds whose gap timeout expired and returns everything that
+    /// can be released now. Call this regularly, e.g. once per period.
+    pub fn poll(&mut self, now: Instant) -> Vec<Released> {
+        let mut released = Vec::new();
+        self.expire(now, &mut released);
+        released
+    }
+
+    fn expire(&mut self, now: Instant, released: &mut Vec<Released>) {
+        match self.waiting_since {
+            Some(since) if now.saturating_duration_since(since) >= self.gap_timeout => {
+                self.skip_gap(released);
+                // Another gap starts now
+                self.waiting_since = (!self.pending.is_empty()).then(|| now);
+            }
+            Some(_) => {}
+            None => self.waiting_since = (!self.pending.is_empty()).then(|| now),
+        }
+    }
+
+    /// Skips the rounds up to the first pending beacon and releases what follows
+    fn skip_gap(&mut self, released: &mut Vec<Released>) {
+        if let Some(&first_pending) = self.pending.keys().next() {
+            released.push(Released::Skipped {
+                first: self.next_round,
+                last: first_pending - 1,
+            });
+            self.next_round = first_pending;
+            self.release_ready(released);
+        }
+    }
+
+    /// Releases consecutive beacons starting at `next_round`
+    fn release_ready(&mut self, released: &mut Vec<Released>) {
+        let mut progress = false;
+        while let Some(beacon) = self.pending.remove(&self.next_round) {
+            released.push(Released::Beacon(beacon));
+            self.next_round += 1;
+            progress = true;
+        }
+        if progress {
+            self.waiting_since = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon(round: u64) -> VerifiedBeacon {
+        VerifiedBeacon::new(round, &[], &round.to_be_bytes())
+    }
+
+    fn rounds(released: &[Released]) -> Vec<String> {
+        released
+            .iter()
+            .map(|item| match item {
+                Released::Beacon(beacon) => beacon.round().to_string(),
+                Released::Skipped { first, last } => format!("skip {}-{}", first, last),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn push_reorders_and_deduplicates() {
+        let now = Instant::now();
+        let mut buffer = ReorderBuffer::new(10, 100, Duration::from_secs(5));
+        assert!(buffer.push(beacon(12), now).is_empty());
+        assert!(buffer.push(beacon(11), now).is_empty());
+        assert!(buffer.push(beacon(12), now).is_empty());
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(rounds(&buffer.push(beacon(10), now)), ["10", "11", "12"]);
+        assert_eq!(buffer.next_round(), 13);
+        assert!(buffer.is_empty());
+
+        // old rounds are dropped
+        assert!(buffer.push(beacon(11), now).is_empty());
+        assert!(buffer.is_empty());
+        assert_eq!(rounds(&buffer.push(beacon(13), now)), ["13"]);
+    }
+
+    #[test]
+    fn gaps_time_out() {
+        let start = Instant::now();
+        let timeout = Duration::from_secs(5);
+        let mut buffer = ReorderBuffer::new(1, 100, timeout);
+        assert!(buffer.push(beacon(3), start).is_empty());
+        assert!(buffer
+            .push(beacon(5), start + Duration::from_secs(4))
+            .is_empty());
+        assert!(buffer.poll(start + Duration::from_secs(4)).is_empty());
+
+        let t = start + timeout;
+        assert_eq!(rounds(&buffer.poll(t)), ["skip 1-2", "3"]);
+        // the gap at round 4 has its own timeout
+        assert!(buffer.poll(t + Duration::from_secs(4)).is_empty());
+        assert_eq!(rounds(&buffer.poll(t + timeout)), ["skip 4-4", "5"]);
+        assert!(buffer.poll(t + timeout * 10).is_empty());
+    }
+
+    #[test]
+    fn full_buffer_skips_gap() {
+        let now = Instant::now();
+        let mut buffer = ReorderBuffer::new(1, 2, Duration::from_secs(5));
+        assert!(buffer.push(beacon(3), now).is_empty());
+        assert!(buffer.push(beacon(4), now).is_empty());
+        assert_eq!(rounds(&buffer.push(beacon(6), now)), ["skip 1-2", "3", "4"]);
+        assert_eq!(buffer.len(), 1);
+
+        let mut buffer = ReorderBuffer::new(1, 0, Duration::from_secs(5));
+        assert_eq!(rounds(&buffer.push(beacon(3), now)), ["skip 1-2", "3"]);
+    }
+}
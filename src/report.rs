@@ -0,0 +1,328 @@
+//! A diagnostics mode which reports every step of a verification.
+//!
+//! This is slower than [`Pubkey::verify`](crate::Pubkey::verify) and meant for debugging
+//! integrations, e.g. to find out whether a beacon fails because of a wrong key, a
+//! corrupted signature or a wrong round.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::scheme::Scheme;
+use crate::signature::Signature;
+use crate::verify::{message, G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// The steps of a verification
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    DecodePubkey,
+    DecodeSignature,
+    HashToCurve,
+    Pairing,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::DecodePubkey => f.write_str("decode public key"),
+            Step::DecodeSignature => f.write_str("decode signature"),
+            Step::HashToCurve => f.write_str("hash to curve"),
+            Step::Pairing => f.write_str("pairing check"),
+        }
+    }
+}
+
+/// The result of one step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    pub step: Step,
+    pub passed: bool,
+    /// The time the step took. `None` on platforms without a monotonic clock (wasm32-unknown-unknown).
+    pub duration: Option<Duration>,
+    /// Details about a failure
+    pub detail: Option<String>,
+}
+
+/// The result of [`verify_with_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    pub scheme: Scheme,
+    pub round: u64,
+    /// The executed steps in order. Verification stops after the first failed step.
+    pub steps: Vec<StepReport>,
+}
+
+impl VerificationReport {
+    /// Returns true if all steps passed
+    pub fn is_valid(&self) -> bool {
+        self.steps.len() == 4 && self.steps.iter().all(|step| step.passed)
+    }
+
+    /// The first step that failed
+    pub fn failed_step(&self) -> Option<&StepReport> {
+        self.steps.iter().find(|step| !step.passed)
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Verification of round {} ({})", self.round, self.scheme)?;
+        for step in &self.steps {
+            write!(
+                f,
+                "  {}: {}",
+                step.step,
+                if step.passed { "ok" } else { "failed" }
+            )?;
+            if let Some(duration) = step.duration {
+                write!(f, " ({:?})", duration)?;
+            }
+            if let Some(detail) = &step.detail {
+                write!(f, " - {}", detail)?;
+            }
+            writeln!(f)?;
+        }
+        write!(
+            f,
+            "Result: {}",
+            if self.is_valid() { "valid" } else { "invalid" }
+        )
+    }
+}
+
+/// Verifies a beacon from raw inputs and reports the result of every step.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::report::{verify_with_report, Step};
+/// use drand_verify::Scheme;
+/// use hex_literal::hex;
+///
+/// let pk = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+///
+/// let report = verify_with_report(Scheme::BlsUnchainedG1Rfc9380, &pk, 123, &[], &signature);
+/// assert!(report.is_valid());
+///
+/// // Wrong round
+/// let report = verify_with_report(Scheme::BlsUnchainedG1Rfc9380, &pk, 124, &[], &signature);
+/// assert_eq!(report.failed_step().unwrap().step, Step::Pairing);
+/// println!("{}", report);
+/// ```
+pub fn verify_with_report(
+    scheme: Scheme,
+    pubkey: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> VerificationReport {
+    let steps = match scheme {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+            run_steps::<G1Pubkey>(pubkey, round, previous_signature, signature)
+        }
+        Scheme::BlsUnchainedOnG1 => {
+            run_steps::<G2PubkeyFastnet>(pubkey, round, previous_signature, signature)
+        }
+        Scheme::BlsUnchainedG1Rfc9380 => {
+            run_steps::<G2PubkeyRfc>(pubkey, round, previous_signature, signature)
+        }
+    };
+    VerificationReport {
+        scheme,
+        round,
+        steps,
+    }
+}
+
+fn run_steps<P: Pubkey>(
+    pubkey: &[u8],
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Vec<StepReport> {
+    let mut steps = Vec::with_capacity(4);
+
+    let (pk, report) = timed(Step::DecodePubkey, || {
+        P::from_variable(pubkey).map_err(|err| err.to_string())
+    });
+    steps.push(report);
+    let pk = match pk {
+        Some(pk) => pk,
+        None => return steps,
+    };
+
+    let (sig, report) = timed(Step::DecodeSignature, || {
+        if signature.len() != pk.signature_size() {
+            return Err(format!(
+                "Expected {} bytes for a signature on {}, actual: {}",
+                pk.signature_size(),
+                pk.signature_group(),
+                signature.len()
+            ));
+        }
+        Signature::from_variable(signature).map_err(|err| err.to_string())
+    });
+    steps.push(report);
+    let sig = match sig {
+        Some(sig) => sig,
+        None => return steps,
+    };
+
+    let (msg_on_curve, report) = timed(Step::HashToCurve, || {
+        Ok(P::msg_to_curve(&message(round, previous_signature)))
+    });
+    steps.push(report);
+    let msg_on_curve = match msg_on_curve {
+        Some(msg_on_curve) => msg_on_curve,
+        None => return steps,
+    };
+
+    let (_, report) = timed(Step::Pairing, || {
+        match pk.verify_step2_decompressed(&sig, &msg_on_curve) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("Signature does not match public key and message".to_string()),
+            Err(err) => Err(err.to_string()),
+        }
+    });
+    steps.push(report);
+    steps
+}
+
+fn timed<T>(step: Step, f: impl FnOnce() -> Result<T, String>) -> (Option<T>, StepReport) {
+    let start = Stopwatch::start();
+    let result = f();
+    let duration = start.elapsed();
+    match result {
+        Ok(value) => (
+            Some(value),
+            StepReport {
+                step,
+                passed: true,
+                duration,
+                detail: None,
+            },
+        ),
+        Err(detail) => (
+            None,
+            StepReport {
+                step,
+                passed: false,
+                duration,
+                detail: Some(detail),
+            },
+        ),
+    }
+}
+
+/// `Instant::now` panics on wasm32-unknown-unknown, so there is no timing there
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+struct Stopwatch(std::time::Instant);
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl Stopwatch {
+    fn start() -> Self {
+        Self(std::time::Instant::now())
+    }
+
+    fn elapsed(&self) -> Option<Duration> {
+        Some(self.0.elapsed())
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+struct Stopwatch;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+impl Stopwatch {
+    fn start() -> Self {
+        Self
+    }
+
+    fn elapsed(&self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+    // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+    const SIG_QUICKNET_123: [u8; 48] = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+
+    fn steps(report: &VerificationReport) -> Vec<(Step, bool)> {
+        report
+            .steps
+            .iter()
+            .map(|step| (step.step, step.passed))
+            .collect()
+    }
+
+    #[test]
+    fn verify_with_report_works() {
+        let scheme = Scheme::BlsUnchainedG1Rfc9380;
+
+        let report = verify_with_report(scheme, &PK_QUICKNET, 123, &[], &SIG_QUICKNET_123);
+        assert!(report.is_valid());
+        assert_eq!(
+            steps(&report),
+            [
+                (Step::DecodePubkey, true),
+                (Step::DecodeSignature, true),
+                (Step::HashToCurve, true),
+                (Step::Pairing, true)
+            ]
+        );
+        assert!(report.steps.iter().all(|step| step.duration.is_some()));
+        assert_eq!(report.failed_step(), None);
+
+        // wrong key
+        let report = verify_with_report(scheme, &PK_QUICKNET[1..], 123, &[], &SIG_QUICKNET_123);
+        assert!(!report.is_valid());
+        assert_eq!(steps(&report), [(Step::DecodePubkey, false)]);
+
+        // signature of the wrong group
+        let report = verify_with_report(scheme, &PK_QUICKNET, 123, &[], &PK_QUICKNET);
+        assert_eq!(
+            steps(&report),
+            [(Step::DecodePubkey, true), (Step::DecodeSignature, false)]
+        );
+        assert_eq!(
+            report.failed_step().unwrap().detail.as_deref(),
+            Some("Expected 48 bytes for a signature on G1, actual: 96")
+        );
+
+        // wrong round
+        let report = verify_with_report(scheme, &PK_QUICKNET, 124, &[], &SIG_QUICKNET_123);
+        assert_eq!(report.failed_step().unwrap().step, Step::Pairing);
+        assert_eq!(report.steps.len(), 4);
+
+        // wrong scheme
+        let report = verify_with_report(
+            Scheme::BlsUnchainedOnG1,
+            &PK_QUICKNET,
+            123,
+            &[],
+            &SIG_QUICKNET_123,
+        );
+        assert_eq!(report.failed_step().unwrap().step, Step::Pairing);
+    }
+
+    #[test]
+    fn display_works() {
+        let report = verify_with_report(
+            Scheme::BlsUnchainedG1Rfc9380,
+            &PK_QUICKNET,
+            123,
+            &[],
+            &[0u8; 48],
+        );
+        let text = report.to_string();
+        assert!(text.starts_with("Verification of round 123 (bls-unchained-g1-rfc9380)\n"));
+        assert!(text.contains("  decode public key: ok"));
+        assert!(text.contains("  decode signature: failed"));
+        assert!(text.ends_with("Result: invalid"));
+    }
+}
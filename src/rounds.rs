@@ -0,0 +1,312 @@
+//! Conversion between round numbers and the times at which rounds are published.
+//!
+//! Times can be given as Unix timestamps in seconds (`u64`), as [`SystemTime`] and,
+//! with the features `time` and `chrono`, as `time::OffsetDateTime` and
+//! `chrono::DateTime<Utc>`.
+
+use std::marker::PhantomData;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A point in time that can be converted from and to Unix timestamps in seconds.
+///
+/// Sub-second precision is truncated. Times before the Unix epoch are treated as the epoch.
+pub trait Timestamp: Sized {
+    fn to_unix_seconds(&self) -> u64;
+
+    /// Creates the timestamp. Values that cannot be represented saturate at the maximum.
+    fn from_unix_seconds(seconds: u64) -> Self;
+}
+
+impl Timestamp for u64 {
+    fn to_unix_seconds(&self) -> u64 {
+        *self
+    }
+
+    fn from_unix_seconds(seconds: u64) -> Self {
+        seconds
+    }
+}
+
+impl Timestamp for SystemTime {
+    fn to_unix_seconds(&self) -> u64 {
+        self.duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+
+    fn from_unix_seconds(seconds: u64) -> Self {
+        // The range of SystemTime is platform specific
+        [seconds, i64::MAX as u64, u32::MAX as u64]
+            .into_iter()
+            .find_map(|seconds| UNIX_EPOCH.checked_add(Duration::from_secs(seconds)))
+            .expect("All platforms can represent u32::MAX seconds")
+    }
+}
+
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn to_unix_seconds(&self) -> u64 {
+        self.unix_timestamp().max(0) as u64
+    }
+
+    fn from_unix_seconds(seconds: u64) -> Self {
+        let max = time::PrimitiveDateTime::MAX.assume_utc().unix_timestamp();
+        let seconds = i64::try_from(seconds).unwrap_or(max).min(max);
+        time::OffsetDateTime::from_unix_timestamp(seconds).expect("Timestamp is in range")
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn to_unix_seconds(&self) -> u64 {
+        self.timestamp().max(0) as u64
+    }
+
+    fn from_unix_seconds(seconds: u64) -> Self {
+        let max = chrono::DateTime::<chrono::Utc>::MAX_UTC.timestamp();
+        let seconds = i64::try_from(seconds).unwrap_or(max).min(max);
+        chrono::DateTime::from_timestamp(seconds, 0).expect("Timestamp is in range")
+    }
+}
+
+/// Returns the latest round published at or before `time`.
+///
+/// Round 1 is published at `genesis_time`. Before that the result is 0.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::rounds::round_at;
+///
+/// // quicknet
+/// let genesis_time = 1692803367;
+/// let period = 3;
+/// assert_eq!(round_at(genesis_time, period, 1692803367u64), 1);
+/// assert_eq!(round_at(genesis_time, period, 1692803369u64), 1);
+/// assert_eq!(round_at(genesis_time, period, 1692803370u64), 2);
+/// ```
+pub fn round_at(genesis_time: u64, period: u64, time: impl Timestamp) -> u64 {
+    let time = time.to_unix_seconds();
+    if time < genesis_time || period == 0 {
+        return 0;
+    }
+    ((time - genesis_time) / period).saturating_add(1)
+}
+
+/// Returns the time at which `round` is published.
+///
+/// Round 0 does not exist; for convenience its time is the genesis time like round 1.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::rounds::round_time;
+///
+/// // quicknet
+/// let genesis_time = 1692803367;
+/// let period = 3;
+/// assert_eq!(round_time::<u64>(genesis_time, period, 1), 1692803367);
+/// assert_eq!(round_time::<u64>(genesis_time, period, 2), 1692803370);
+/// ```
+pub fn round_time<T: Timestamp>(genesis_time: u64, period: u64, round: u64) -> T {
+    let offset = round.saturating_sub(1).saturating_mul(period);
+    T::from_unix_seconds(genesis_time.saturating_add(offset))
+}
+
+/// Returns an iterator over all rounds published in the time range from `start` to `end` (inclusive)
+/// together with the time they are published at.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::rounds::rounds_between;
+///
+/// // quicknet
+/// let genesis_time = 1692803367;
+/// let period = 3;
+/// let rounds: Vec<(u64, u64)> = rounds_between(genesis_time, period, 1692803368u64, 1692803373u64).collect();
+/// assert_eq!(rounds, [(2, 1692803370), (3, 1692803373)]);
+/// ```
+pub fn rounds_between<T: Timestamp>(
+    genesis_time: u64,
+    period: u64,
+    start: impl Timestamp,
+    end: impl Timestamp,
+) -> RoundIterator<T> {
+    let start = start.to_unix_seconds().max(genesis_time);
+    let mut first = round_at(genesis_time, period, start);
+    if first == 0 || round_time::<u64>(genesis_time, period, first) < start {
+        first = first.saturating_add(1);
+    }
+    let last = round_at(genesis_time, period, end);
+    RoundIterator {
+        genesis_time,
+        period,
+        next: first,
+        last,
+        timestamp: PhantomData,
+    }
+}
+
+/// An iterator over consecutive rounds and their publishing times.
+///
+/// Created by [`rounds_between`].
+#[derive(Debug, Clone)]
+pub struct RoundIterator<T> {
+    genesis_time: u64,
+    period: u64,
+    /// The next round to be returned from the front
+    next: u64,
+    /// The last round to be returned (inclusive)
+    last: u64,
+    timestamp: PhantomData<T>,
+}
+
+impl<T: Timestamp> Iterator for RoundIterator<T> {
+    type Item = (u64, T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next > self.last || self.period == 0 {
+            return None;
+        }
+        let round = self.next;
+        // Avoid overflow when last is u64::MAX
+        if round == self.last {
+            self.last = 0;
+            self.next = 1;
+        } else {
+            self.next += 1;
+        }
+        Some((round, round_time(self.genesis_time, self.period, round)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = if self.next > self.last || self.period == 0 {
+            0
+        } else {
+            usize::try_from(self.last - self.next).map_or(usize::MAX, |len| len.saturating_add(1))
+        };
+        (len, Some(len))
+    }
+}
+
+impl<T: Timestamp> ExactSizeIterator for RoundIterator<T> {}
+
+impl<T: Timestamp> DoubleEndedIterator for RoundIterator<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.next > self.last || self.period == 0 {
+            return None;
+        }
+        let round = self.last;
+        if round == self.next {
+            self.last = 0;
+            self.next = 1;
+        } else {
+            self.last -= 1;
+        }
+        Some((round, round_time(self.genesis_time, self.period, round)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::{MAINNET, QUICKNET};
+
+    #[test]
+    fn round_at_works() {
+        let quicknet_genesis: u64 = QUICKNET.genesis_time;
+        assert_eq!(round_at(quicknet_genesis, 3, 0u64), 0);
+        assert_eq!(round_at(quicknet_genesis, 3, quicknet_genesis - 1), 0);
+        assert_eq!(round_at(quicknet_genesis, 3, quicknet_genesis), 1);
+        assert_eq!(round_at(quicknet_genesis, 3, quicknet_genesis + 2), 1);
+        assert_eq!(round_at(quicknet_genesis, 3, quicknet_genesis + 3), 2);
+        assert_eq!(
+            round_at(quicknet_genesis, 3, u64::MAX),
+            (u64::MAX - quicknet_genesis) / 3 + 1
+        );
+        assert_eq!(round_at(quicknet_genesis, 0, quicknet_genesis), 0);
+        assert_eq!(round_at(0, 1, u64::MAX), u64::MAX);
+
+        // https://api.drand.sh/public/1337 was published at 1595431050 + 1336 * 30
+        assert_eq!(round_at(MAINNET.genesis_time, 30, 1595471130u64), 1337);
+        let system_time = UNIX_EPOCH + Duration::from_secs(1595471130) + Duration::from_millis(999);
+        assert_eq!(round_at(MAINNET.genesis_time, 30, system_time), 1337);
+    }
+
+    #[test]
+    fn round_time_works() {
+        assert_eq!(round_time::<u64>(1000, 3, 0), 1000);
+        assert_eq!(round_time::<u64>(1000, 3, 1), 1000);
+        assert_eq!(round_time::<u64>(1000, 3, 2), 1003);
+        assert_eq!(round_time::<u64>(1000, 3, u64::MAX), u64::MAX);
+        assert_eq!(
+            round_time::<SystemTime>(1000, 3, 2),
+            UNIX_EPOCH + Duration::from_secs(1003)
+        );
+        let _: SystemTime = round_time(1000, 3, u64::MAX);
+    }
+
+    #[test]
+    fn rounds_between_works() {
+        let rounds: Vec<(u64, u64)> = rounds_between(1000, 3, 1000u64, 1006u64).collect();
+        assert_eq!(rounds, [(1, 1000), (2, 1003), (3, 1006)]);
+
+        // start between rounds, end between rounds
+        let rounds: Vec<(u64, u64)> = rounds_between(1000, 3, 1001u64, 1008u64).collect();
+        assert_eq!(rounds, [(2, 1003), (3, 1006)]);
+
+        // before genesis
+        let rounds: Vec<(u64, u64)> = rounds_between(1000, 3, 0u64, 1003u64).collect();
+        assert_eq!(rounds, [(1, 1000), (2, 1003)]);
+        let rounds: Vec<(u64, u64)> = rounds_between(1000, 3, 0u64, 999u64).collect();
+        assert_eq!(rounds, []);
+
+        // empty ranges
+        assert_eq!(rounds_between::<u64>(1000, 3, 1001u64, 1002u64).count(), 0);
+        assert_eq!(rounds_between::<u64>(1000, 3, 1006u64, 1000u64).count(), 0);
+
+        // reverse, size
+        let iter = rounds_between::<u64>(1000, 3, 1000u64, 1009u64);
+        assert_eq!(iter.len(), 4);
+        let rounds: Vec<u64> = iter.rev().map(|(round, _)| round).collect();
+        assert_eq!(rounds, [4, 3, 2, 1]);
+
+        // end of time
+        let mut iter = rounds_between::<u64>(1, 1, u64::MAX - 1, u64::MAX);
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some((u64::MAX - 1, u64::MAX - 1)));
+        assert_eq!(iter.next(), Some((u64::MAX, u64::MAX)));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn offset_date_time_works() {
+        use time::OffsetDateTime;
+
+        // 2025-01-01 0:00 UTC
+        let new_year = OffsetDateTime::from_unix_timestamp(1_735_689_600).unwrap();
+        let round = round_at(QUICKNET.genesis_time, 3, new_year);
+        assert_eq!(round, 14_295_412);
+        let time: OffsetDateTime = round_time(QUICKNET.genesis_time, 3, round);
+        assert_eq!(time, new_year);
+        let _: time::OffsetDateTime = round_time(QUICKNET.genesis_time, 3, u64::MAX);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono_works() {
+        use chrono::{TimeZone, Utc};
+
+        let round = round_at(
+            QUICKNET.genesis_time,
+            3,
+            Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap(),
+        );
+        assert_eq!(round, 14_295_412);
+        let time: chrono::DateTime<Utc> = round_time(QUICKNET.genesis_time, 3, round);
+        assert_eq!(time, Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+        let _: chrono::DateTime<Utc> = round_time(QUICKNET.genesis_time, 3, u64::MAX);
+    }
+}
@@ -0,0 +1,145 @@
+//! A deterministic random number generator seeded by beacon randomness (feature `rand`).
+//!
+//! [`BeaconRng`] implements [`rand_core::RngCore`], so it can be passed to the distributions
+//! of the `rand` 0.8 crate such as `Uniform` and `WeightedIndex`:
+//!
+//! ```ignore
+//! use rand::distributions::{Distribution, Uniform, WeightedIndex};
+//!
+//! let mut rng = verified_beacon.distribution_sampler();
+//! let die = Uniform::new_inclusive(1, 6).sample(&mut rng);
+//! let winner = WeightedIndex::new([10, 20, 70]).unwrap().sample(&mut rng);
+//! ```
+//!
+//! The byte stream of the generator is fixed, see [`BeaconRng`]. Note that the algorithms of
+//! the distributions belong to `rand` and may change between its major versions.
+
+use rand_core::{impls, Error, RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
+
+/// Domain separation tag of the byte stream of [`BeaconRng`]
+pub const DISTRIBUTION_SAMPLER_V1: &str = "drand-verify/distribution-sampler/v1";
+
+/// A random number generator that produces a fixed byte stream from a beacon's randomness.
+///
+/// The stream is the concatenation of the blocks
+///
+/// ```text
+/// SHA-256(DISTRIBUTION_SAMPLER_V1 || randomness || counter)
+/// ```
+///
+/// for `counter = 0, 1, 2, …` as 8 byte big endian integer. `next_u32` and `next_u64` take the
+/// next 4 or 8 bytes of the stream as a little endian integer. This construction never changes
+/// for the tag `v1`, so samples are reproducible across releases of this crate.
+///
+/// The output is public to everyone who knows the beacon. Do not use it for secrets.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::sampler::BeaconRng;
+/// use rand_core::RngCore;
+///
+/// let mut a = BeaconRng::new([7; 32]);
+/// let mut b = BeaconRng::new([7; 32]);
+/// assert_eq!(a.next_u64(), b.next_u64());
+/// ```
+#[derive(Debug, Clone)]
+pub struct BeaconRng {
+    randomness: [u8; 32],
+    counter: u64,
+    block: [u8; 32],
+    /// The next unused byte of `block`
+    position: usize,
+}
+
+impl BeaconRng {
+    pub fn new(randomness: [u8; 32]) -> Self {
+        Self {
+            randomness,
+            counter: 0,
+            block: [0; 32],
+            position: 32,
+        }
+    }
+
+    fn refill(&mut self) {
+        let mut hasher = Sha256::new();
+        hasher.update(DISTRIBUTION_SAMPLER_V1.as_bytes());
+        hasher.update(self.randomness);
+        hasher.update(self.counter.to_be_bytes());
+        self.block = hasher.finalize().into();
+        self.counter += 1;
+        self.position = 0;
+    }
+}
+
+impl RngCore for BeaconRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            if self.position == self.block.len() {
+                self.refill();
+            }
+            *byte = self.block[self.position];
+            self.position += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl SeedableRng for BeaconRng {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> Self {
+        Self::new(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn stream_is_stable() {
+        let randomness = hex!("8b676484b5fb1f37f9ec5c413d7d29883504e5b669f604a1ce68b3388e9ae3d9");
+        let block = |counter: u64| -> [u8; 32] {
+            let mut hasher = Sha256::new();
+            hasher.update(b"drand-verify/distribution-sampler/v1");
+            hasher.update(randomness);
+            hasher.update(counter.to_be_bytes());
+            hasher.finalize().into()
+        };
+
+        let mut rng = BeaconRng::new(randomness);
+        let mut stream = [0u8; 80];
+        rng.fill_bytes(&mut stream);
+        assert_eq!(stream[..32], block(0));
+        assert_eq!(stream[32..64], block(1));
+        assert_eq!(stream[64..], block(2)[..16]);
+
+        // integers continue the stream in little endian
+        let expected = u32::from_le_bytes(block(2)[16..20].try_into().unwrap());
+        assert_eq!(rng.next_u32(), expected);
+        let expected = u64::from_le_bytes(block(2)[20..28].try_into().unwrap());
+        assert_eq!(rng.next_u64(), expected);
+
+        let mut rng = BeaconRng::from_seed(randomness);
+        assert_eq!(
+            rng.next_u64(),
+            u64::from_le_bytes(block(0)[..8].try_into().unwrap())
+        );
+    }
+}
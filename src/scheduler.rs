@@ -0,0 +1,196 @@
+//! Scheduling of work around the publication of upcoming rounds.
+//!
+//! A [`Scheduler`] yields the round numbers of upcoming rounds together with the wall-clock
+//! time at which they are published, optionally shifted by a lead time. This lets applications
+//! pre-arm work right before the randomness they need becomes available.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::chain_info::ChainInfo;
+use crate::rounds::{round_at, round_time, Timestamp};
+
+/// An iterator over upcoming rounds and the times to act on them.
+///
+/// Each item is a round together with its publication time minus the lead time.
+/// The iterator is endless for networks with a period greater than 0.
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use drand_verify::scheduler::Scheduler;
+/// use drand_verify::{networks, ChainInfo};
+///
+/// let info = ChainInfo::from(&networks::QUICKNET);
+/// let now = 1735689600u64; // 2025-01-01T00:00:00Z
+///
+/// // Wake up 500ms before each round is published
+/// let mut scheduler = Scheduler::new(&info, now).with_lead(Duration::from_millis(500));
+///
+/// let (round, wake_up) = scheduler.next().unwrap();
+/// assert_eq!(round, 14295412);
+/// assert_eq!(wake_up, UNIX_EPOCH + Duration::from_secs(1735689600) - Duration::from_millis(500));
+/// assert_eq!(scheduler.next().unwrap().0, 14295413);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Scheduler {
+    genesis_time: u64,
+    period: u64,
+    next_round: u64,
+    lead: Duration,
+}
+
+impl Scheduler {
+    /// Creates a scheduler starting at the first round published after `now`.
+    ///
+    /// A round published exactly at `now` is considered upcoming.
+    pub fn new(info: &ChainInfo, now: impl Timestamp) -> Self {
+        let now = now.to_unix_seconds();
+        let latest = round_at(info.genesis_time, info.period, now);
+        let next_round = if latest == 0 {
+            1
+        } else if round_time::<u64>(info.genesis_time, info.period, latest) == now {
+            latest
+        } else {
+            latest.saturating_add(1)
+        };
+        Self::from_round(info, next_round)
+    }
+
+    /// Creates a scheduler starting at the given round, which may be in the past
+    pub fn from_round(info: &ChainInfo, round: u64) -> Self {
+        Self {
+            genesis_time: info.genesis_time,
+            period: info.period,
+            next_round: round.max(1),
+            lead: Duration::ZERO,
+        }
+    }
+
+    /// Yields times `lead` before the publication of each round
+    pub fn with_lead(mut self, lead: Duration) -> Self {
+        self.lead = lead;
+        self
+    }
+
+    /// The round returned next
+    pub fn next_round(&self) -> u64 {
+        self.next_round
+    }
+
+    /// The time at which to act on `round`, i.e. its publication time minus the lead time
+    pub fn time_for(&self, round: u64) -> SystemTime {
+        let published: SystemTime = round_time(self.genesis_time, self.period, round);
+        published.checked_sub(self.lead).unwrap_or(UNIX_EPOCH)
+    }
+
+    /// Waits until the time of the next round and returns it (feature `tokio`).
+    ///
+    /// Returns immediately if that time has passed already, so a slow consumer
+    /// catches up instead of skipping rounds.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::scheduler::Scheduler;
+    /// use drand_verify::{networks, ChainInfo};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap().block_on(async {
+    /// let info = ChainInfo::from(&networks::QUICKNET);
+    /// let mut scheduler = Scheduler::from_round(&info, 123);
+    /// let (round, _time) = scheduler.tick().await;
+    /// assert_eq!(round, 123);
+    /// # });
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub async fn tick(&mut self) -> (u64, SystemTime) {
+        let round = self.next_round;
+        let time = self.time_for(round);
+        if let Ok(remaining) = time.duration_since(SystemTime::now()) {
+            tokio::time::sleep(remaining).await;
+        }
+        self.next_round = round.saturating_add(1);
+        (round, time)
+    }
+}
+
+impl Iterator for Scheduler {
+    type Item = (u64, SystemTime);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.period == 0 || self.next_round == u64::MAX {
+            return None;
+        }
+        let round = self.next_round;
+        self.next_round += 1;
+        Some((round, self.time_for(round)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::QUICKNET;
+
+    fn seconds(time: SystemTime) -> u64 {
+        time.to_unix_seconds()
+    }
+
+    #[test]
+    fn new_works() {
+        let info = ChainInfo::from(&QUICKNET);
+        let genesis = QUICKNET.genesis_time;
+
+        // before genesis
+        let scheduler = Scheduler::new(&info, genesis - 100);
+        assert_eq!(scheduler.next_round(), 1);
+
+        // exactly at a round
+        let scheduler = Scheduler::new(&info, genesis + 3);
+        assert_eq!(scheduler.next_round(), 2);
+
+        // between rounds
+        let scheduler = Scheduler::new(&info, genesis + 4);
+        assert_eq!(scheduler.next_round(), 3);
+    }
+
+    #[test]
+    fn iterator_works() {
+        let info = ChainInfo::from(&QUICKNET);
+        let genesis = QUICKNET.genesis_time;
+
+        let rounds: Vec<(u64, u64)> = Scheduler::from_round(&info, 0)
+            .take(3)
+            .map(|(round, time)| (round, seconds(time)))
+            .collect();
+        assert_eq!(rounds, [(1, genesis), (2, genesis + 3), (3, genesis + 6)]);
+
+        let mut scheduler = Scheduler::from_round(&info, 2).with_lead(Duration::from_secs(1));
+        assert_eq!(seconds(scheduler.next().unwrap().1), genesis + 2);
+        assert_eq!(scheduler.next_round(), 3);
+
+        // lead time before the epoch
+        let scheduler = Scheduler::from_round(&info, 1).with_lead(Duration::from_secs(u64::MAX));
+        assert_eq!(scheduler.time_for(1), UNIX_EPOCH);
+
+        let mut info = info;
+        info.period = 0;
+        assert_eq!(Scheduler::from_round(&info, 1).next(), None);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tick_works() {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let info = ChainInfo::from(&QUICKNET);
+            let mut scheduler = Scheduler::from_round(&info, 10);
+            assert_eq!(scheduler.tick().await.0, 10);
+            assert_eq!(scheduler.tick().await.0, 11);
+            assert_eq!(scheduler.next_round(), 12);
+        })
+    }
+}
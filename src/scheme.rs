@@ -0,0 +1,349 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::verify::Group;
+
+/// The drand signature schemes supported by this crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// pedersen-bls-chained, verified with [`G1Pubkey`](crate::G1Pubkey)
+    PedersenBlsChained,
+    /// pedersen-bls-unchained, verified with [`G1Pubkey`](crate::G1Pubkey)
+    PedersenBlsUnchained,
+    /// bls-unchained-on-g1 (fastnet), verified with [`G2PubkeyFastnet`](crate::G2PubkeyFastnet)
+    BlsUnchainedOnG1,
+    /// bls-unchained-g1-rfc9380 (quicknet), verified with [`G2PubkeyRfc`](crate::G2PubkeyRfc)
+    BlsUnchainedG1Rfc9380,
+}
+
+impl Scheme {
+    pub const ALL: [Scheme; 4] = [
+        Scheme::PedersenBlsChained,
+        Scheme::PedersenBlsUnchained,
+        Scheme::BlsUnchainedOnG1,
+        Scheme::BlsUnchainedG1Rfc9380,
+    ];
+
+    /// Parses a scheme ID as reported by the `/info` endpoint of drand.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::Scheme;
+    ///
+    /// let scheme = Scheme::from_scheme_str("bls-unchained-g1-rfc9380").unwrap();
+    /// assert_eq!(scheme, Scheme::BlsUnchainedG1Rfc9380);
+    /// assert_eq!(scheme.pubkey_type(), "G2PubkeyRfc");
+    /// ```
+    pub fn from_scheme_str(scheme_id: &str) -> Result<Self, UnknownScheme> {
+        Self::ALL
+            .into_iter()
+            .find(|scheme| scheme.as_str() == scheme_id)
+            .ok_or_else(|| UnknownScheme {
+                scheme_id: scheme_id.to_string(),
+            })
+    }
+
+    /// The scheme ID as used by drand
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scheme::PedersenBlsChained => "pedersen-bls-chained",
+            Scheme::PedersenBlsUnchained => "pedersen-bls-unchained",
+            Scheme::BlsUnchainedOnG1 => "bls-unchained-on-g1",
+            Scheme::BlsUnchainedG1Rfc9380 => "bls-unchained-g1-rfc9380",
+        }
+    }
+
+    /// The name of the [`Pubkey`](crate::Pubkey) type that verifies beacons of this scheme.
+    ///
+    /// This helps migrating code that used the old `G2Pubkey` alias.
+    pub fn pubkey_type(self) -> &'static str {
+        match self {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => "G1Pubkey",
+            Scheme::BlsUnchainedOnG1 => "G2PubkeyFastnet",
+            Scheme::BlsUnchainedG1Rfc9380 => "G2PubkeyRfc",
+        }
+    }
+
+    /// Returns true if the message of a round includes the previous signature
+    pub const fn is_chained(self) -> bool {
+        matches!(self, Scheme::PedersenBlsChained)
+    }
+
+    /// The group on which the public keys of this scheme live
+    pub const fn key_group(self) -> Group {
+        self.signature_group().other()
+    }
+
+    /// The group on which the signatures of this scheme live
+    pub const fn signature_group(self) -> Group {
+        match self {
+            Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => Group::G2,
+            Scheme::BlsUnchainedOnG1 | Scheme::BlsUnchainedG1Rfc9380 => Group::G1,
+        }
+    }
+
+    /// Checks that a compressed public key of `len` bytes can belong to this scheme.
+    ///
+    /// Use this when reading chain info from untrusted sources to get a descriptive error
+    /// for keys and schemes that do not fit together (e.g. after the G1/G2 swap).
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use drand_verify::Scheme;
+    ///
+    /// assert!(Scheme::BlsUnchainedG1Rfc9380.check_key_length(96).is_ok());
+    /// let err = Scheme::BlsUnchainedG1Rfc9380.check_key_length(48).unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "Public key of 48 bytes does not fit scheme bls-unchained-g1-rfc9380 which uses 96 byte keys on G2. \
+    ///      48 byte keys are on G1 as used by pedersen-bls-chained, pedersen-bls-unchained."
+    /// );
+    /// ```
+    pub fn check_key_length(self, len: usize) -> Result<(), KeyLengthMismatch> {
+        if len == self.key_group().compressed_size() {
+            Ok(())
+        } else {
+            Err(KeyLengthMismatch {
+                scheme: self,
+                actual: len,
+            })
+        }
+    }
+}
+
+/// The size of a compressed signature of the given scheme in bytes
+pub const fn signature_size_for_scheme(scheme: Scheme) -> usize {
+    scheme.signature_group().compressed_size()
+}
+
+/// Checks the lengths of a compressed public key and signature without decoding any points.
+///
+/// Use this to reject malformed messages of a wire format early. The previous signature of
+/// chained schemes has the size of a signature of the same scheme.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{validate_lengths, LengthMismatch, Scheme, G1_POINT_SIZE, G2_POINT_SIZE};
+///
+/// let scheme = Scheme::BlsUnchainedG1Rfc9380;
+/// assert_eq!(validate_lengths(scheme, &[0; G2_POINT_SIZE], &[0; G1_POINT_SIZE]), Ok(()));
+/// assert_eq!(
+///     validate_lengths(scheme, &[0; G2_POINT_SIZE], &[0; 32]),
+///     Err(LengthMismatch::Signature { scheme, actual: 32 })
+/// );
+/// ```
+pub fn validate_lengths(
+    scheme: Scheme,
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<(), LengthMismatch> {
+    scheme
+        .check_key_length(public_key.len())
+        .map_err(LengthMismatch::PublicKey)?;
+    if signature.len() != signature_size_for_scheme(scheme) {
+        return Err(LengthMismatch::Signature {
+            scheme,
+            actual: signature.len(),
+        });
+    }
+    Ok(())
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Scheme {
+    type Err = UnknownScheme;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_scheme_str(s)
+    }
+}
+
+/// The error returned when parsing an unsupported scheme ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownScheme {
+    pub scheme_id: String,
+}
+
+impl fmt::Display for UnknownScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Unsupported scheme ID: {}", self.scheme_id)
+    }
+}
+
+impl Error for UnknownScheme {}
+
+/// The error returned by [`Scheme::check_key_length`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyLengthMismatch {
+    pub scheme: Scheme,
+    /// The length of the public key in bytes
+    pub actual: usize,
+}
+
+impl fmt::Display for KeyLengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let group = self.scheme.key_group();
+        write!(
+            f,
+            "Public key of {} bytes does not fit scheme {} which uses {} byte keys on {}.",
+            self.actual,
+            self.scheme,
+            group.compressed_size(),
+            group
+        )?;
+        let other = group.other();
+        if self.actual == other.compressed_size() {
+            let schemes: Vec<&str> = Scheme::ALL
+                .into_iter()
+                .filter(|scheme| scheme.key_group() == other)
+                .map(Scheme::as_str)
+                .collect();
+            write!(
+                f,
+                " {} byte keys are on {} as used by {}.",
+                self.actual,
+                other,
+                schemes.join(", ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for KeyLengthMismatch {}
+
+/// The error returned by [`validate_lengths`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LengthMismatch {
+    PublicKey(KeyLengthMismatch),
+    Signature {
+        scheme: Scheme,
+        /// The length of the signature in bytes
+        actual: usize,
+    },
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LengthMismatch::PublicKey(err) => err.fmt(f),
+            LengthMismatch::Signature { scheme, actual } => write!(
+                f,
+                "Signature of {} bytes does not fit scheme {} which uses {} byte signatures on {}.",
+                actual,
+                scheme,
+                signature_size_for_scheme(*scheme),
+                scheme.signature_group()
+            ),
+        }
+    }
+}
+
+impl Error for LengthMismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_scheme_str_works() {
+        for scheme in Scheme::ALL {
+            assert_eq!(Scheme::from_scheme_str(scheme.as_str()), Ok(scheme));
+            assert_eq!(scheme.to_string().parse::<Scheme>(), Ok(scheme));
+        }
+        assert_eq!(
+            Scheme::from_scheme_str("bls-bn254-unchained-on-g1"),
+            Err(UnknownScheme {
+                scheme_id: "bls-bn254-unchained-on-g1".to_string()
+            })
+        );
+        assert_eq!(
+            Scheme::from_scheme_str("").unwrap_err().to_string(),
+            "Unsupported scheme ID: "
+        );
+    }
+
+    #[test]
+    fn pubkey_type_works() {
+        assert_eq!(Scheme::PedersenBlsChained.pubkey_type(), "G1Pubkey");
+        assert_eq!(Scheme::PedersenBlsUnchained.pubkey_type(), "G1Pubkey");
+        assert_eq!(Scheme::BlsUnchainedOnG1.pubkey_type(), "G2PubkeyFastnet");
+        assert_eq!(Scheme::BlsUnchainedG1Rfc9380.pubkey_type(), "G2PubkeyRfc");
+    }
+
+    #[test]
+    fn check_key_length_works() {
+        assert_eq!(Scheme::PedersenBlsChained.key_group(), Group::G1);
+        assert_eq!(Scheme::PedersenBlsChained.signature_group(), Group::G2);
+        assert_eq!(Scheme::BlsUnchainedOnG1.key_group(), Group::G2);
+        assert_eq!(Scheme::BlsUnchainedOnG1.signature_group(), Group::G1);
+
+        assert_eq!(Scheme::PedersenBlsUnchained.check_key_length(48), Ok(()));
+        assert_eq!(Scheme::BlsUnchainedOnG1.check_key_length(96), Ok(()));
+        assert_eq!(
+            Scheme::BlsUnchainedG1Rfc9380.check_key_length(48),
+            Err(KeyLengthMismatch {
+                scheme: Scheme::BlsUnchainedG1Rfc9380,
+                actual: 48
+            })
+        );
+        // no hint for lengths that are not a key of any scheme
+        assert_eq!(
+            Scheme::PedersenBlsChained
+                .check_key_length(32)
+                .unwrap_err()
+                .to_string(),
+            "Public key of 32 bytes does not fit scheme pedersen-bls-chained which uses 48 byte keys on G1."
+        );
+    }
+
+    #[test]
+    fn validate_lengths_works() {
+        assert_eq!(signature_size_for_scheme(Scheme::PedersenBlsChained), 96);
+        assert_eq!(signature_size_for_scheme(Scheme::BlsUnchainedOnG1), 48);
+
+        for scheme in Scheme::ALL {
+            let key = vec![0; scheme.key_group().compressed_size()];
+            let signature = vec![0; signature_size_for_scheme(scheme)];
+            assert_eq!(validate_lengths(scheme, &key, &signature), Ok(()));
+            // swapped
+            assert!(matches!(
+                validate_lengths(scheme, &signature, &key),
+                Err(LengthMismatch::PublicKey(_))
+            ));
+        }
+
+        let err = validate_lengths(Scheme::PedersenBlsUnchained, &[0; 48], &[0; 48]).unwrap_err();
+        assert_eq!(
+            err,
+            LengthMismatch::Signature {
+                scheme: Scheme::PedersenBlsUnchained,
+                actual: 48
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Signature of 48 bytes does not fit scheme pedersen-bls-unchained which uses 96 byte signatures on G2."
+        );
+    }
+
+    #[test]
+    fn g2_pubkey_is_rfc_alias() {
+        use crate::{G2Pubkey, Pubkey};
+        use hex_literal::hex;
+
+        let pk = G2Pubkey::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        assert!(pk.verify(123, &[], &signature).unwrap());
+    }
+}
@@ -0,0 +1,163 @@
+use bls12_381::{G1Affine, G2Affine};
+use subtle::ConstantTimeEq;
+
+use crate::points::{
+    g1_from_variable, g1_non_identity, g2_from_variable, g2_non_identity, InvalidPoint,
+};
+
+/// A signature that was decompressed and checked to be a valid point of
+/// the G1 or G2 subgroup.
+///
+/// Decompression and the subgroup check are relatively expensive. If the same
+/// signature needs to be checked against multiple rounds or keys, create a
+/// `Signature` once and use [`Pubkey::verify_decompressed`](crate::Pubkey::verify_decompressed).
+///
+/// ## Examples
+///
+/// ```
+/// use hex_literal::hex;
+/// use drand_verify::{G2PubkeyFastnet, G2PubkeyRfc, Pubkey, Signature};
+///
+/// const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+///
+/// let signature = Signature::from_variable(&hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92")).unwrap();
+///
+/// // Same key, different schemes
+/// let rfc = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+/// let fastnet = G2PubkeyFastnet::from_fixed(PK_QUICKNET).unwrap();
+/// assert!(rfc.verify_decompressed(123, b"", &signature).unwrap());
+/// assert!(!fastnet.verify_decompressed(123, b"", &signature).unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature(pub(crate) SignaturePoint);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SignaturePoint {
+    G1(G1Affine),
+    G2(G2Affine),
+}
+
+impl Signature {
+    /// Decompresses a signature in compressed format.
+    ///
+    /// The group is detected by length: 48 bytes for G1 and 96 bytes for G2 signatures.
+    pub fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+        match data.len() {
+            48 => Ok(Self(SignaturePoint::G1(g1_from_variable(data)?))),
+            96 => Ok(Self(SignaturePoint::G2(g2_from_variable(data)?))),
+            actual => Err(InvalidPoint::InvalidLength {
+                expected: if actual < 96 { 48 } else { 96 },
+                actual,
+            }),
+        }
+    }
+
+    /// Decodes a signature in uncompressed format.
+    ///
+    /// The group is detected by length: 96 bytes for G1 and 192 bytes for G2 signatures.
+    pub fn from_uncompressed(data: &[u8]) -> Result<Self, InvalidPoint> {
+        let point = match data.len() {
+            96 => SignaturePoint::G1(g1_non_identity(
+                G1Affine::from_uncompressed(data.try_into().unwrap()).into(),
+            )?),
+            192 => SignaturePoint::G2(g2_non_identity(
+                G2Affine::from_uncompressed(data.try_into().unwrap()).into(),
+            )?),
+            actual => {
+                return Err(InvalidPoint::InvalidLength {
+                    expected: if actual < 192 { 96 } else { 192 },
+                    actual,
+                })
+            }
+        };
+        Ok(Self(point))
+    }
+
+    /// Returns the signature in compressed format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self.0 {
+            SignaturePoint::G1(point) => point.to_compressed().to_vec(),
+            SignaturePoint::G2(point) => point.to_compressed().to_vec(),
+        }
+    }
+
+    pub(crate) fn g1(&self) -> Option<&G1Affine> {
+        match &self.0 {
+            SignaturePoint::G1(point) => Some(point),
+            SignaturePoint::G2(_) => None,
+        }
+    }
+
+    pub(crate) fn g2(&self) -> Option<&G2Affine> {
+        match &self.0 {
+            SignaturePoint::G1(_) => None,
+            SignaturePoint::G2(point) => Some(point),
+        }
+    }
+}
+
+/// Compares two signatures (or any other byte strings like randomness values) in constant time.
+///
+/// The time depends on the lengths of the inputs but not on their content, so an attacker
+/// cannot learn how many leading bytes of a guess are correct. The crate uses it for all
+/// comparisons of signatures and randomness values, e.g. in [`check_link`](crate::check_link).
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::constant_time_eq_signatures;
+///
+/// assert!(constant_time_eq_signatures(&[1; 96], &[1; 96]));
+/// assert!(!constant_time_eq_signatures(&[1; 96], &[2; 96]));
+/// assert!(!constant_time_eq_signatures(&[1; 96], &[1; 48]));
+/// ```
+pub fn constant_time_eq_signatures(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex_literal::hex;
+
+    #[test]
+    fn constant_time_eq_signatures_works() {
+        let a = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let mut b = a;
+        assert!(constant_time_eq_signatures(&a, &b));
+        b[47] ^= 1;
+        assert!(!constant_time_eq_signatures(&a, &b));
+        assert!(!constant_time_eq_signatures(&a, &a[..47]));
+        assert!(constant_time_eq_signatures(&[], &[]));
+    }
+
+    #[test]
+    fn from_variable_works() {
+        let g1 = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let signature = Signature::from_variable(&g1).unwrap();
+        assert!(signature.g1().is_some());
+        assert!(signature.g2().is_none());
+        assert_eq!(signature.to_bytes(), g1);
+
+        let g2 = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        let signature = Signature::from_variable(&g2).unwrap();
+        assert!(signature.g1().is_none());
+        assert!(signature.g2().is_some());
+        assert_eq!(signature.to_bytes(), g2);
+
+        match Signature::from_variable(&g2[..95]).unwrap_err() {
+            InvalidPoint::InvalidLength { expected, actual } => {
+                assert_eq!(expected, 48);
+                assert_eq!(actual, 95);
+            }
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        let mut corrupted = g1;
+        corrupted[47] ^= 0x01;
+        match Signature::from_variable(&corrupted).unwrap_err() {
+            InvalidPoint::DecodingError {} => {}
+            err => panic!("Unexpected error: {:?}", err),
+        }
+    }
+}
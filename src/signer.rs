@@ -0,0 +1,162 @@
+//! Creation of drand-style signatures from a local secret key.
+//!
+//! This is intended for generating test vectors and for testing applications
+//! without a real drand network. It must not be used to run a production network.
+
+use bls12_381::{
+    hash_to_curve::{ExpandMsgXmd, HashToCurve},
+    G1Affine, G1Projective, G2Affine, G2Projective, Scalar,
+};
+use sha2::{Digest, Sha512};
+
+use crate::pop::{POP_DOMAIN_G1, POP_DOMAIN_G2};
+use crate::verify::{message, Group};
+use crate::{G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, Pubkey};
+
+/// A BLS secret key
+pub struct SecretKey(Scalar);
+
+impl SecretKey {
+    /// Deterministically derives a secret key from arbitrary seed bytes.
+    ///
+    /// Use a seed with at least 32 bytes of entropy if the key is meant to be secret.
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(&Sha512::digest(seed));
+        Self(Scalar::from_bytes_wide(&hash))
+    }
+
+    /// Returns the public key in compressed format for the scheme `P`
+    pub fn public_key<P: Sign>(&self) -> Vec<u8> {
+        P::public_key_bytes(self)
+    }
+
+    /// Signs a beacon for the scheme `P`.
+    ///
+    /// `previous_signature` should be set to an empty slice for the unchained mode.
+    pub fn sign<P: Sign>(&self, round: u64, previous_signature: &[u8]) -> Vec<u8> {
+        P::sign(self, round, previous_signature)
+    }
+
+    /// Creates the proof of possession for the public key on the given group,
+    /// see [`verify_pop`](crate::pop::verify_pop)
+    pub fn prove_possession(&self, key_group: Group) -> Vec<u8> {
+        match key_group {
+            Group::G1 => {
+                let public_key = G1Affine::from(G1Affine::generator() * self.0).to_compressed();
+                let msg_on_curve: G2Projective =
+                    HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(
+                        public_key,
+                        POP_DOMAIN_G2,
+                    );
+                G2Affine::from(msg_on_curve * self.0)
+                    .to_compressed()
+                    .to_vec()
+            }
+            Group::G2 => {
+                let public_key = G2Affine::from(G2Affine::generator() * self.0).to_compressed();
+                let msg_on_curve: G1Projective =
+                    HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(
+                        public_key,
+                        POP_DOMAIN_G1,
+                    );
+                G1Affine::from(msg_on_curve * self.0)
+                    .to_compressed()
+                    .to_vec()
+            }
+        }
+    }
+}
+
+/// Implemented by all pubkey types for which signatures can be created
+pub trait Sign: Pubkey {
+    /// The public key in compressed format
+    fn public_key_bytes(secret_key: &SecretKey) -> Vec<u8>;
+
+    /// The compressed signature of a beacon
+    fn sign(secret_key: &SecretKey, round: u64, previous_signature: &[u8]) -> Vec<u8>;
+}
+
+impl Sign for G1Pubkey {
+    fn public_key_bytes(secret_key: &SecretKey) -> Vec<u8> {
+        G1Affine::from(G1Affine::generator() * secret_key.0)
+            .to_compressed()
+            .to_vec()
+    }
+
+    fn sign(secret_key: &SecretKey, round: u64, previous_signature: &[u8]) -> Vec<u8> {
+        let msg_on_curve = Self::msg_to_curve(&message(round, previous_signature));
+        G2Affine::from(msg_on_curve.0 * secret_key.0)
+            .to_compressed()
+            .to_vec()
+    }
+}
+
+impl Sign for G2PubkeyFastnet {
+    fn public_key_bytes(secret_key: &SecretKey) -> Vec<u8> {
+        g2_public_key_bytes(secret_key)
+    }
+
+    fn sign(secret_key: &SecretKey, round: u64, previous_signature: &[u8]) -> Vec<u8> {
+        let msg_on_curve = Self::msg_to_curve(&message(round, previous_signature));
+        G1Affine::from(msg_on_curve.0 * secret_key.0)
+            .to_compressed()
+            .to_vec()
+    }
+}
+
+impl Sign for G2PubkeyRfc {
+    fn public_key_bytes(secret_key: &SecretKey) -> Vec<u8> {
+        g2_public_key_bytes(secret_key)
+    }
+
+    fn sign(secret_key: &SecretKey, round: u64, previous_signature: &[u8]) -> Vec<u8> {
+        let msg_on_curve = Self::msg_to_curve(&message(round, previous_signature));
+        G1Affine::from(msg_on_curve.0 * secret_key.0)
+            .to_compressed()
+            .to_vec()
+    }
+}
+
+fn g2_public_key_bytes(secret_key: &SecretKey) -> Vec<u8> {
+    G2Affine::from(G2Affine::generator() * secret_key.0)
+        .to_compressed()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_and_verify<P: Sign>(previous_signature: &[u8]) {
+        let sk = SecretKey::from_seed(b"drand-verify test key");
+        let pk = P::from_variable(&sk.public_key::<P>()).unwrap();
+
+        let signature = sk.sign::<P>(42, previous_signature);
+        assert!(pk.verify(42, previous_signature, &signature).unwrap());
+        assert!(!pk.verify(43, previous_signature, &signature).unwrap());
+
+        let other = SecretKey::from_seed(b"other key");
+        let signature = other.sign::<P>(42, previous_signature);
+        assert!(!pk.verify(42, previous_signature, &signature).unwrap());
+    }
+
+    #[test]
+    fn sign_works() {
+        sign_and_verify::<G1Pubkey>(b"");
+        // the previous signature must be a valid signature
+        let previous_signature = SecretKey::from_seed(b"previous").sign::<G1Pubkey>(41, b"");
+        sign_and_verify::<G1Pubkey>(&previous_signature);
+        sign_and_verify::<G2PubkeyFastnet>(b"");
+        sign_and_verify::<G2PubkeyRfc>(b"");
+    }
+
+    #[test]
+    fn from_seed_is_deterministic() {
+        let a = SecretKey::from_seed(b"seed");
+        let b = SecretKey::from_seed(b"seed");
+        let c = SecretKey::from_seed(b"seeds");
+        assert_eq!(a.public_key::<G2PubkeyRfc>(), b.public_key::<G2PubkeyRfc>());
+        assert_ne!(a.public_key::<G2PubkeyRfc>(), c.public_key::<G2PubkeyRfc>());
+    }
+}
@@ -0,0 +1,167 @@
+//! A simulated drand network for testing (feature `signer`).
+//!
+//! **The beacons created here are fake.** They are signed by a key generated locally from a
+//! seed, so they only verify against the public key of the same [`SimulatedNetwork`], never
+//! against a real network. The simulation produces beacons for any round, including rounds in
+//! the future, so application logic that schedules work around future rounds can be tested
+//! end-to-end before pointing it at a real network.
+//!
+//! ## Examples
+//!
+//! ```
+//! use drand_verify::rounds::round_at;
+//! use drand_verify::simulate::SimulatedNetwork;
+//! use drand_verify::{G2PubkeyRfc, Pubkey};
+//!
+//! let network = SimulatedNetwork::<G2PubkeyRfc>::new(b"my test network", 1692803367, 3);
+//! let pk = network.pubkey();
+//!
+//! // A round that is far in the future for a real network
+//! let round = round_at(1692803367, 3, 4_000_000_000u64);
+//! let beacon = network.beacon(round);
+//! assert!(pk.verify(beacon.round, &beacon.previous_signature, &beacon.signature).unwrap());
+//! ```
+
+use std::convert::Infallible;
+use std::marker::PhantomData;
+
+use sha2::{Digest, Sha256};
+
+use crate::beacon::Beacon;
+use crate::chain_hash::ChainHash;
+use crate::chain_info::ChainInfo;
+use crate::rounds::{self, Timestamp};
+use crate::scheme::Scheme;
+use crate::signer::{SecretKey, Sign};
+use crate::source::BeaconSource;
+
+/// Domain separation tag of the chain hash of a [`SimulatedNetwork`]
+pub const SIMULATED_CHAIN_V1: &str = "drand-verify/simulated-chain/v1";
+
+/// A fake network with a locally generated key for the pubkey type `P`.
+///
+/// Beacons are always unchained, also for [`G1Pubkey`](crate::G1Pubkey), i.e. the
+/// pedersen-bls-unchained scheme.
+pub struct SimulatedNetwork<P: Sign> {
+    secret_key: SecretKey,
+    genesis_time: u64,
+    period: u64,
+    pubkey_type: PhantomData<P>,
+}
+
+impl<P: Sign> SimulatedNetwork<P> {
+    /// Creates a network whose key is derived from `seed`. The same seed always results in the
+    /// same key and beacons.
+    pub fn new(seed: &[u8], genesis_time: u64, period: u64) -> Self {
+        Self {
+            secret_key: SecretKey::from_seed(seed),
+            genesis_time,
+            period,
+            pubkey_type: PhantomData,
+        }
+    }
+
+    /// The public key in compressed format
+    pub fn public_key(&self) -> Vec<u8> {
+        self.secret_key.public_key::<P>()
+    }
+
+    pub fn pubkey(&self) -> P {
+        P::from_variable(&self.public_key()).expect("Generated keys are valid")
+    }
+
+    /// Chain info like a relay of this network would serve it.
+    ///
+    /// The hash is SHA-256 of [`SIMULATED_CHAIN_V1`], the public key, genesis time and period,
+    /// which never collides with the hash of a real network.
+    pub fn chain_info(&self) -> ChainInfo {
+        let public_key = self.public_key();
+        let mut hasher = Sha256::new();
+        hasher.update(SIMULATED_CHAIN_V1.as_bytes());
+        hasher.update(&public_key);
+        hasher.update(self.genesis_time.to_be_bytes());
+        hasher.update(self.period.to_be_bytes());
+        let scheme = match self.pubkey().scheme() {
+            Scheme::PedersenBlsChained => Scheme::PedersenBlsUnchained,
+            other => other,
+        };
+        ChainInfo {
+            public_key,
+            period: self.period,
+            genesis_time: self.genesis_time,
+            hash: ChainHash::new(hasher.finalize().into()),
+            scheme_id: scheme.as_str().to_string(),
+            group_hash: None,
+        }
+    }
+
+    /// Creates the beacon of any round, whether it is in the past or the future
+    pub fn beacon(&self, round: u64) -> Beacon {
+        Beacon {
+            round,
+            previous_signature: vec![],
+            signature: self.secret_key.sign::<P>(round, &[]),
+        }
+    }
+
+    /// Creates the latest beacon published at or before `time`
+    pub fn beacon_at(&self, time: impl Timestamp) -> Beacon {
+        self.beacon(rounds::round_at(self.genesis_time, self.period, time))
+    }
+}
+
+/// Serves the beacons of every round
+impl<P: Sign> BeaconSource for SimulatedNetwork<P> {
+    type Error = Infallible;
+
+    fn beacon(&mut self, round: u64) -> Result<Beacon, Infallible> {
+        Ok(SimulatedNetwork::beacon(self, round))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::QUICKNET;
+    use crate::{G1Pubkey, G2PubkeyRfc, Pubkey};
+
+    #[test]
+    fn simulated_network_works() {
+        let mut network = SimulatedNetwork::<G2PubkeyRfc>::new(b"simulation", 1000, 3);
+        let pk = network.pubkey();
+        let beacon = network.beacon_at(1007u64);
+        assert_eq!(beacon.round, 3);
+        assert!(pk.verify(3, &[], &beacon.signature).unwrap());
+        assert_eq!(BeaconSource::beacon(&mut network, 3), Ok(beacon.clone()));
+
+        let info = network.chain_info();
+        assert_eq!(info.scheme(), Ok(Scheme::BlsUnchainedG1Rfc9380));
+        assert_eq!((info.genesis_time, info.period), (1000, 3));
+        assert_eq!(info.round_at(1007u64), 3);
+
+        // the beacons do not verify against a real network
+        let quicknet = G2PubkeyRfc::from_variable(QUICKNET.public_key).unwrap();
+        assert!(!quicknet.verify(3, &[], &beacon.signature).unwrap());
+
+        // deterministic
+        let again = SimulatedNetwork::<G2PubkeyRfc>::new(b"simulation", 1000, 3);
+        assert_eq!(again.beacon(3), beacon);
+        assert_eq!(again.chain_info(), info);
+        let other = SimulatedNetwork::<G2PubkeyRfc>::new(b"simulation", 1000, 30);
+        assert_ne!(other.chain_info().hash, info.hash);
+    }
+
+    #[test]
+    fn g1_networks_are_unchained() {
+        let network = SimulatedNetwork::<G1Pubkey>::new(b"simulation", 1000, 30);
+        let beacon = network.beacon(u64::MAX);
+        assert!(network
+            .pubkey()
+            .verify(beacon.round, &beacon.previous_signature, &beacon.signature)
+            .unwrap());
+        assert_eq!(
+            network.chain_info().scheme(),
+            Ok(Scheme::PedersenBlsUnchained)
+        );
+    }
+}
@@ -0,0 +1,20 @@
+use crate::beacon::Beacon;
+
+/// Something beacons can be loaded from, like a relay client, an archive or a database.
+///
+/// This crate does no networking. Implement this trait for your client to use the
+/// utilities working on sources.
+pub trait BeaconSource {
+    type Error;
+
+    /// Loads the beacon of the given round
+    fn beacon(&mut self, round: u64) -> Result<Beacon, Self::Error>;
+}
+
+impl<S: BeaconSource + ?Sized> BeaconSource for &mut S {
+    type Error = S::Error;
+
+    fn beacon(&mut self, round: u64) -> Result<Beacon, Self::Error> {
+        (**self).beacon(round)
+    }
+}
@@ -0,0 +1,288 @@
+//! Persistence of verified beacons.
+//!
+//! A [`BeaconStore`] keeps beacons that were verified before together with a checkpoint,
+//! such that services know where to continue after a restart.
+//!
+//! Anything with write access to the storage location can change its contents, so stores
+//! return a [`StoredBeacon`]. Verify it again with [`StoredBeacon::verify`] before use.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::beacon::{StoredBeacon, VerifiedBeacon};
+
+/// Storage for verified beacons and a checkpoint
+pub trait BeaconStore {
+    type Error;
+
+    /// Stores a verified beacon. Storing the same round again overrides the existing entry.
+    fn put(&mut self, beacon: &VerifiedBeacon) -> Result<(), Self::Error>;
+
+    /// Loads the beacon of the given round if it was stored before
+    fn get(&self, round: u64) -> Result<Option<StoredBeacon>, Self::Error>;
+
+    /// The latest stored beacon
+    fn latest(&self) -> Result<Option<StoredBeacon>, Self::Error>;
+
+    /// The round up to which the history was verified, if set
+    fn checkpoint(&self) -> Result<Option<u64>, Self::Error>;
+
+    fn set_checkpoint(&mut self, round: u64) -> Result<(), Self::Error>;
+}
+
+/// A store keeping everything in memory
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    beacons: BTreeMap<u64, StoredBeacon>,
+    checkpoint: Option<u64>,
+}
+
+impl MemoryStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl BeaconStore for MemoryStore {
+    type Error = std::convert::Infallible;
+
+    fn put(&mut self, beacon: &VerifiedBeacon) -> Result<(), Self::Error> {
+        self.beacons.insert(beacon.round(), beacon.into());
+        Ok(())
+    }
+
+    fn get(&self, round: u64) -> Result<Option<StoredBeacon>, Self::Error> {
+        Ok(self.beacons.get(&round).cloned())
+    }
+
+    fn latest(&self) -> Result<Option<StoredBeacon>, Self::Error> {
+        Ok(self.beacons.values().next_back().cloned())
+    }
+
+    fn checkpoint(&self) -> Result<Option<u64>, Self::Error> {
+        Ok(self.checkpoint)
+    }
+
+    fn set_checkpoint(&mut self, round: u64) -> Result<(), Self::Error> {
+        self.checkpoint = Some(round);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum StoreError {
+    Io(io::Error),
+    /// The storage contains data that cannot be parsed
+    Corrupted {
+        line: usize,
+        msg: String,
+    },
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(err) => write!(f, "I/O error: {}", err),
+            StoreError::Corrupted { line, msg } => {
+                write!(f, "Corrupted store in line {}: {}", line, msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<io::Error> for StoreError {
+    fn from(source: io::Error) -> Self {
+        StoreError::Io(source)
+    }
+}
+
+/// A store persisting beacons in a directory.
+///
+/// Beacons are appended to the text file `beacons` (one `<round> <previous_signature> <signature>`
+/// line in hex per beacon) and kept in memory for lookups. The checkpoint is stored in the file `checkpoint`.
+#[derive(Debug)]
+pub struct FileStore {
+    dir: PathBuf,
+    file: File,
+    memory: MemoryStore,
+}
+
+const BEACONS_FILE: &str = "beacons";
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+impl FileStore {
+    /// Opens the store in the given directory, creating it if needed
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self, StoreError> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+
+        let mut memory = MemoryStore::new();
+        let path = dir.join(BEACONS_FILE);
+        if path.exists() {
+            for (index, line) in BufReader::new(File::open(&path)?).lines().enumerate() {
+                let line = line?;
+                if line.is_empty() {
+                    continue;
+                }
+                let beacon = parse_line(&line).map_err(|msg| StoreError::Corrupted {
+                    line: index + 1,
+                    msg,
+                })?;
+                memory.beacons.insert(beacon.round, beacon);
+            }
+        }
+
+        let checkpoint_path = dir.join(CHECKPOINT_FILE);
+        if checkpoint_path.exists() {
+            let content = fs::read_to_string(&checkpoint_path)?;
+            let round = content
+                .trim()
+                .parse::<u64>()
+                .map_err(|err| StoreError::Corrupted {
+                    line: 1,
+                    msg: err.to_string(),
+                })?;
+            memory.checkpoint = Some(round);
+        }
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { dir, file, memory })
+    }
+}
+
+fn parse_line(line: &str) -> Result<StoredBeacon, String> {
+    let mut parts = line.split(' ');
+    let (round, previous_signature, signature) =
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(round), Some(previous_signature), Some(signature), None) => {
+                (round, previous_signature, signature)
+            }
+            _ => return Err("Expected 3 fields".to_string()),
+        };
+    let round = round.parse::<u64>().map_err(|err| err.to_string())?;
+    let previous_signature = hex::decode(previous_signature).map_err(|err| err.to_string())?;
+    let signature = hex::decode(signature).map_err(|err| err.to_string())?;
+    Ok(StoredBeacon {
+        round,
+        previous_signature,
+        signature,
+    })
+}
+
+impl BeaconStore for FileStore {
+    type Error = StoreError;
+
+    fn put(&mut self, beacon: &VerifiedBeacon) -> Result<(), Self::Error> {
+        writeln!(
+            self.file,
+            "{} {} {}",
+            beacon.round(),
+            hex::encode(beacon.previous_signature()),
+            hex::encode(beacon.signature())
+        )?;
+        self.file.flush()?;
+        self.memory.beacons.insert(beacon.round(), beacon.into());
+        Ok(())
+    }
+
+    fn get(&self, round: u64) -> Result<Option<StoredBeacon>, Self::Error> {
+        Ok(self.memory.beacons.get(&round).cloned())
+    }
+
+    fn latest(&self) -> Result<Option<StoredBeacon>, Self::Error> {
+        Ok(self.memory.beacons.values().next_back().cloned())
+    }
+
+    fn checkpoint(&self) -> Result<Option<u64>, Self::Error> {
+        Ok(self.memory.checkpoint)
+    }
+
+    fn set_checkpoint(&mut self, round: u64) -> Result<(), Self::Error> {
+        // Write to a temporary file first such that the checkpoint is replaced atomically
+        let tmp = self.dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        fs::write(&tmp, round.to_string())?;
+        fs::rename(&tmp, self.dir.join(CHECKPOINT_FILE))?;
+        self.memory.checkpoint = Some(round);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beacon(round: u64) -> VerifiedBeacon {
+        VerifiedBeacon::new(round, &[], &[round as u8; 48])
+    }
+
+    fn stored(round: u64) -> Option<StoredBeacon> {
+        Some(StoredBeacon::from(&beacon(round)))
+    }
+
+    fn exercise<S: BeaconStore>(store: &mut S)
+    where
+        S::Error: fmt::Debug,
+    {
+        assert_eq!(store.get(1).unwrap(), None);
+        assert_eq!(store.latest().unwrap(), None);
+        assert_eq!(store.checkpoint().unwrap(), None);
+
+        store.put(&beacon(2)).unwrap();
+        store.put(&beacon(1)).unwrap();
+        assert_eq!(store.get(1).unwrap(), stored(1));
+        assert_eq!(store.get(2).unwrap(), stored(2));
+        assert_eq!(store.get(3).unwrap(), None);
+        assert_eq!(store.latest().unwrap(), stored(2));
+
+        store.set_checkpoint(2).unwrap();
+        assert_eq!(store.checkpoint().unwrap(), Some(2));
+    }
+
+    #[test]
+    fn memory_store_works() {
+        exercise(&mut MemoryStore::new());
+    }
+
+    #[test]
+    fn file_store_works() {
+        let dir = std::env::temp_dir().join(format!("drand-verify-store-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        exercise(&mut FileStore::open(&dir).unwrap());
+
+        // reopen
+        let mut store = FileStore::open(&dir).unwrap();
+        assert_eq!(store.get(1).unwrap(), stored(1));
+        assert_eq!(store.latest().unwrap(), stored(2));
+        assert_eq!(store.checkpoint().unwrap(), Some(2));
+        store.put(&beacon(3)).unwrap();
+        let store = FileStore::open(&dir).unwrap();
+        assert_eq!(store.latest().unwrap(), stored(3));
+
+        // modified by someone else
+        use crate::Pubkey;
+
+        let pk = crate::G2PubkeyRfc::from_variable(crate::networks::QUICKNET.public_key).unwrap();
+        let signature = "b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92";
+        fs::write(dir.join(BEACONS_FILE), format!("123  {}\n", signature)).unwrap();
+        let store = FileStore::open(&dir).unwrap();
+        assert!(store.get(123).unwrap().unwrap().verify(&pk).is_valid());
+        fs::write(dir.join(BEACONS_FILE), format!("124  {}\n", signature)).unwrap();
+        let store = FileStore::open(&dir).unwrap();
+        assert!(!store.get(124).unwrap().unwrap().verify(&pk).is_valid());
+
+        // corrupted
+        fs::write(dir.join(BEACONS_FILE), "1 zz 00\n").unwrap();
+        match FileStore::open(&dir).unwrap_err() {
+            StoreError::Corrupted { line, .. } => assert_eq!(line, 1),
+            err => panic!("Unexpected error: {:?}", err),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
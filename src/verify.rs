@@ -1,16 +1,23 @@
 use bls12_381::{
     hash_to_curve::{ExpandMsgXmd, HashToCurve},
-    Bls12, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective,
+    pairing, Bls12, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Scalar,
 };
-use pairing::{group::Group, MultiMillerLoop};
+use pairing::{group::Group as _, MultiMillerLoop};
 use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Add;
 
+use crate::beacon::{Beacon, VerifiedBeacon};
+use crate::outcome::{InvalidReason, VerificationOutcome};
 use crate::points::{
-    g1_from_fixed, g1_from_fixed_unchecked, g1_from_variable, g2_from_fixed,
-    g2_from_fixed_unchecked, g2_from_variable, InvalidPoint,
+    code_for_message, g1_from_fixed, g1_from_fixed_unchecked, g1_from_variable, g2_from_fixed,
+    g2_from_fixed_unchecked, g2_from_variable, InvalidPoint, UncheckedCapability,
 };
+use crate::previous_signature::PreviousSignature;
+use crate::scheme::Scheme;
+use crate::signature::Signature;
 
 // See https://github.com/drand/kyber-bls12381/issues/22 and
 // https://github.com/drand/drand/pull/1249
@@ -18,10 +25,68 @@ const DOMAIN_HASH_TO_G2: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
 const DOMAIN_HASH_TO_G1: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
 
 /// Point on G1
-pub struct G1(G1Affine);
+pub struct G1(pub(crate) G1Affine);
 
 /// Point on G2
-pub struct G2(G2Affine);
+pub struct G2(pub(crate) G2Affine);
+
+/// Adds messages hashed to the curve, see [`aggregate`](crate::aggregate)
+impl Add for G1 {
+    type Output = G1;
+
+    fn add(self, other: G1) -> G1 {
+        G1((G1Projective::from(self.0) + other.0).into())
+    }
+}
+
+/// Adds messages hashed to the curve, see [`aggregate`](crate::aggregate)
+impl Add for G2 {
+    type Output = G2;
+
+    fn add(self, other: G2) -> G2 {
+        G2((G2Projective::from(self.0) + other.0).into())
+    }
+}
+
+/// The size of a compressed G1 point in bytes
+pub const G1_POINT_SIZE: usize = 48;
+
+/// The size of a compressed G2 point in bytes
+pub const G2_POINT_SIZE: usize = 96;
+
+/// The two source groups of the BLS12-381 pairing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Group {
+    G1,
+    G2,
+}
+
+impl Group {
+    /// The size of a point of this group in compressed format in bytes
+    pub const fn compressed_size(self) -> usize {
+        match self {
+            Group::G1 => G1_POINT_SIZE,
+            Group::G2 => G2_POINT_SIZE,
+        }
+    }
+
+    /// The other group
+    pub const fn other(self) -> Group {
+        match self {
+            Group::G1 => Group::G2,
+            Group::G2 => Group::G1,
+        }
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Group::G1 => f.write_str("G1"),
+            Group::G2 => f.write_str("G2"),
+        }
+    }
+}
 
 pub trait Pubkey: Sized {
     /// The curve (G1 or G2) on which the public key lives
@@ -35,9 +100,39 @@ pub trait Pubkey: Sized {
 
     fn msg_to_curve(msg: &[u8]) -> Self::Other;
 
+    /// The scheme of the beacons verified with this key.
+    ///
+    /// [`G1Pubkey`] verifies both pedersen-bls-chained and pedersen-bls-unchained beacons
+    /// and reports the chained scheme.
+    fn scheme(&self) -> Scheme;
+
+    /// The scheme ID as used by drand, see [`Pubkey::scheme`]
+    fn scheme_id(&self) -> &'static str {
+        self.scheme().as_str()
+    }
+
+    /// The group on which signatures live
+    fn signature_group(&self) -> Group;
+
+    /// The size of this key in compressed format in bytes
+    fn key_size(&self) -> usize {
+        self.signature_group().other().compressed_size()
+    }
+
+    /// The size of a signature in compressed format in bytes
+    fn signature_size(&self) -> usize {
+        self.signature_group().compressed_size()
+    }
+
     fn from_fixed(data: Self::ThisCompressed) -> Result<Self, InvalidPoint>;
 
-    fn from_fixed_unchecked(data: Self::ThisCompressed) -> Result<Self, InvalidPoint>;
+    /// Like [`Pubkey::from_fixed`] but without the subgroup check.
+    ///
+    /// The capability can only be created with [`assume_inputs_valid`](crate::assume_inputs_valid).
+    fn from_fixed_unchecked(
+        data: Self::ThisCompressed,
+        capability: UncheckedCapability,
+    ) -> Result<Self, InvalidPoint>;
 
     fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint>;
 
@@ -49,21 +144,699 @@ pub trait Pubkey: Sized {
         msg_on_curve: &Self::Other,
     ) -> Result<bool, VerificationError>;
 
+    /// Like [`Pubkey::verify_step2`] but without heap allocations, see [`Pubkey::verify_no_alloc`].
+    fn verify_step2_no_alloc(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, ErrorCode>;
+
+    /// Like [`Pubkey::verify_step2`] but with a signature that was decompressed already.
+    fn verify_step2_decompressed(
+        &self,
+        signature: &Signature,
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError>;
+
     /// The high level verification method for a drand beacon.
     ///
     /// `previous_signature` should be set to an empty slice for the unchained mode.
+    /// See [`Pubkey::verify_round`] for a variant that makes this explicit.
+    ///
+    /// A non-empty previous signature must be a valid point of the signature group, except in
+    /// round 1 where it is the genesis seed of the chain. Otherwise an error for the field
+    /// `previous_signature` is returned.
+    ///
+    /// Malformed inputs result in an error or `Ok(false)`, never in a panic.
     fn verify(
         &self,
         round: u64,
         previous_signature: &[u8],
         signature: &[u8],
     ) -> Result<bool, VerificationError> {
-        let msg = message(round, previous_signature);
-        let msg_on_curve = Self::msg_to_curve(&msg);
-        self.verify_step2(signature, &msg_on_curve)
+        let result = check_previous_signature(self.signature_group(), round, previous_signature)
+            .and_then(|()| {
+                let msg = message(round, previous_signature);
+                let msg_on_curve = Self::msg_to_curve(&msg);
+                self.verify_step2(signature, &msg_on_curve)
+            });
+        audited(self.scheme(), round, previous_signature, signature, result)
+    }
+
+    /// Like [`Pubkey::verify`] but with the previous signature as a [`PreviousSignature`]
+    /// instead of a slice which must be empty for unchained networks.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use hex_literal::hex;
+    /// use drand_verify::{G2PubkeyRfc, PreviousSignature, Pubkey};
+    ///
+    /// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+    /// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    /// assert!(pk.verify_round(123, &PreviousSignature::None, &signature).unwrap());
+    /// ```
+    fn verify_round(
+        &self,
+        round: u64,
+        previous_signature: &PreviousSignature,
+        signature: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.verify(round, previous_signature.as_bytes(), signature)
+    }
+
+    /// Like [`Pubkey::verify`] but uses only stack memory, including for errors.
+    ///
+    /// This avoids heap allocations during verification, e.g. for callers with a fixed memory
+    /// budget. The crate itself requires `std`, so this does not make it usable on `no_std`
+    /// targets. It computes two separate pairings instead of one multi Miller loop, which makes
+    /// it slower than [`Pubkey::verify`]. Rejected inputs are passed to the audit log sink
+    /// (feature `audit-log`) if one is registered, which allocates.
+    fn verify_no_alloc(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, ErrorCode> {
+        let result =
+            check_previous_signature_no_alloc(self.signature_group(), round, previous_signature)
+                .map_err(|err| err.code_for("previous_signature"))
+                .and_then(|()| {
+                    let msg = message_fixed(round, previous_signature);
+                    let msg_on_curve = Self::msg_to_curve(&msg);
+                    self.verify_step2_no_alloc(signature, &msg_on_curve)
+                });
+        audited(self.scheme(), round, previous_signature, signature, result)
+    }
+
+    /// Like [`Pubkey::verify`] but returns a typed outcome which contains the verified beacon
+    /// or the reason why the beacon is invalid.
+    fn verify_with_outcome(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> VerificationOutcome {
+        match self.verify(round, previous_signature, signature) {
+            Ok(true) => VerificationOutcome::Valid(VerifiedBeacon::new(
+                round,
+                previous_signature,
+                signature,
+            )),
+            Ok(false) => VerificationOutcome::Invalid(InvalidReason::SignatureMismatch),
+            Err(err) => VerificationOutcome::Invalid(err.into()),
+        }
+    }
+
+    /// Like [`Pubkey::verify`] but takes the round, previous signature and signature from
+    /// the beacon, e.g. as deserialized from the `/public/{round}` endpoint.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use hex_literal::hex;
+    /// use drand_verify::{Beacon, G2PubkeyRfc, Pubkey};
+    ///
+    /// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+    /// let beacon = Beacon {
+    ///     round: 123,
+    ///     previous_signature: vec![],
+    ///     signature: hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").to_vec(),
+    /// };
+    /// assert!(pk.verify_beacon(&beacon).unwrap());
+    /// ```
+    fn verify_beacon(&self, beacon: &Beacon) -> Result<bool, VerificationError> {
+        self.verify(beacon.round, &beacon.previous_signature, &beacon.signature)
+    }
+
+    /// Like [`Pubkey::verify`] but builds the message of the round with `H` instead of
+    /// SHA-256. `verify_with_hash::<Sha256>` is the same as [`Pubkey::verify`].
+    ///
+    /// This is meant for experimental networks. All public drand networks use SHA-256.
+    /// See [`MessageHash`] for how to implement another hash.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use hex_literal::hex;
+    /// use drand_verify::{G2PubkeyRfc, Pubkey};
+    /// use sha2::Sha256;
+    ///
+    /// let pk = G2PubkeyRfc::from_fixed(hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a")).unwrap();
+    /// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    /// assert!(pk.verify_with_hash::<Sha256>(123, &[], &signature).unwrap());
+    /// ```
+    fn verify_with_hash<H: MessageHash>(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<bool, VerificationError> {
+        let result = check_previous_signature(self.signature_group(), round, previous_signature)
+            .and_then(|()| {
+                let msg = H::hash_message(round, previous_signature);
+                let msg_on_curve = Self::msg_to_curve(&msg);
+                self.verify_step2(signature, &msg_on_curve)
+            });
+        audited(self.scheme(), round, previous_signature, signature, result)
+    }
+
+    /// Like [`Pubkey::verify`] but with a signature that was decompressed already.
+    ///
+    /// Use this to check one signature against multiple rounds or keys without
+    /// decompressing it multiple times.
+    fn verify_decompressed(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &Signature,
+    ) -> Result<bool, VerificationError> {
+        let result = check_previous_signature(self.signature_group(), round, previous_signature)
+            .and_then(|()| {
+                let msg = message(round, previous_signature);
+                let msg_on_curve = Self::msg_to_curve(&msg);
+                self.verify_step2_decompressed(signature, &msg_on_curve)
+            });
+        if matches!(result, Ok(true)) {
+            return result;
+        }
+        audited(
+            self.scheme(),
+            round,
+            previous_signature,
+            &signature.to_bytes(),
+            result,
+        )
+    }
+}
+
+/// A public key of the drand scheme `S`.
+///
+/// Use the aliases [`G1Pubkey`], [`G2PubkeyFastnet`] and [`G2PubkeyRfc`] to refer to
+/// the pubkey type of a specific scheme.
+pub struct DrandPubkey<S: SchemeMarker> {
+    point: S::Key,
+    scheme: PhantomData<S>,
+}
+
+impl<S: SchemeMarker> DrandPubkey<S> {
+    fn new(point: S::Key) -> Self {
+        Self {
+            point,
+            scheme: PhantomData,
+        }
+    }
+
+    /// Checks the previous signature, decodes the signature and hashes the message of a
+    /// beacon. Returns `(σ, H)` for [`DrandPubkey::combined_pairing_check_no_alloc`].
+    pub(crate) fn prepare_no_alloc(
+        &self,
+        round: u64,
+        previous_signature: &[u8],
+        signature: &[u8],
+    ) -> Result<(<Self as Pubkey>::Other, <Self as Pubkey>::Other), ErrorCode> {
+        check_previous_signature_no_alloc(self.signature_group(), round, previous_signature)
+            .map_err(|err| err.code_for("previous_signature"))?;
+        let sigma =
+            S::Key::signature_from_variable(signature).map_err(|err| err.code_for("signature"))?;
+        let msg = message_fixed(round, previous_signature);
+        Ok((sigma, Self::msg_to_curve(&msg)))
+    }
+
+    /// Checks `e(Σ r_i σ_i, g) == e(Σ r_i H_i, pk)` using only stack memory
+    pub(crate) fn combined_pairing_check_no_alloc<'a, I>(&self, entries: I) -> bool
+    where
+        I: Iterator<
+            Item = (
+                &'a <Self as Pubkey>::Other,
+                &'a <Self as Pubkey>::Other,
+                Scalar,
+            ),
+        >,
+        <Self as Pubkey>::Other: 'a,
+    {
+        let (sigma, msg_on_curve) = S::Key::combine(entries);
+        self.point.pairing_check_no_alloc(&sigma, &msg_on_curve)
+    }
+
+    /// Like [`DrandPubkey::combined_pairing_check_no_alloc`] with both pairings computed in
+    /// one multi-Miller loop and a single final exponentiation
+    pub(crate) fn combined_pairing_check<'a, I>(&self, entries: I) -> bool
+    where
+        I: Iterator<
+            Item = (
+                &'a <Self as Pubkey>::Other,
+                &'a <Self as Pubkey>::Other,
+                Scalar,
+            ),
+        >,
+        <Self as Pubkey>::Other: 'a,
+    {
+        let (sigma, msg_on_curve) = S::Key::combine(entries);
+        self.point.pairing_check(&sigma, &msg_on_curve)
+    }
+}
+
+impl<S: SchemeMarker> DrandPubkey<S> {
+    /// Precomputes the parts of the pairing check that only depend on this key,
+    /// see [`PreparedPubkey`]
+    pub fn prepare(self) -> PreparedPubkey<S> {
+        let prepared = self.point.prepare();
+        PreparedPubkey {
+            pubkey: self,
+            prepared,
+        }
+    }
+}
+
+/// A [`DrandPubkey`] with precomputed Miller loop tables for verifying many beacons.
+///
+/// For keys on G2 ([`G2PubkeyFastnet`] and [`G2PubkeyRfc`]), the G2 inputs of the pairing
+/// check are the generator and the key, which are the same for every beacon. Preparing them
+/// once saves this work in every verification. For keys on G1 ([`G1Pubkey`]), the G2 inputs
+/// are the signature and the message, so there is nothing to precompute and verification
+/// works like with the plain key.
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{networks, G2PubkeyRfc, Pubkey};
+/// use hex_literal::hex;
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key)
+///     .unwrap()
+///     .prepare();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// assert!(pk.verify(123, &[], &signature).unwrap());
+/// assert!(!pk.verify(124, &[], &signature).unwrap());
+/// ```
+pub struct PreparedPubkey<S: SchemeMarker> {
+    pubkey: DrandPubkey<S>,
+    prepared: <S::Key as KeyGroupImpl>::Prepared,
+}
+
+impl<S: SchemeMarker> PreparedPubkey<S> {
+    pub fn inner(&self) -> &DrandPubkey<S> {
+        &self.pubkey
+    }
+
+    pub fn into_inner(self) -> DrandPubkey<S> {
+        self.pubkey
+    }
+}
+
+impl<S: SchemeMarker> Pubkey for PreparedPubkey<S> {
+    type This = S::Key;
+    type ThisCompressed = <S::Key as KeyGroupImpl>::Compressed;
+    type Other = <S::Key as KeyGroupImpl>::Other;
+
+    fn msg_to_curve(msg: &[u8]) -> Self::Other {
+        DrandPubkey::<S>::msg_to_curve(msg)
+    }
+
+    fn scheme(&self) -> Scheme {
+        self.pubkey.scheme()
+    }
+
+    fn signature_group(&self) -> Group {
+        self.pubkey.signature_group()
+    }
+
+    fn from_fixed(data: Self::ThisCompressed) -> Result<Self, InvalidPoint> {
+        Ok(DrandPubkey::from_fixed(data)?.prepare())
+    }
+
+    fn from_fixed_unchecked(
+        data: Self::ThisCompressed,
+        capability: UncheckedCapability,
+    ) -> Result<Self, InvalidPoint> {
+        Ok(DrandPubkey::from_fixed_unchecked(data, capability)?.prepare())
+    }
+
+    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+        Ok(DrandPubkey::from_variable(data)?.prepare())
+    }
+
+    fn verify_step2(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        let sigma = S::Key::signature_from_variable(signature)
+            .map_err(|err| VerificationError::invalid_point("signature", err))?;
+        Ok(self
+            .pubkey
+            .point
+            .pairing_check_prepared(&self.prepared, &sigma, msg_on_curve))
+    }
+
+    fn verify_step2_decompressed(
+        &self,
+        signature: &Signature,
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        let sigma = S::Key::signature_from_decompressed(signature)
+            .ok_or_else(|| wrong_group(S::Key::GROUP.other()))?;
+        Ok(self
+            .pubkey
+            .point
+            .pairing_check_prepared(&self.prepared, &sigma, msg_on_curve))
+    }
+
+    /// The prepared tables are allocated, so this works like with the plain key
+    fn verify_step2_no_alloc(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, ErrorCode> {
+        self.pubkey.verify_step2_no_alloc(signature, msg_on_curve)
+    }
+}
+
+impl<S: SchemeMarker> Pubkey for DrandPubkey<S> {
+    type This = S::Key;
+    type ThisCompressed = <S::Key as KeyGroupImpl>::Compressed;
+    type Other = <S::Key as KeyGroupImpl>::Other;
+
+    fn msg_to_curve(msg: &[u8]) -> Self::Other {
+        S::Key::hash_to_other(msg, S::DOMAIN)
+    }
+
+    fn scheme(&self) -> Scheme {
+        S::SCHEME
+    }
+
+    fn signature_group(&self) -> Group {
+        S::Key::GROUP.other()
+    }
+
+    fn from_fixed(data: Self::ThisCompressed) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(S::Key::from_fixed(data)?))
+    }
+
+    fn from_fixed_unchecked(
+        data: Self::ThisCompressed,
+        capability: UncheckedCapability,
+    ) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(S::Key::from_fixed_unchecked(data, capability)?))
+    }
+
+    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+        Ok(Self::new(S::Key::from_variable(data)?))
+    }
+
+    /// Takes this public key and verifies the signature with it.
+    /// The message has to be created with `Self::msg_to_curve`.
+    fn verify_step2(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        let sigma = S::Key::signature_from_variable(signature)
+            .map_err(|err| VerificationError::invalid_point("signature", err))?;
+        Ok(self.point.pairing_check(&sigma, msg_on_curve))
+    }
+
+    fn verify_step2_decompressed(
+        &self,
+        signature: &Signature,
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, VerificationError> {
+        let sigma = S::Key::signature_from_decompressed(signature)
+            .ok_or_else(|| wrong_group(S::Key::GROUP.other()))?;
+        Ok(self.point.pairing_check(&sigma, msg_on_curve))
+    }
+
+    fn verify_step2_no_alloc(
+        &self,
+        signature: &[u8],
+        msg_on_curve: &Self::Other,
+    ) -> Result<bool, ErrorCode> {
+        let sigma =
+            S::Key::signature_from_variable(signature).map_err(|err| err.code_for("signature"))?;
+        Ok(self.point.pairing_check_no_alloc(&sigma, msg_on_curve))
+    }
+}
+
+mod sealed_scheme {
+    pub trait Sealed {}
+
+    impl Sealed for super::PedersenBls {}
+    impl Sealed for super::BlsUnchainedOnG1 {}
+    impl Sealed for super::BlsUnchainedG1Rfc9380 {}
+}
+
+/// A drand scheme on the type level, see [`DrandPubkey`].
+///
+/// This trait is sealed. A new scheme is added to this crate by defining a marker type
+/// and implementing this trait for it.
+pub trait SchemeMarker: sealed_scheme::Sealed {
+    /// The group of public keys, [`G1`] or [`G2`]. Signatures live on the other group.
+    type Key: KeyGroup;
+
+    const SCHEME: Scheme;
+
+    /// The domain separation tag for hashing messages to the signature group
+    const DOMAIN: &'static [u8];
+}
+
+/// The scheme marker of pedersen-bls-chained and pedersen-bls-unchained
+pub struct PedersenBls;
+
+impl SchemeMarker for PedersenBls {
+    type Key = G1;
+    const SCHEME: Scheme = Scheme::PedersenBlsChained;
+    const DOMAIN: &'static [u8] = DOMAIN_HASH_TO_G2;
+}
+
+/// The scheme marker of bls-unchained-on-g1
+pub struct BlsUnchainedOnG1;
+
+impl SchemeMarker for BlsUnchainedOnG1 {
+    type Key = G2;
+    const SCHEME: Scheme = Scheme::BlsUnchainedOnG1;
+    // The usage of DOMAIN_HASH_TO_G2 here is needed to be compatible to a bug in drand's fastnet.
+    // See https://github.com/noislabs/drand-verify/pull/22 for more information about that topic.
+    const DOMAIN: &'static [u8] = DOMAIN_HASH_TO_G2;
+}
+
+/// The scheme marker of bls-unchained-g1-rfc9380
+pub struct BlsUnchainedG1Rfc9380;
+
+impl SchemeMarker for BlsUnchainedG1Rfc9380 {
+    type Key = G2;
+    const SCHEME: Scheme = Scheme::BlsUnchainedG1Rfc9380;
+    const DOMAIN: &'static [u8] = DOMAIN_HASH_TO_G1;
+}
+
+mod sealed_group {
+    use super::*;
+
+    /// The operations on a group of public keys used by [`DrandPubkey`]
+    pub trait KeyGroupImpl: Sized {
+        /// The group of signatures
+        type Other;
+        type Compressed;
+
+        const GROUP: Group;
+
+        fn from_fixed(data: Self::Compressed) -> Result<Self, InvalidPoint>;
+
+        fn from_fixed_unchecked(
+            data: Self::Compressed,
+            capability: UncheckedCapability,
+        ) -> Result<Self, InvalidPoint>;
+
+        fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint>;
+
+        fn hash_to_other(msg: &[u8], domain: &[u8]) -> Self::Other;
+
+        fn signature_from_variable(signature: &[u8]) -> Result<Self::Other, InvalidPoint>;
+
+        fn signature_from_decompressed(signature: &Signature) -> Option<Self::Other>;
+
+        fn pairing_check(&self, sigma: &Self::Other, msg_on_curve: &Self::Other) -> bool;
+
+        fn pairing_check_no_alloc(&self, sigma: &Self::Other, msg_on_curve: &Self::Other) -> bool;
+
+        /// Computes `(Σ r_i σ_i, Σ r_i H_i)` of `(σ_i, H_i, r_i)` triples
+        fn combine<'a, I>(entries: I) -> (Self::Other, Self::Other)
+        where
+            I: Iterator<Item = (&'a Self::Other, &'a Self::Other, Scalar)>,
+            Self::Other: 'a;
+
+        /// The Miller loop tables of the inputs of the pairing check that do not depend on
+        /// the beacon, see [`PreparedPubkey`]
+        type Prepared;
+
+        fn prepare(&self) -> Self::Prepared;
+
+        fn pairing_check_prepared(
+            &self,
+            prepared: &Self::Prepared,
+            sigma: &Self::Other,
+            msg_on_curve: &Self::Other,
+        ) -> bool;
+    }
+
+    impl KeyGroupImpl for G1 {
+        type Other = G2;
+        type Compressed = [u8; 48];
+
+        const GROUP: Group = Group::G1;
+
+        fn from_fixed(data: [u8; 48]) -> Result<Self, InvalidPoint> {
+            Ok(G1(g1_from_fixed(data)?))
+        }
+
+        fn from_fixed_unchecked(
+            data: [u8; 48],
+            capability: UncheckedCapability,
+        ) -> Result<Self, InvalidPoint> {
+            Ok(G1(g1_from_fixed_unchecked(data, capability)?))
+        }
+
+        fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+            Ok(G1(g1_from_variable(data)?))
+        }
+
+        fn hash_to_other(msg: &[u8], domain: &[u8]) -> G2 {
+            let g: G2Projective =
+                HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(msg, domain);
+            G2(g.into())
+        }
+
+        fn signature_from_variable(signature: &[u8]) -> Result<G2, InvalidPoint> {
+            Ok(G2(g2_from_variable(signature)?))
+        }
+
+        fn signature_from_decompressed(signature: &Signature) -> Option<G2> {
+            signature.g2().map(|point| G2(*point))
+        }
+
+        fn pairing_check(&self, sigma: &G2, msg_on_curve: &G2) -> bool {
+            let g1 = G1Affine::generator();
+            fast_pairing_equality(&g1, &sigma.0, &self.0, &msg_on_curve.0)
+        }
+
+        fn pairing_check_no_alloc(&self, sigma: &G2, msg_on_curve: &G2) -> bool {
+            let g1 = G1Affine::generator();
+            pairing(&g1, &sigma.0) == pairing(&self.0, &msg_on_curve.0)
+        }
+
+        fn combine<'a, I>(entries: I) -> (G2, G2)
+        where
+            I: Iterator<Item = (&'a G2, &'a G2, Scalar)>,
+        {
+            let mut sigma = G2Projective::identity();
+            let mut msg = G2Projective::identity();
+            for (s, m, r) in entries {
+                sigma += s.0 * r;
+                msg += m.0 * r;
+            }
+            (G2(sigma.into()), G2(msg.into()))
+        }
+
+        // Both G2 inputs of the check (σ and H) depend on the beacon
+        type Prepared = ();
+
+        fn prepare(&self) {}
+
+        fn pairing_check_prepared(&self, _prepared: &(), sigma: &G2, msg_on_curve: &G2) -> bool {
+            self.pairing_check(sigma, msg_on_curve)
+        }
+    }
+
+    impl KeyGroupImpl for G2 {
+        type Other = G1;
+        type Compressed = [u8; 96];
+
+        const GROUP: Group = Group::G2;
+
+        fn from_fixed(data: [u8; 96]) -> Result<Self, InvalidPoint> {
+            Ok(G2(g2_from_fixed(data)?))
+        }
+
+        fn from_fixed_unchecked(
+            data: [u8; 96],
+            capability: UncheckedCapability,
+        ) -> Result<Self, InvalidPoint> {
+            Ok(G2(g2_from_fixed_unchecked(data, capability)?))
+        }
+
+        fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
+            Ok(G2(g2_from_variable(data)?))
+        }
+
+        fn hash_to_other(msg: &[u8], domain: &[u8]) -> G1 {
+            let g: G1Projective =
+                HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(msg, domain);
+            G1(g.into())
+        }
+
+        fn signature_from_variable(signature: &[u8]) -> Result<G1, InvalidPoint> {
+            Ok(G1(g1_from_variable(signature)?))
+        }
+
+        fn signature_from_decompressed(signature: &Signature) -> Option<G1> {
+            signature.g1().map(|point| G1(*point))
+        }
+
+        fn pairing_check(&self, sigma: &G1, msg_on_curve: &G1) -> bool {
+            let g2 = G2Affine::generator();
+            fast_pairing_equality(&sigma.0, &g2, &msg_on_curve.0, &self.0)
+        }
+
+        fn pairing_check_no_alloc(&self, sigma: &G1, msg_on_curve: &G1) -> bool {
+            let g2 = G2Affine::generator();
+            pairing(&sigma.0, &g2) == pairing(&msg_on_curve.0, &self.0)
+        }
+
+        fn combine<'a, I>(entries: I) -> (G1, G1)
+        where
+            I: Iterator<Item = (&'a G1, &'a G1, Scalar)>,
+        {
+            let mut sigma = G1Projective::identity();
+            let mut msg = G1Projective::identity();
+            for (s, m, r) in entries {
+                sigma += s.0 * r;
+                msg += m.0 * r;
+            }
+            (G1(sigma.into()), G1(msg.into()))
+        }
+
+        /// The generator and the key
+        type Prepared = (G2Prepared, G2Prepared);
+
+        fn prepare(&self) -> (G2Prepared, G2Prepared) {
+            (
+                G2Prepared::from(G2Affine::generator()),
+                G2Prepared::from(self.0),
+            )
+        }
+
+        fn pairing_check_prepared(
+            &self,
+            (generator, key): &(G2Prepared, G2Prepared),
+            sigma: &G1,
+            msg_on_curve: &G1,
+        ) -> bool {
+            let minus_sigma = -sigma.0;
+            let looped =
+                Bls12::multi_miller_loop(&[(&minus_sigma, generator), (&msg_on_curve.0, key)]);
+            looped.final_exponentiation().is_identity().into()
+        }
     }
 }
 
+use sealed_group::KeyGroupImpl;
+
+/// The group of public keys of a [`SchemeMarker`], implemented for [`G1`] and [`G2`]
+pub trait KeyGroup: KeyGroupImpl {}
+
+impl KeyGroup for G1 {}
+impl KeyGroup for G2 {}
+
 /// The pubkey type for drand networks with scheme ID pedersen-bls-chained or pedersen-bls-unchained.
 ///
 /// ## Examples
@@ -105,57 +878,20 @@ pub trait Pubkey: Sized {
 /// let result = pk.verify(round, b"", &signature).unwrap();
 /// assert!(result);
 /// ```
-pub struct G1Pubkey(G1);
-
-impl Pubkey for G1Pubkey {
-    type This = G1;
-    type ThisCompressed = [u8; 48];
-    type Other = G2;
-
-    fn msg_to_curve(msg: &[u8]) -> Self::Other {
-        let g: G2Projective =
-            HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(msg, DOMAIN_HASH_TO_G2);
-        G2(g.into())
-    }
-
-    fn from_fixed(data: [u8; 48]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G1(g1_from_fixed(data)?)))
-    }
+pub type G1Pubkey = DrandPubkey<PedersenBls>;
 
-    fn from_fixed_unchecked(data: [u8; 48]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G1(g1_from_fixed_unchecked(data)?)))
-    }
-
-    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G1(g1_from_variable(data)?)))
-    }
-
-    /// Takes this public key and verifies the signature with it.
-    /// The message has to be created with `Self::msg_to_curve`.
-    fn verify_step2(
-        &self,
-        signature: &[u8],
-        msg_on_curve: &Self::Other,
-    ) -> Result<bool, VerificationError> {
-        let g1 = G1Affine::generator();
-        let sigma = match g2_from_variable(signature) {
-            Ok(sigma) => sigma,
-            Err(err) => {
-                return Err(VerificationError::InvalidPoint {
-                    field: "signature".into(),
-                    msg: err.to_string(),
-                })
-            }
-        };
-        let r = (self.0).0;
-        Ok(fast_pairing_equality(&g1, &sigma, &r, &msg_on_curve.0))
-    }
-}
+/// An alias for [`G2PubkeyRfc`], the pubkey type of drand networks with scheme ID bls-unchained-g1-rfc9380.
+///
+/// Up to version 0.6 this was an alias for [`G2PubkeyFastnet`]. Code written for fastnet must use
+/// [`G2PubkeyFastnet`] now. Use [`Scheme::from_scheme_str`](crate::Scheme::from_scheme_str)
+/// to find the right type for a scheme ID.
+pub type G2Pubkey = G2PubkeyRfc;
 
+/// The type that [`G2Pubkey`] was an alias of up to version 0.6.
 #[deprecated(
-    note = "Use G2PubkeyFastnet for drand networks with scheme ID bls-unchained-on-g1 or G2PubkeyRfc for drand networks with scheme ID bls-unchained-g1-rfc9380. G2Pubkey will be removed at some point and later re-introduced as an alias for G2PubkeyRfc."
+    note = "G2Pubkey is now an alias for G2PubkeyRfc (scheme ID bls-unchained-g1-rfc9380). LegacyG2Pubkey only exists for migration, use G2PubkeyFastnet for drand networks with scheme ID bls-unchained-on-g1."
 )]
-pub type G2Pubkey = G2PubkeyFastnet;
+pub type LegacyG2Pubkey = G2PubkeyFastnet;
 
 /// The pubkey type for drand networks with scheme ID bls-unchained-on-g1.
 ///
@@ -163,54 +899,7 @@ pub type G2Pubkey = G2PubkeyFastnet;
 /// but also "testnet-g".
 /// Please note that fastnet is deprecated and will be shut down:
 /// <https://drand.love/blog/2023/07/03/fastnet-sunset-quicknet-new/>
-pub struct G2PubkeyFastnet(G2);
-
-impl Pubkey for G2PubkeyFastnet {
-    type This = G2;
-    type ThisCompressed = [u8; 96];
-    type Other = G1;
-
-    fn msg_to_curve(msg: &[u8]) -> Self::Other {
-        // The usage of DOMAIN_HASH_TO_G2 here is needed to be compatible to a bug in drand's fastnet.
-        // See https://github.com/noislabs/drand-verify/pull/22 for more information about that topic.
-        let g: G1Projective =
-            HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(msg, DOMAIN_HASH_TO_G2);
-        G1(g.into())
-    }
-
-    fn from_fixed(data: [u8; 96]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_fixed(data)?)))
-    }
-
-    fn from_fixed_unchecked(data: [u8; 96]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_fixed_unchecked(data)?)))
-    }
-
-    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_variable(data)?)))
-    }
-
-    /// Takes this public key and verifies the signature with it.
-    /// The message has to be created with `Self::msg_to_curve`.
-    fn verify_step2(
-        &self,
-        signature: &[u8],
-        msg_on_curve: &Self::Other,
-    ) -> Result<bool, VerificationError> {
-        let g2 = G2Affine::generator();
-        let sigma = match g1_from_variable(signature) {
-            Ok(sigma) => sigma,
-            Err(err) => {
-                return Err(VerificationError::InvalidPoint {
-                    field: "signature".into(),
-                    msg: err.to_string(),
-                })
-            }
-        };
-        let s = (self.0).0;
-        Ok(fast_pairing_equality(&sigma, &g2, &msg_on_curve.0, &s))
-    }
-}
+pub type G2PubkeyFastnet = DrandPubkey<BlsUnchainedOnG1>;
 
 /// The pubkey type for drand networks with scheme ID bls-unchained-g1-rfc9380.
 ///
@@ -231,69 +920,188 @@ impl Pubkey for G2PubkeyFastnet {
 /// let result = pk.verify(round, b"", &signature).unwrap();
 /// assert!(result);
 /// ```
-pub struct G2PubkeyRfc(G2);
+pub type G2PubkeyRfc = DrandPubkey<BlsUnchainedG1Rfc9380>;
 
-impl Pubkey for G2PubkeyRfc {
-    type This = G2;
-    type ThisCompressed = [u8; 96];
-    type Other = G1;
+#[derive(Debug)]
+pub enum VerificationError {
+    InvalidPoint { field: String, msg: String },
+}
 
-    fn msg_to_curve(msg: &[u8]) -> Self::Other {
-        let g: G1Projective =
-            HashToCurve::<ExpandMsgXmd<sha2::Sha256>>::hash_to_curve(msg, DOMAIN_HASH_TO_G1);
-        G1(g.into())
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationError::InvalidPoint { field, msg } => {
+                write!(f, "Invalid point for field {}: {}", field, msg)
+            }
+        }
     }
+}
 
-    fn from_fixed(data: [u8; 96]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_fixed(data)?)))
+impl VerificationError {
+    /// The error for the input `field` that could not be decoded
+    pub fn invalid_point(field: &str, source: InvalidPoint) -> Self {
+        VerificationError::InvalidPoint {
+            field: field.to_string(),
+            msg: source.to_string(),
+        }
     }
 
-    fn from_fixed_unchecked(data: [u8; 96]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_fixed_unchecked(data)?)))
+    /// The stable numeric code of this error, see [`ErrorCode`].
+    ///
+    /// The code agrees with [`InvalidPoint::code_for`] of the error the message was created from.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            VerificationError::InvalidPoint { field, msg } => code_for_message(field, msg),
+        }
     }
+}
 
-    fn from_variable(data: &[u8]) -> Result<Self, InvalidPoint> {
-        Ok(Self(G2(g2_from_variable(data)?)))
+impl Error for VerificationError {}
+
+/// Numeric error codes for [`Pubkey::verify_no_alloc`] and callers in other languages.
+///
+/// The values are stable and will never be reassigned. They are exposed by the JS bindings
+/// (as the `code` property of thrown errors) and the example CLI (as exit code `10 + code`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ErrorCode {
+    /// The signature does not have the length of a compressed point of the expected group
+    InvalidSignatureLength = 1,
+    /// The signature is not a valid point of the expected group
+    InvalidSignature = 2,
+    /// An input other than the signature (e.g. the public key) does not have the length
+    /// of a compressed point, see [`InvalidPoint::InvalidLength`]
+    InvalidPointLength = 3,
+    /// An input other than the signature is not a valid point, see [`InvalidPoint::DecodingError`]
+    InvalidPoint = 4,
+    /// An input cannot be decoded, e.g. invalid hex
+    InvalidEncoding = 5,
+    /// A batch exceeds the maximum size, see [`BatchTooLarge`](crate::batch::BatchTooLarge)
+    BatchTooLarge = 6,
+}
+
+impl ErrorCode {
+    pub fn code(self) -> u8 {
+        self as u8
     }
 
-    /// Takes this public key and verifies the signature with it.
-    /// The message has to be created with `Self::msg_to_curve`.
-    fn verify_step2(
-        &self,
-        signature: &[u8],
-        msg_on_curve: &Self::Other,
-    ) -> Result<bool, VerificationError> {
-        let g2 = G2Affine::generator();
-        let sigma = match g1_from_variable(signature) {
-            Ok(sigma) => sigma,
-            Err(err) => {
-                return Err(VerificationError::InvalidPoint {
-                    field: "signature".into(),
-                    msg: err.to_string(),
-                })
-            }
-        };
-        let s = (self.0).0;
-        Ok(fast_pairing_equality(&sigma, &g2, &msg_on_curve.0, &s))
+    /// A stable snake case name of the code, e.g. `"invalid_signature"`
+    pub fn kind(self) -> &'static str {
+        match self {
+            ErrorCode::InvalidSignatureLength => "invalid_signature_length",
+            ErrorCode::InvalidSignature => "invalid_signature",
+            ErrorCode::InvalidPointLength => "invalid_point_length",
+            ErrorCode::InvalidPoint => "invalid_point",
+            ErrorCode::InvalidEncoding => "invalid_encoding",
+            ErrorCode::BatchTooLarge => "batch_too_large",
+        }
     }
 }
 
-#[derive(Debug)]
-pub enum VerificationError {
-    InvalidPoint { field: String, msg: String },
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCode::InvalidSignatureLength => write!(f, "Invalid signature length"),
+            ErrorCode::InvalidSignature => write!(f, "Invalid signature"),
+            ErrorCode::InvalidPointLength => write!(f, "Invalid point length"),
+            ErrorCode::InvalidPoint => write!(f, "Invalid point"),
+            ErrorCode::InvalidEncoding => write!(f, "Invalid encoding"),
+            ErrorCode::BatchTooLarge => write!(f, "Batch too large"),
+        }
+    }
 }
 
-impl fmt::Display for VerificationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl Error for ErrorCode {}
+
+impl From<InvalidPoint> for ErrorCode {
+    fn from(source: InvalidPoint) -> Self {
+        source.code()
+    }
+}
+
+/// An error of a rejected input, see [`audited`]
+pub(crate) trait Rejection {
+    fn reason(&self) -> InvalidReason;
+}
+
+impl Rejection for VerificationError {
+    fn reason(&self) -> InvalidReason {
         match self {
-            VerificationError::InvalidPoint { field, msg } => {
-                write!(f, "Invalid point for field {}: {}", field, msg)
-            }
+            VerificationError::InvalidPoint { field, msg } => InvalidReason::InvalidPoint {
+                field: field.clone(),
+                msg: msg.clone(),
+            },
         }
     }
 }
 
-impl Error for VerificationError {}
+impl Rejection for ErrorCode {
+    fn reason(&self) -> InvalidReason {
+        let field = match self {
+            ErrorCode::InvalidSignatureLength | ErrorCode::InvalidSignature => "signature",
+            ErrorCode::InvalidPointLength | ErrorCode::InvalidPoint => "previous_signature",
+            ErrorCode::InvalidEncoding | ErrorCode::BatchTooLarge => "input",
+        };
+        InvalidReason::InvalidPoint {
+            field: field.to_string(),
+            msg: self.to_string(),
+        }
+    }
+}
+
+/// The rejection path shared by all verification methods. Passes rejected inputs to the
+/// audit log (feature `audit-log`) and returns the result unchanged.
+pub(crate) fn audited<E: Rejection>(
+    scheme: Scheme,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+    result: Result<bool, E>,
+) -> Result<bool, E> {
+    match result {
+        Ok(true) => Ok(true),
+        Ok(false) => {
+            record_rejection(scheme, round, previous_signature, signature, || {
+                InvalidReason::SignatureMismatch
+            });
+            Ok(false)
+        }
+        Err(err) => Err(rejected(scheme, round, previous_signature, signature, err)),
+    }
+}
+
+/// Like [`audited`] for inputs rejected before verification, e.g. when added to a batch
+pub(crate) fn rejected<E: Rejection>(
+    scheme: Scheme,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+    err: E,
+) -> E {
+    record_rejection(scheme, round, previous_signature, signature, || {
+        err.reason()
+    });
+    err
+}
+
+#[cfg_attr(not(feature = "audit-log"), allow(unused_variables))]
+fn record_rejection(
+    scheme: Scheme,
+    round: u64,
+    previous_signature: &[u8],
+    signature: &[u8],
+    reason: impl FnOnce() -> InvalidReason,
+) {
+    #[cfg(feature = "audit-log")]
+    crate::audit_log::record(scheme, round, previous_signature, signature, reason);
+}
+
+fn wrong_group(expected: Group) -> VerificationError {
+    VerificationError::InvalidPoint {
+        field: "signature".into(),
+        msg: format!("Signature must be on {}", expected),
+    }
+}
 
 /// Checks if e(p, q) == e(r, s)
 ///
@@ -315,11 +1123,84 @@ fn fast_pairing_equality(p: &G1Affine, q: &G2Affine, r: &G1Affine, s: &G2Affine)
     value.is_identity().into()
 }
 
-fn message(current_round: u64, prev_sig: &[u8]) -> Vec<u8> {
+/// Checks that a non-empty previous signature is a valid point of `group`.
+///
+/// Round 1 is exempt because its previous signature is the genesis seed of the chain.
+fn check_previous_signature_no_alloc(
+    group: Group,
+    round: u64,
+    previous_signature: &[u8],
+) -> Result<(), InvalidPoint> {
+    if previous_signature.is_empty() || round == 1 {
+        return Ok(());
+    }
+    match group {
+        Group::G1 => g1_from_variable(previous_signature).map(|_| ()),
+        Group::G2 => g2_from_variable(previous_signature).map(|_| ()),
+    }
+}
+
+fn check_previous_signature(
+    group: Group,
+    round: u64,
+    previous_signature: &[u8],
+) -> Result<(), VerificationError> {
+    check_previous_signature_no_alloc(group, round, previous_signature)
+        .map_err(|err| VerificationError::invalid_point("previous_signature", err))
+}
+
+pub(crate) fn message(current_round: u64, prev_sig: &[u8]) -> Vec<u8> {
+    message_fixed(current_round, prev_sig).to_vec()
+}
+
+/// A hash function that builds the message of a round, see [`Pubkey::verify_with_hash`].
+///
+/// Implement this for a marker type to verify networks that hash rounds differently. The
+/// contract is:
+///
+/// - The result is the message that is hashed to the curve and signed. It must be exactly
+///   what the signers of the network hash to the curve.
+/// - It must be deterministic and only depend on `round` and `previous_signature`.
+///   `previous_signature` is empty for unchained networks.
+///
+/// drand hashes the previous signature followed by the round as 8 byte big endian integer
+/// with SHA-256, which is implemented for [`sha2::Sha256`].
+///
+/// ## Examples
+///
+/// ```
+/// use drand_verify::{networks, G2PubkeyRfc, MessageHash, Pubkey};
+/// use hex_literal::hex;
+///
+/// /// The hash of a network that only signs the round number
+/// struct RoundOnly;
+///
+/// impl MessageHash for RoundOnly {
+///     fn hash_message(round: u64, _previous_signature: &[u8]) -> Vec<u8> {
+///         round.to_be_bytes().to_vec()
+///     }
+/// }
+///
+/// let pk = G2PubkeyRfc::from_variable(networks::QUICKNET.public_key).unwrap();
+/// let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+/// // quicknet signs SHA-256 messages
+/// assert!(!pk.verify_with_hash::<RoundOnly>(123, &[], &signature).unwrap());
+/// ```
+pub trait MessageHash {
+    fn hash_message(round: u64, previous_signature: &[u8]) -> Vec<u8>;
+}
+
+impl MessageHash for Sha256 {
+    fn hash_message(round: u64, previous_signature: &[u8]) -> Vec<u8> {
+        message_fixed(round, previous_signature).to_vec()
+    }
+}
+
+fn message_fixed(current_round: u64, prev_sig: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::default();
     hasher.update(prev_sig);
     hasher.update(round_to_bytes(current_round));
-    hasher.finalize().to_vec()
+    hasher.finalize().into()
 }
 
 /// https://github.com/drand/drand-client/blob/master/wasm/chain/verify.go#L28-L33
@@ -356,13 +1237,33 @@ mod tests {
         let result = pk.verify(321, &previous_signature, &signature).unwrap();
         assert!(!result);
 
-        // wrong previous signature
+        // corrupted previous signature
         let previous_signature_corrupted = hex::decode("6a09e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").unwrap();
-        let result = pk
+        match pk
             .verify(round, &previous_signature_corrupted, &signature)
+            .unwrap_err()
+        {
+            VerificationError::InvalidPoint { field, .. } => {
+                assert_eq!(field, "previous_signature")
+            }
+        }
+        assert_eq!(
+            pk.verify_no_alloc(round, &previous_signature[..95], &signature),
+            Err(ErrorCode::InvalidPointLength)
+        );
+
+        // wrong previous signature
+        // (use signature from https://drand.cloudflare.com/public/1 to get a valid curve point)
+        let previous_signature_wrong = hex::decode("8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655").unwrap();
+        let result = pk
+            .verify(round, &previous_signature_wrong, &signature)
             .unwrap();
         assert!(!result);
 
+        // the previous signature of round 1 is the genesis seed
+        let result = pk.verify(1, &[0x17; 32], &signature).unwrap();
+        assert!(!result);
+
         // wrong signature
         // (use signature from https://drand.cloudflare.com/public/1 to get a valid curve point)
         let wrong_signature = hex::decode("8d61d9100567de44682506aea1a7a6fa6e5491cd27a0a0ed349ef6910ac5ac20ff7bc3e09d7c046566c9f7f3c6f3b10104990e7cb424998203d8f7de586fb7fa5f60045417a432684f85093b06ca91c769f0e7ca19268375e659c2a2352b4655").unwrap();
@@ -372,6 +1273,39 @@ mod tests {
         assert!(!result);
     }
 
+    #[test]
+    fn scheme_markers_match_schemes() {
+        fn check<S: SchemeMarker>() {
+            assert_eq!(S::SCHEME.key_group(), S::Key::GROUP);
+        }
+        check::<PedersenBls>();
+        check::<BlsUnchainedOnG1>();
+        check::<BlsUnchainedG1Rfc9380>();
+
+        let pk = DrandPubkey::<PedersenBls>::from_fixed(PK_LEO_MAINNET).unwrap();
+        assert_eq!(pk.scheme(), Scheme::PedersenBlsChained);
+        assert_eq!(pk.signature_group(), Group::G2);
+    }
+
+    #[test]
+    fn message_hash_works() {
+        let previous_signature = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        assert_eq!(
+            Sha256::hash_message(72785, &previous_signature),
+            message(72785, &previous_signature)
+        );
+        assert_eq!(Sha256::hash_message(123, &[]), message(123, &[]));
+
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert!(pk
+            .verify_with_hash::<Sha256>(72785, &previous_signature, &signature)
+            .unwrap());
+        assert!(!pk
+            .verify_with_hash::<Sha256>(72786, &previous_signature, &signature)
+            .unwrap());
+    }
+
     #[test]
     fn verify_works_for_unchained() {
         let pk = G1Pubkey::from_fixed(PK_UNCHAINED_TESTNET).unwrap();
@@ -484,4 +1418,331 @@ mod tests {
             assert!(result);
         }
     }
+
+    #[test]
+    fn verify_decompressed_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+
+        // curl -sS https://drand.cloudflare.com/public/72785
+        let previous_signature = hex::decode("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").unwrap();
+        let signature = Signature::from_variable(&hex::decode("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").unwrap()).unwrap();
+
+        let result = pk
+            .verify_decompressed(72785, &previous_signature, &signature)
+            .unwrap();
+        assert!(result);
+        let result = pk
+            .verify_decompressed(72786, &previous_signature, &signature)
+            .unwrap();
+        assert!(!result);
+
+        // signature on wrong group
+        let signature = Signature::from_variable(&hex::decode("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92").unwrap()).unwrap();
+        match pk.verify_decompressed(123, b"", &signature).unwrap_err() {
+            VerificationError::InvalidPoint { field, msg, .. } => {
+                assert_eq!(field, "signature");
+                assert_eq!(msg, "Signature must be on G2");
+            }
+        }
+    }
+
+    #[test]
+    fn verify_with_outcome_works() {
+        let pk = G1Pubkey::from_fixed(PK_UNCHAINED_TESTNET).unwrap();
+
+        // curl -sS https://pl-us.testnet.drand.sh/7672797f548f3f4748ac4bf3352fc6c6b6468c9ad40ad456a397545c6e2df5bf/public/223344
+        let signature = hex::decode("94f6b85df7cce7237e8e7df66d794ddad092de5d8bb6a791b97e905aa89852e506ac36a792eba7021e22eebf34891f8914bf9a8dd9233ea0a4c5ca00ef8404999f899073dd2eade61fe54077fee8168f83dcb61a758b6883b38904054e64a433").unwrap();
+
+        match pk.verify_with_outcome(223344, b"", &signature) {
+            VerificationOutcome::Valid(beacon) => {
+                assert_eq!(beacon.round(), 223344);
+                assert_eq!(beacon.previous_signature(), b"");
+                assert_eq!(beacon.signature(), signature);
+                assert_eq!(
+                    beacon.randomness(),
+                    hex!("f3d6adf1daa2c7877f90fb0f1a675ab0a42653a1e2a9b66fee0749d47a47bc57")
+                );
+            }
+            outcome => panic!("Unexpected outcome: {:?}", outcome),
+        }
+
+        let outcome = pk.verify_with_outcome(223345, b"", &signature);
+        assert_eq!(
+            outcome,
+            VerificationOutcome::Invalid(InvalidReason::SignatureMismatch)
+        );
+
+        match pk.verify_with_outcome(223344, b"", &signature[1..]) {
+            VerificationOutcome::Invalid(InvalidReason::InvalidPoint { field, .. }) => {
+                assert_eq!(field, "signature");
+            }
+            outcome => panic!("Unexpected outcome: {:?}", outcome),
+        }
+    }
+
+    #[test]
+    fn verify_no_alloc_works() {
+        // curl -sS https://drand.cloudflare.com/public/72785
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let previous_signature = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert_eq!(
+            pk.verify_no_alloc(72785, &previous_signature, &signature),
+            Ok(true)
+        );
+        assert_eq!(
+            pk.verify_no_alloc(321, &previous_signature, &signature),
+            Ok(false)
+        );
+        assert_eq!(
+            pk.verify_no_alloc(72785, &previous_signature, &signature[1..]),
+            Err(ErrorCode::InvalidSignatureLength)
+        );
+        assert_eq!(
+            pk.verify_no_alloc(72785, &previous_signature, &[0xff; 96]),
+            Err(ErrorCode::InvalidSignature)
+        );
+
+        // https://api3.drand.sh/52db9ba70e0cc0f6eaf7803dd07447a1f5477735fd3f661792ba94600c84e971/public/123
+        const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let rfc = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        assert_eq!(rfc.verify_no_alloc(123, &[], &signature), Ok(true));
+        assert_eq!(rfc.verify_no_alloc(124, &[], &signature), Ok(false));
+        let fastnet = G2PubkeyFastnet::from_fixed(PK_QUICKNET).unwrap();
+        assert_eq!(fastnet.verify_no_alloc(123, &[], &signature), Ok(false));
+
+        assert_eq!(ErrorCode::InvalidSignatureLength.code(), 1);
+        assert_eq!(ErrorCode::InvalidSignature.code(), 2);
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(ErrorCode::InvalidSignatureLength.code(), 1);
+        assert_eq!(ErrorCode::InvalidSignature.code(), 2);
+        assert_eq!(ErrorCode::InvalidPointLength.code(), 3);
+        assert_eq!(ErrorCode::InvalidPoint.code(), 4);
+        assert_eq!(ErrorCode::InvalidEncoding.code(), 5);
+        assert_eq!(ErrorCode::BatchTooLarge.code(), 6);
+        assert_eq!(
+            ErrorCode::InvalidSignatureLength.kind(),
+            "invalid_signature_length"
+        );
+        assert_eq!(ErrorCode::BatchTooLarge.kind(), "batch_too_large");
+
+        let err = G1Pubkey::from_variable(&[0x86; 2]).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::InvalidPointLength);
+        let err = G1Pubkey::from_fixed([0x11; 48]).err().unwrap();
+        assert_eq!(err.code(), ErrorCode::InvalidPoint);
+
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let err = pk.verify(1, &[], &[0x11; 3]).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignatureLength);
+        let err = pk.verify(1, &[], &[0x11; 96]).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignature);
+        let err = VerificationError::invalid_point("public_key", InvalidPoint::DecodingError {});
+        assert_eq!(err.code(), ErrorCode::InvalidPoint);
+
+        // errors created without `invalid_point` and signatures on the wrong group
+        let err = VerificationError::InvalidPoint {
+            field: "signature".to_string(),
+            msg: InvalidPoint::InvalidLength {
+                expected: 96,
+                actual: 3,
+            }
+            .to_string(),
+        };
+        assert_eq!(err.code(), ErrorCode::InvalidSignatureLength);
+        assert_eq!(wrong_group(Group::G2).code(), ErrorCode::InvalidSignature);
+    }
+
+    #[test]
+    fn error_codes_agree_across_entry_points() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        let valid = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        let cases: [(&[u8], &[u8], ErrorCode); 4] = [
+            (&[], &[0x11; 3], ErrorCode::InvalidSignatureLength),
+            (&[], &[0x11; 96], ErrorCode::InvalidSignature),
+            (&valid[..95], &valid, ErrorCode::InvalidPointLength),
+            (&[0x11; 96], &valid, ErrorCode::InvalidPoint),
+        ];
+        for (previous_signature, signature, expected) in cases {
+            let round = 2;
+            let err = pk.verify(round, previous_signature, signature).unwrap_err();
+            assert_eq!(err.code(), expected);
+            assert_eq!(
+                pk.verify_no_alloc(round, previous_signature, signature),
+                Err(expected)
+            );
+
+            let (field, source) =
+                match check_previous_signature_no_alloc(Group::G2, round, previous_signature) {
+                    Err(source) => ("previous_signature", source),
+                    Ok(()) => ("signature", g2_from_variable(signature).unwrap_err()),
+                };
+            assert_eq!(source.code_for(field), expected);
+            assert_eq!(
+                VerificationError::invalid_point(field, source).code(),
+                expected
+            );
+        }
+
+        // errors of inputs other than the signature
+        for source in [
+            InvalidPoint::InvalidLength {
+                expected: 48,
+                actual: 2,
+            },
+            InvalidPoint::DecodingError {},
+        ] {
+            let code = source.code();
+            assert_eq!(source.code_for("public_key"), code);
+            assert_eq!(ErrorCode::from(source), code);
+        }
+        let err = crate::parse_signature([0x11; 3], crate::Encoding::Compressed).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::InvalidSignatureLength);
+    }
+
+    #[test]
+    fn metadata_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+        assert_eq!(pk.scheme(), Scheme::PedersenBlsChained);
+        assert_eq!(pk.scheme_id(), "pedersen-bls-chained");
+        assert_eq!(pk.signature_group(), Group::G2);
+        assert_eq!(pk.key_size(), 48);
+        assert_eq!(pk.signature_size(), 96);
+
+        const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let pk = G2PubkeyFastnet::from_fixed(PK_QUICKNET).unwrap();
+        assert_eq!(pk.scheme_id(), "bls-unchained-on-g1");
+        assert_eq!(pk.signature_group(), Group::G1);
+        assert_eq!(pk.key_size(), 96);
+        assert_eq!(pk.signature_size(), 48);
+
+        let pk = G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap();
+        assert_eq!(pk.scheme_id(), "bls-unchained-g1-rfc9380");
+        assert_eq!(pk.signature_group(), Group::G1);
+        assert_eq!(pk.key_size(), 96);
+        assert_eq!(pk.signature_size(), 48);
+
+        assert_eq!(Group::G1.to_string(), "G1");
+        assert_eq!(Group::G2.other(), Group::G1);
+    }
+
+    #[test]
+    fn verify_round_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+
+        // curl -sS https://drand.cloudflare.com/public/72785
+        let previous_signature = PreviousSignature::Chained(hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747"));
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert!(pk
+            .verify_round(72785, &previous_signature, &signature)
+            .unwrap());
+        assert!(!pk
+            .verify_round(72785, &PreviousSignature::None, &signature)
+            .unwrap());
+    }
+
+    #[test]
+    fn verify_beacon_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap();
+
+        // curl -sS https://drand.cloudflare.com/public/72785
+        let mut beacon = Beacon {
+            round: 72785,
+            previous_signature: hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747").to_vec(),
+            signature: hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42").to_vec(),
+        };
+        assert!(pk.verify_beacon(&beacon).unwrap());
+        beacon.round += 1;
+        assert!(!pk.verify_beacon(&beacon).unwrap());
+        beacon.signature.truncate(10);
+        assert!(pk.verify_beacon(&beacon).is_err());
+    }
+
+    /// Inputs that are not valid points of any kind: wrong lengths, invalid flags,
+    /// coordinates out of range and the point at infinity
+    fn malformed_points() -> Vec<Vec<u8>> {
+        let mut points = Vec::new();
+        for len in [0, 1, 32, 47, 48, 49, 95, 96, 97, 144, 192] {
+            for first in [0x00, 0x80, 0xa0, 0xc0, 0xe0, 0xff] {
+                let mut point = vec![0u8; len];
+                if let Some(byte) = point.first_mut() {
+                    *byte = first;
+                }
+                points.push(point);
+            }
+            points.push(vec![0xff; len]);
+        }
+        points
+    }
+
+    fn assert_rejects_malformed<P: Pubkey>(pk: &P) {
+        for signature in malformed_points() {
+            assert!(!matches!(pk.verify(1, &[], &signature), Ok(true)));
+            assert!(!matches!(pk.verify_no_alloc(1, &[], &signature), Ok(true)));
+            assert!(!pk.verify_with_outcome(1, &[], &signature).is_valid());
+        }
+        let signature = [0xc0; 96];
+        for round in [0, u64::MAX] {
+            for previous_signature in [&[][..], &[0xff; 1], &[0x00; 300]] {
+                assert!(!matches!(
+                    pk.verify(round, previous_signature, &signature),
+                    Ok(true)
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn malformed_inputs_are_rejected_without_panic() {
+        for key in malformed_points() {
+            // This includes the point at infinity, which is a valid encoding but neither
+            // a valid public key nor a valid signature
+            assert!(G1Pubkey::from_variable(&key).is_err());
+            assert!(G2PubkeyFastnet::from_variable(&key).is_err());
+            assert!(G2PubkeyRfc::from_variable(&key).is_err());
+            assert!(Signature::from_variable(&key).is_err());
+        }
+
+        // The point at infinity in uncompressed format
+        let mut infinity = [0u8; 192];
+        infinity[0] = 0x40;
+        assert!(Signature::from_uncompressed(&infinity[..96]).is_err());
+        assert!(Signature::from_uncompressed(&infinity).is_err());
+
+        assert_rejects_malformed(&G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap());
+        const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        assert_rejects_malformed(&G2PubkeyRfc::from_fixed(PK_QUICKNET).unwrap());
+        assert_rejects_malformed(&G2PubkeyFastnet::from_fixed(PK_QUICKNET).unwrap());
+        assert_rejects_malformed(
+            &PreparedPubkey::<BlsUnchainedG1Rfc9380>::from_fixed(PK_QUICKNET).unwrap(),
+        );
+    }
+
+    #[test]
+    fn prepared_pubkey_works() {
+        let pk = G1Pubkey::from_fixed(PK_LEO_MAINNET).unwrap().prepare();
+        assert_eq!(pk.scheme(), Scheme::PedersenBlsChained);
+        let previous_signature = hex!("a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747");
+        let signature = hex!("82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42");
+        assert!(pk.verify(72785, &previous_signature, &signature).unwrap());
+        assert!(!pk.verify(72786, &previous_signature, &signature).unwrap());
+
+        const PK_QUICKNET: [u8; 96] = hex!("83cf0f2896adee7eb8b5f01fcad3912212c437e0073e911fb90022d3e760183c8c4b450b6a0a6c3ac6a5776a2d1064510d1fec758c921cc22b0e17e63aaf4bcb5ed66304de9cf809bd274ca73bab4af5a6e9c76a4bc09e76eae8991ef5ece45a");
+        let signature = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+        let pk = PreparedPubkey::<BlsUnchainedG1Rfc9380>::from_fixed(PK_QUICKNET).unwrap();
+        assert_eq!(pk.scheme(), Scheme::BlsUnchainedG1Rfc9380);
+        assert!(pk.verify(123, &[], &signature).unwrap());
+        assert!(!pk.verify(124, &[], &signature).unwrap());
+        let decompressed = Signature::from_variable(&signature).unwrap();
+        assert!(pk.verify_decompressed(123, &[], &decompressed).unwrap());
+        assert!(pk.verify_no_alloc(123, &[], &signature).unwrap());
+
+        // same key, different scheme
+        let pk = G2PubkeyFastnet::from_fixed(PK_QUICKNET).unwrap().prepare();
+        assert!(!pk.verify(123, &[], &signature).unwrap());
+        assert_eq!(pk.into_inner().scheme(), Scheme::BlsUnchainedOnG1);
+    }
 }
@@ -0,0 +1,249 @@
+use jni::objects::{JByteArray, JClass, JString};
+use jni::sys::{jboolean, jbyteArray, jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+
+use crate::{
+    derive_randomness, G1Pubkey, G2PubkeyFastnet, G2PubkeyRfc, InvalidPoint, Pubkey, Scheme,
+    UnknownScheme, VerificationError,
+};
+
+/// The Java exception thrown for invalid inputs
+const ILLEGAL_ARGUMENT: &str = "java/lang/IllegalArgumentException";
+
+/// The Java exception thrown when the JVM interaction itself fails
+const RUNTIME_EXCEPTION: &str = "java/lang/RuntimeException";
+
+/// An error that is translated to a Java exception
+struct VerifyJniError {
+    class: &'static str,
+    msg: String,
+}
+
+impl VerifyJniError {
+    fn illegal_argument(msg: impl Into<String>) -> Self {
+        Self {
+            class: ILLEGAL_ARGUMENT,
+            msg: msg.into(),
+        }
+    }
+}
+
+impl From<jni::errors::Error> for VerifyJniError {
+    fn from(source: jni::errors::Error) -> Self {
+        Self {
+            class: RUNTIME_EXCEPTION,
+            msg: source.to_string(),
+        }
+    }
+}
+
+impl From<InvalidPoint> for VerifyJniError {
+    fn from(source: InvalidPoint) -> Self {
+        Self::illegal_argument(source.to_string())
+    }
+}
+
+impl From<UnknownScheme> for VerifyJniError {
+    fn from(source: UnknownScheme) -> Self {
+        Self::illegal_argument(source.to_string())
+    }
+}
+
+impl From<VerificationError> for VerifyJniError {
+    fn from(source: VerificationError) -> Self {
+        Self::illegal_argument(source.to_string())
+    }
+}
+
+/// Throws the error as a Java exception unless there is one pending already
+fn throw(env: &mut JNIEnv, err: VerifyJniError) {
+    if !env.exception_check().unwrap_or(true) {
+        // If throwing fails there is nothing left we can do
+        let _ = env.throw_new(err.class, err.msg);
+    }
+}
+
+/// Entry point for `com.noislabs.drandverify.DrandVerify.verifyBeacon`:
+///
+/// ```java
+/// static native boolean verifyBeacon(String schemeId, byte[] pubkey, long round, byte[] previousSignature, byte[] signature);
+/// ```
+///
+/// `previousSignature` should be an empty array for unchained networks.
+/// Throws an `IllegalArgumentException` for invalid inputs.
+#[no_mangle]
+pub extern "system" fn Java_com_noislabs_drandverify_DrandVerify_verifyBeacon<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    scheme_id: JString<'local>,
+    pubkey: JByteArray<'local>,
+    round: jlong,
+    previous_signature: JByteArray<'local>,
+    signature: JByteArray<'local>,
+) -> jboolean {
+    match verify_beacon_impl(
+        &mut env,
+        scheme_id,
+        pubkey,
+        round,
+        previous_signature,
+        signature,
+    ) {
+        Ok(true) => JNI_TRUE,
+        Ok(false) => JNI_FALSE,
+        Err(err) => {
+            throw(&mut env, err);
+            JNI_FALSE
+        }
+    }
+}
+
+fn verify_beacon_impl(
+    env: &mut JNIEnv,
+    scheme_id: JString,
+    pubkey: JByteArray,
+    round: jlong,
+    previous_signature: JByteArray,
+    signature: JByteArray,
+) -> Result<bool, VerifyJniError> {
+    let scheme_id: String = env.get_string(&scheme_id)?.into();
+    let pubkey = env.convert_byte_array(pubkey)?;
+    let previous_signature = env.convert_byte_array(previous_signature)?;
+    let signature = env.convert_byte_array(signature)?;
+    verify_beacon(&scheme_id, &pubkey, round, &previous_signature, &signature)
+}
+
+/// The JVM independent part of `verifyBeacon`
+fn verify_beacon(
+    scheme_id: &str,
+    pubkey: &[u8],
+    round: jlong,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Result<bool, VerifyJniError> {
+    let round = u64::try_from(round)
+        .map_err(|_| VerifyJniError::illegal_argument("Round must not be negative"))?;
+    let result = match Scheme::from_scheme_str(scheme_id)? {
+        Scheme::PedersenBlsChained | Scheme::PedersenBlsUnchained => {
+            G1Pubkey::from_variable(pubkey)?.verify(round, previous_signature, signature)?
+        }
+        Scheme::BlsUnchainedOnG1 => {
+            G2PubkeyFastnet::from_variable(pubkey)?.verify(round, previous_signature, signature)?
+        }
+        Scheme::BlsUnchainedG1Rfc9380 => {
+            G2PubkeyRfc::from_variable(pubkey)?.verify(round, previous_signature, signature)?
+        }
+    };
+    Ok(result)
+}
+
+/// Entry point for `com.noislabs.drandverify.DrandVerify.deriveRandomness`:
+///
+/// ```java
+/// static native byte[] deriveRandomness(byte[] signature);
+/// ```
+///
+/// Returns the 32 byte randomness of a beacon. Only call this for verified signatures.
+#[no_mangle]
+pub extern "system" fn Java_com_noislabs_drandverify_DrandVerify_deriveRandomness<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    signature: JByteArray<'local>,
+) -> jbyteArray {
+    match derive_randomness_impl(&mut env, signature) {
+        Ok(randomness) => randomness.into_raw(),
+        Err(err) => {
+            throw(&mut env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+fn derive_randomness_impl<'local>(
+    env: &mut JNIEnv<'local>,
+    signature: JByteArray<'local>,
+) -> Result<JByteArray<'local>, VerifyJniError> {
+    let signature = env.convert_byte_array(signature)?;
+    let randomness = derive_randomness(&signature);
+    Ok(env.byte_array_from_slice(&randomness)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networks::{MAINNET, QUICKNET};
+    use hex_literal::hex;
+
+    const SIG_123: [u8; 48] = hex!("b75c69d0b72a5d906e854e808ba7e2accb1542ac355ae486d591aa9d43765482e26cd02df835d3546d23c4b13e0dfc92");
+    const QUICKNET_ID: &str = "bls-unchained-g1-rfc9380";
+
+    fn illegal_argument(result: Result<bool, VerifyJniError>) -> String {
+        let err = result.expect_err("expected an error");
+        assert_eq!(err.class, ILLEGAL_ARGUMENT);
+        err.msg
+    }
+
+    #[test]
+    fn verify_beacon_works() {
+        let result = verify_beacon(QUICKNET_ID, QUICKNET.public_key, 123, &[], &SIG_123);
+        assert!(result.ok().unwrap());
+
+        // An invalid signature is a result, not an exception
+        let result = verify_beacon(QUICKNET_ID, QUICKNET.public_key, 124, &[], &SIG_123);
+        assert!(!result.ok().unwrap());
+    }
+
+    #[test]
+    fn verify_beacon_maps_invalid_inputs_to_illegal_argument() {
+        let msg = illegal_argument(verify_beacon(
+            QUICKNET_ID,
+            QUICKNET.public_key,
+            -1,
+            &[],
+            &SIG_123,
+        ));
+        assert_eq!(msg, "Round must not be negative");
+
+        let msg = illegal_argument(verify_beacon(
+            "bls-bn254-unchained-on-g1",
+            QUICKNET.public_key,
+            123,
+            &[],
+            &SIG_123,
+        ));
+        assert_eq!(
+            msg,
+            UnknownScheme {
+                scheme_id: "bls-bn254-unchained-on-g1".to_string()
+            }
+            .to_string()
+        );
+
+        // A G1 public key for a scheme with public keys on G2
+        illegal_argument(verify_beacon(
+            QUICKNET_ID,
+            MAINNET.public_key,
+            123,
+            &[],
+            &SIG_123,
+        ));
+
+        illegal_argument(verify_beacon(
+            QUICKNET_ID,
+            QUICKNET.public_key,
+            123,
+            &[],
+            &SIG_123[..47],
+        ));
+    }
+
+    #[test]
+    fn jni_errors_map_to_runtime_exception() {
+        let err = VerifyJniError::from(jni::errors::Error::NullPtr("signature"));
+        assert_eq!(err.class, RUNTIME_EXCEPTION);
+        assert_eq!(
+            err.msg,
+            jni::errors::Error::NullPtr("signature").to_string()
+        );
+    }
+}
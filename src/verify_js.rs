@@ -1,40 +1,155 @@
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
 
-use crate::{G1Pubkey, InvalidPoint, Pubkey, VerificationError};
+use crate::batch::{check_batch_size, BatchTooLarge, DEFAULT_MAX_BATCH_SIZE};
+use crate::cbor::{self, CborError};
+use crate::error_json::ErrorReport;
+use crate::randomness::{derive_randomness_versioned as derive_versioned, RandomnessVersion};
+use crate::{
+    decode_signature, derive_randomness, parse_signature, Encoding, ErrorCode, G1Pubkey,
+    InvalidPoint, ParseSignatureError, Pubkey, VerificationError,
+};
 
-struct VerifyWebError(pub String);
+/// An error that is thrown as a JS `Error` with the `code`, `kind` and `field` properties
+/// of its [`ErrorReport`]
+struct VerifyWebError {
+    code: ErrorCode,
+    field: Option<String>,
+    msg: String,
+}
 
 impl From<hex::FromHexError> for VerifyWebError {
     fn from(source: hex::FromHexError) -> Self {
-        Self(source.to_string())
+        Self {
+            code: ErrorCode::InvalidEncoding,
+            field: None,
+            msg: source.to_string(),
+        }
     }
 }
 
 impl From<InvalidPoint> for VerifyWebError {
     fn from(source: InvalidPoint) -> Self {
-        Self(source.to_string())
+        Self {
+            code: source.code(),
+            field: None,
+            msg: source.to_string(),
+        }
+    }
+}
+
+impl From<ParseSignatureError> for VerifyWebError {
+    fn from(source: ParseSignatureError) -> Self {
+        Self {
+            code: source.code(),
+            field: None,
+            msg: source.to_string(),
+        }
     }
 }
 
 impl From<VerificationError> for VerifyWebError {
     fn from(source: VerificationError) -> Self {
-        Self(source.to_string())
+        let VerificationError::InvalidPoint { field, .. } = &source;
+        Self {
+            code: source.code(),
+            field: Some(field.clone()),
+            msg: source.to_string(),
+        }
+    }
+}
+
+impl From<BatchTooLarge> for VerifyWebError {
+    fn from(source: BatchTooLarge) -> Self {
+        Self {
+            code: source.code(),
+            field: None,
+            msg: source.to_string(),
+        }
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for VerifyWebError {
+    fn from(source: serde_wasm_bindgen::Error) -> Self {
+        Self {
+            code: ErrorCode::InvalidEncoding,
+            field: None,
+            msg: source.to_string(),
+        }
+    }
+}
+
+impl From<CborError> for VerifyWebError {
+    fn from(source: CborError) -> Self {
+        Self {
+            code: ErrorCode::InvalidEncoding,
+            field: None,
+            msg: source.to_string(),
+        }
     }
 }
 
+/// Decodes a hex encoded (previous) signature, reporting errors for `field`
+fn decode_hex_signature(input: &str, field: &str) -> Result<Vec<u8>, VerifyWebError> {
+    decode_signature(input, Encoding::Hex).map_err(|err| VerifyWebError {
+        field: Some(field.to_string()),
+        ..err.into()
+    })
+}
+
+/// Decodes the public key, reporting errors for the field `public_key`
+fn parse_pubkey(pk_hex: &str) -> Result<G1Pubkey, VerifyWebError> {
+    let decoded = hex::decode(pk_hex)
+        .map_err(VerifyWebError::from)
+        .and_then(|pk| Ok(G1Pubkey::from_variable(&pk)?));
+    decoded.map_err(|err| VerifyWebError {
+        field: Some("public_key".to_string()),
+        ..err
+    })
+}
+
 impl From<VerifyWebError> for JsValue {
     fn from(source: VerifyWebError) -> JsValue {
-        JsValue::from_str(&source.0)
+        let mut report = ErrorReport::new(source.code, source.msg);
+        report.field = source.field;
+        let error = js_sys::Error::new(&report.message);
+        let field = report
+            .field
+            .as_deref()
+            .map_or(JsValue::NULL, JsValue::from_str);
+        // Setting a property on a fresh Error object cannot fail
+        for (key, value) in [
+            ("code", JsValue::from(report.code)),
+            ("kind", JsValue::from_str(report.kind)),
+            ("field", field),
+        ] {
+            let _ = js_sys::Reflect::set(&error, &JsValue::from_str(key), &value);
+        }
+        error.into()
     }
 }
 
+/// The JS representation of a successfully verified beacon.
+///
+/// This is serialized to a plain object `{ round, randomness, scheme }`
+/// with the randomness in hex.
+#[derive(Serialize)]
+struct VerifiedBeacon {
+    round: u32,
+    randomness: String,
+    scheme: &'static str,
+}
+
 /// This is the entry point from JavaScript.
 ///
 /// The argument types are chosen such that the JS binding is simple
 /// (u32 can be expressed as number, u64 cannot; strings are easier than binary data).
 ///
-/// The result type is translated to an exception in case of an error
-/// and too a boolean value in case of success.
+/// The result type is translated to an exception in case of an error,
+/// to a `{ round, randomness, scheme }` object if the beacon is valid
+/// and to `null` if the beacon is invalid.
 #[wasm_bindgen]
 #[allow(dead_code)] // exported via wasm_bindgen
 pub fn verify_beacon(
@@ -42,7 +157,7 @@ pub fn verify_beacon(
     round: u32,
     previous_signature_hex: &str,
     signature_hex: &str,
-) -> Result<bool, JsValue> {
+) -> Result<JsValue, JsValue> {
     Ok(verify_beacon_impl(
         pk_hex,
         round,
@@ -59,10 +174,226 @@ fn verify_beacon_impl(
     round: u32,
     previous_signature_hex: &str,
     signature_hex: &str,
-) -> Result<bool, VerifyWebError> {
-    let pk = G1Pubkey::from_variable(&hex::decode(pk_hex)?)?;
-    let previous_signature = hex::decode(previous_signature_hex)?;
-    let signature = hex::decode(signature_hex)?;
-    let result = pk.verify(round.into(), &previous_signature, &signature)?;
-    Ok(result)
+) -> Result<JsValue, VerifyWebError> {
+    let pk = parse_pubkey(pk_hex)?;
+    verify_with_pk(&pk, round, previous_signature_hex, signature_hex)
+}
+
+fn verify_with_pk(
+    pk: &G1Pubkey,
+    round: u32,
+    previous_signature_hex: &str,
+    signature_hex: &str,
+) -> Result<JsValue, VerifyWebError> {
+    let previous_signature = decode_hex_signature(previous_signature_hex, "previous_signature")?;
+    let signature = decode_hex_signature(signature_hex, "signature")?;
+    if !pk.verify(round.into(), &previous_signature, &signature)? {
+        return Ok(JsValue::NULL);
+    }
+    to_js(round, &previous_signature, &signature)
+}
+
+fn to_js(
+    round: u32,
+    previous_signature: &[u8],
+    signature: &[u8],
+) -> Result<JsValue, VerifyWebError> {
+    let scheme = if previous_signature.is_empty() {
+        "pedersen-bls-unchained"
+    } else {
+        "pedersen-bls-chained"
+    };
+    let verified = VerifiedBeacon {
+        round,
+        randomness: hex::encode(derive_randomness(signature)),
+        scheme,
+    };
+    Ok(serde_wasm_bindgen::to_value(&verified)?)
+}
+
+/// Verifies a beacon and returns it as a cache entry for use in Service Workers.
+///
+/// The result is a `Uint8Array` with the CBOR encoding of the verified beacon, which can
+/// be stored as the body of a `Response` in the Cache API or as a value in IndexedDB.
+/// Use [`rehydrate_beacon`] to read it back.
+/// Returns `null` if the beacon is invalid.
+#[wasm_bindgen]
+#[allow(dead_code)] // exported via wasm_bindgen
+pub fn verify_beacon_for_cache(
+    pk_hex: &str,
+    round: u32,
+    previous_signature_hex: &str,
+    signature_hex: &str,
+) -> Result<JsValue, JsValue> {
+    Ok(verify_beacon_for_cache_impl(
+        pk_hex,
+        round,
+        previous_signature_hex,
+        signature_hex,
+    )?)
+}
+
+fn verify_beacon_for_cache_impl(
+    pk_hex: &str,
+    round: u32,
+    previous_signature_hex: &str,
+    signature_hex: &str,
+) -> Result<JsValue, VerifyWebError> {
+    let pk = parse_pubkey(pk_hex)?;
+    let previous_signature = decode_hex_signature(previous_signature_hex, "previous_signature")?;
+    let signature = decode_hex_signature(signature_hex, "signature")?;
+    let verified = match pk
+        .verify_with_outcome(round.into(), &previous_signature, &signature)
+        .into_verified()
+    {
+        Some(verified) => verified,
+        None => return Ok(JsValue::NULL),
+    };
+    let entry = cbor::to_vec(&verified)?;
+    Ok(js_sys::Uint8Array::from(entry.as_slice()).into())
+}
+
+/// Reads a cache entry created by [`verify_beacon_for_cache`] and verifies it again.
+///
+/// Anything with access to the cache can write entries, so the signature is verified
+/// against the public key like in `verify_beacon`. Entries whose randomness does not match
+/// the signature are rejected. Returns a `{ round, randomness, scheme }` object like
+/// `verify_beacon`, or `null` if the beacon is invalid.
+#[wasm_bindgen]
+#[allow(dead_code)] // exported via wasm_bindgen
+pub fn rehydrate_beacon(pk_hex: &str, entry: &[u8]) -> Result<JsValue, JsValue> {
+    Ok(rehydrate_beacon_impl(pk_hex, entry)?)
+}
+
+fn rehydrate_beacon_impl(pk_hex: &str, entry: &[u8]) -> Result<JsValue, VerifyWebError> {
+    let pk = parse_pubkey(pk_hex)?;
+    let stored: crate::StoredBeacon = cbor::from_slice(entry)?;
+    let round = u32::try_from(stored.round).map_err(|_| VerifyWebError {
+        code: ErrorCode::InvalidEncoding,
+        field: None,
+        msg: "round does not fit into a JS number".to_string(),
+    })?;
+    let verified = match stored.verify(&pk).into_verified() {
+        Some(verified) => verified,
+        None => return Ok(JsValue::NULL),
+    };
+    to_js(round, verified.previous_signature(), verified.signature())
+}
+
+/// A beacon as passed in from JavaScript in batch calls.
+#[derive(Deserialize)]
+struct BeaconInput {
+    round: u32,
+    #[serde(default)]
+    previous_signature: String,
+    signature: String,
+}
+
+/// Verifies a batch of beacons in chunks of `chunk_size`.
+///
+/// `beacons` is an array of `{ round, previous_signature, signature }` objects
+/// (`previous_signature` may be omitted for unchained networks).
+///
+/// Between two chunks control is handed back to the event loop (using `setTimeout`),
+/// such that verifying thousands of rounds in a browser does not freeze the UI.
+/// This also works in web workers.
+///
+/// At most `max_batch_size` beacons are accepted (defaults to 10000 if omitted).
+/// Larger batches are rejected before anything is decoded.
+///
+/// The returned promise resolves to an array with one entry per input beacon which is
+/// either a `{ round, randomness, scheme }` object or `null` in case the beacon is invalid.
+/// It rejects at the first beacon that cannot be processed at all (e.g. invalid hex).
+#[wasm_bindgen]
+#[allow(dead_code)] // exported via wasm_bindgen
+pub async fn verify_beacons_chunked(
+    pk_hex: String,
+    beacons: JsValue,
+    chunk_size: u32,
+    max_batch_size: Option<u32>,
+) -> Result<JsValue, JsValue> {
+    let max_batch_size = max_batch_size.map_or(DEFAULT_MAX_BATCH_SIZE, |max| max as usize);
+    let length = beacons
+        .dyn_ref::<js_sys::Array>()
+        .ok_or_else(|| VerifyWebError {
+            code: ErrorCode::InvalidEncoding,
+            field: None,
+            msg: "beacons must be an array".to_string(),
+        })?
+        .length() as usize;
+    check_batch_size(length, max_batch_size).map_err(VerifyWebError::from)?;
+
+    let pk = parse_pubkey(&pk_hex)?;
+    let beacons: Vec<BeaconInput> =
+        serde_wasm_bindgen::from_value(beacons).map_err(VerifyWebError::from)?;
+    let chunk_size = (chunk_size as usize).max(1);
+
+    let out = js_sys::Array::new();
+    for (i, chunk) in beacons.chunks(chunk_size).enumerate() {
+        if i != 0 {
+            yield_to_event_loop().await?;
+        }
+        for beacon in chunk {
+            out.push(&verify_with_pk(
+                &pk,
+                beacon.round,
+                &beacon.previous_signature,
+                &beacon.signature,
+            )?);
+        }
+    }
+    Ok(out.into())
+}
+
+/// Derives the randomness of a hex encoded signature with the given version of the
+/// construction (1 is SHA-256 of the signature). Returns the randomness in hex.
+///
+/// Throws for unknown versions, so an upgrade of the derivation never changes results silently.
+#[wasm_bindgen]
+#[allow(dead_code)] // exported via wasm_bindgen
+pub fn derive_randomness_versioned(signature_hex: &str, version: u8) -> Result<String, JsValue> {
+    let version = RandomnessVersion::try_from(version).map_err(|err| VerifyWebError {
+        code: ErrorCode::InvalidEncoding,
+        field: Some("version".to_string()),
+        msg: err.to_string(),
+    })?;
+    let signature = decode_hex_signature(signature_hex, "signature")?;
+    Ok(hex::encode(derive_versioned(&signature, version)))
+}
+
+/// Decodes and checks a signature in the given encoding (`"hex"` or `"base64"`) and returns
+/// the compressed signature in hex as expected by the other functions of this module.
+#[wasm_bindgen]
+#[allow(dead_code)] // exported via wasm_bindgen
+pub fn normalize_signature(signature: &str, encoding: &str) -> Result<String, JsValue> {
+    Ok(normalize_signature_impl(signature, encoding)?)
+}
+
+fn normalize_signature_impl(signature: &str, encoding: &str) -> Result<String, VerifyWebError> {
+    let encoding = encoding
+        .parse::<Encoding>()
+        .ok()
+        .filter(|encoding| encoding.is_text())
+        .ok_or_else(|| VerifyWebError {
+            code: ErrorCode::InvalidEncoding,
+            field: Some("encoding".to_string()),
+            msg: format!("Unsupported encoding: {}. Use hex or base64.", encoding),
+        })?;
+    let signature = parse_signature(signature, encoding).map_err(|err| VerifyWebError {
+        field: Some("signature".to_string()),
+        ..err.into()
+    })?;
+    Ok(hex::encode(signature.to_bytes()))
+}
+
+/// Returns a future that resolves in the next macrotask, giving the event loop
+/// the chance to render and process user input.
+async fn yield_to_event_loop() -> Result<(), JsValue> {
+    let set_timeout: js_sys::Function =
+        js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("setTimeout"))?.dyn_into()?;
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let _ = set_timeout.call2(&JsValue::UNDEFINED, &resolve, &JsValue::from(0));
+    });
+    JsFuture::from(promise).await?;
+    Ok(())
 }